@@ -0,0 +1,185 @@
+//! Persistent build cache for `rheo compile`.
+//!
+//! After a build, the [`IncrementalState`] (the dependencies of every output
+//! and the data of compiled pages) is written to the build directory, together
+//! with a content hash of every dependency. The next build compares the hashes
+//! against the files on disk and only recompiles outputs whose inputs changed.
+//!
+//! Anything else that affects outputs (rheo version, configuration, the set of
+//! source files, page layouts, project fonts) is summarized in a fingerprint; if it differs,
+//! the cache is discarded and everything is rebuilt.
+
+use crate::config::SOURCE_DATE_EPOCH;
+use crate::incremental::IncrementalState;
+use crate::project::ProjectConfig;
+use crate::{Result, RheoError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::debug;
+use typst::utils::hash128;
+use walkdir::WalkDir;
+
+/// File name of the build cache in the build directory.
+pub const BUILD_CACHE_FILE: &str = ".rheo-cache.json";
+
+/// Contents of the build cache file (the state is borrowed when saving).
+#[derive(Serialize, Deserialize)]
+struct CacheFile<S> {
+    /// Fingerprint of everything besides dependencies that affects outputs
+    fingerprint: String,
+    /// Content hashes of the dependencies of the cached outputs
+    hashes: BTreeMap<PathBuf, String>,
+    state: S,
+}
+
+/// Compute the fingerprint of a project's build settings.
+///
+/// # Arguments
+/// * `project` - Project configuration with source files
+pub fn fingerprint(project: &ProjectConfig) -> Result<String> {
+    let config = serde_json::to_string(&project.config)
+        .map_err(|e| RheoError::invalid_data(format!("failed to serialize config: {}", e)))?;
+    // Layout templates are applied outside of Typst, so they aren't dependencies
+    let layouts: Vec<Option<Vec<u8>>> = project
+        .config
+        .html
+        .layouts
+        .iter()
+        .map(|layout| fs::read(project.root.join(&layout.template)).ok())
+        .collect();
+    // Fonts are loaded outside of the world's files, so they aren't dependencies either
+    let fonts: Vec<(PathBuf, u64, Option<SystemTime>)> = project
+        .config
+        .resolve_font_paths(&project.root)
+        .iter()
+        .flat_map(|dir| WalkDir::new(dir).sort_by_file_name())
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            metadata
+                .is_file()
+                .then(|| (entry.into_path(), metadata.len(), metadata.modified().ok()))
+        })
+        .collect();
+    // The build date can also come from the environment
+    let source_date_epoch = std::env::var(SOURCE_DATE_EPOCH).ok();
+    Ok(format!(
+        "{:032x}",
+        hash128(&(
+            env!("CARGO_PKG_VERSION"),
+            config,
+            &project.typ_files,
+            layouts,
+            fonts,
+            source_date_epoch
+        ))
+    ))
+}
+
+/// Load the build cache.
+///
+/// # Arguments
+/// * `path` - Location of the build cache file
+/// * `fingerprint` - Fingerprint of the current build settings
+///
+/// # Returns
+/// The state of the previous build and the dependencies that changed since, or
+/// an empty state and None (rebuild everything) if there is no usable cache.
+pub fn load(path: &Path, fingerprint: &str) -> (IncrementalState, Option<Vec<PathBuf>>) {
+    let cache = fs::read(path)
+        .ok()
+        .and_then(|data| serde_json::from_slice::<CacheFile<IncrementalState>>(&data).ok());
+    let Some(cache) = cache else {
+        debug!(path = %path.display(), "no usable build cache");
+        return (IncrementalState::new(), None);
+    };
+    if cache.fingerprint != fingerprint {
+        debug!(path = %path.display(), "build settings changed, ignoring build cache");
+        return (IncrementalState::new(), None);
+    }
+
+    let changed: Vec<PathBuf> = cache
+        .hashes
+        .into_iter()
+        .filter(|(dep, hash)| file_hash(dep).as_ref() != Some(hash))
+        .map(|(dep, _)| dep)
+        .collect();
+    debug!(changed = changed.len(), "loaded build cache");
+    (cache.state, Some(changed))
+}
+
+/// Save the build cache.
+///
+/// # Arguments
+/// * `path` - Location of the build cache file
+/// * `fingerprint` - Fingerprint of the build settings
+/// * `state` - State after the build
+///
+/// # Errors
+/// Returns an error if the cache file can't be written
+pub fn save(path: &Path, fingerprint: &str, state: &IncrementalState) -> Result<()> {
+    let hashes = state
+        .dependency_paths()
+        .into_iter()
+        .filter_map(|dep| Some((dep.to_path_buf(), file_hash(dep)?)))
+        .collect();
+    let cache = CacheFile {
+        fingerprint: fingerprint.to_string(),
+        hashes,
+        state,
+    };
+    let data = serde_json::to_vec(&cache)
+        .map_err(|e| RheoError::invalid_data(format!("failed to serialize build cache: {}", e)))?;
+
+    fs::write(path, data)
+        .map_err(|e| RheoError::io(e, format!("writing build cache {:?}", path)))?;
+    debug!(path = %path.display(), "saved build cache");
+    Ok(())
+}
+
+/// Hash of a file's contents, or None if it can't be read.
+fn file_hash(path: &Path) -> Option<String> {
+    let data = fs::read(path).ok()?;
+    Some(format!("{:032x}", hash128(&data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OutputFormat;
+    use crate::incremental::OutputKey;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_reports_changed_dependencies() {
+        let temp = TempDir::new().unwrap();
+        let a = temp.path().join("a.typ");
+        let b = temp.path().join("b.typ");
+        std::fs::write(&a, "= A\n").unwrap();
+        std::fs::write(&b, "= B\n").unwrap();
+        let cache_path = temp.path().join(BUILD_CACHE_FILE);
+
+        let mut state = IncrementalState::new();
+        state.record(
+            OutputKey::Page(OutputFormat::Html, a.clone()),
+            [a.clone(), b.clone()],
+        );
+        save(&cache_path, "settings", &state).unwrap();
+
+        let (_, changed) = load(&cache_path, "settings");
+        assert_eq!(changed, Some(vec![]));
+
+        std::fs::write(&b, "= B changed\n").unwrap();
+        let (state, changed) = load(&cache_path, "settings");
+        assert_eq!(changed, Some(vec![b.clone()]));
+        let key = OutputKey::Page(OutputFormat::Html, a);
+        assert!(state.needs_rebuild(&key, changed.as_deref()));
+
+        // Other build settings discard the cache
+        let (_, changed) = load(&cache_path, "other settings");
+        assert_eq!(changed, None);
+    }
+}
@@ -1,11 +1,24 @@
 use crate::CompilationResults;
+use crate::build_cache::{self, BUILD_CACHE_FILE};
 use crate::compile::RheoCompileOptions;
-use crate::config::{EpubOptions, HtmlOptions, SpineConfig};
+use crate::config::{EpubOptions, HtmlOptions, OutputLayout, SpineConfig, build_glob_set};
+use crate::fonts::{self, FontCache};
+use crate::formats::html::feed::{self, FeedItem};
+use crate::formats::html::search::{self, SearchEntry};
+use crate::formats::html::sitemap::{self, SitemapPage};
+use crate::formats::pdf::{DocumentTitle, sanitize_label_name};
 use crate::formats::{epub, html, pdf};
+use crate::incremental::{IncrementalState, OutputKey, PageRecord};
+use crate::postprocess::PageLayouts;
+use crate::postprocess::site_nav::{NavEntry, SiteNav, relative_href};
+use crate::reticulate::parser::find_page_label;
 use crate::reticulate::spine::generate_spine;
-use crate::{OutputFormat, Result, open_all_files_in_folder};
+use crate::world::{FormatWorlds, TARGET_INPUT, WorldSettings};
+use crate::{OutputFormat, PageMeta, Result, open_all_files_in_folder};
 use clap::{Parser, Subcommand};
-use std::collections::HashSet;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use tracing::{debug, error, info, warn};
 
@@ -27,12 +40,242 @@ impl FormatFlags {
 enum CompilationMode<'a> {
     /// Fresh compilation (creates new World for each file)
     Fresh { root: PathBuf },
-    /// Incremental compilation (reuses existing World)
+    /// Incremental compilation (reuses the existing World of each format)
     Incremental {
-        world: &'a mut crate::world::RheoWorld,
+        worlds: &'a mut FormatWorlds,
+        /// Dependencies of the outputs of previous builds
+        state: &'a mut IncrementalState,
+        /// Paths changed since the last build, or None to rebuild everything
+        changed: Option<&'a [PathBuf]>,
     },
 }
 
+impl<'a> CompilationMode<'a> {
+    /// Paths changed since the last build, or None if everything has to be rebuilt
+    fn changed(&self) -> Option<&'a [PathBuf]> {
+        match self {
+            CompilationMode::Fresh { .. } => None,
+            CompilationMode::Incremental { changed, .. } => *changed,
+        }
+    }
+
+    /// Check whether an output has to be compiled (always true for fresh compilation)
+    fn needs_rebuild(&self, key: &OutputKey, changed: Option<&[PathBuf]>) -> bool {
+        match self {
+            CompilationMode::Fresh { .. } => true,
+            CompilationMode::Incremental { state, .. } => state.needs_rebuild(key, changed),
+        }
+    }
+
+    /// Root directory passed to the compile functions of per-file jobs
+    fn job_root(&self, project: &crate::project::ProjectConfig) -> PathBuf {
+        match self {
+            CompilationMode::Fresh { root } => root.clone(),
+            CompilationMode::Incremental { .. } => project.root.clone(),
+        }
+    }
+
+    /// World of a job compiling a file (incremental mode only)
+    ///
+    /// Each job gets a fork of the format's World: it shares fonts, packages and
+    /// the library, but has its own main file and file cache, so jobs can run in
+    /// parallel and the files each job read are its output's dependencies.
+    fn job_world(
+        &mut self,
+        format: Option<OutputFormat>,
+        main_file: &Path,
+    ) -> Result<Option<crate::world::RheoWorld>> {
+        match self {
+            CompilationMode::Fresh { .. } => Ok(None),
+            CompilationMode::Incremental { worlds, .. } => {
+                let mut world = worlds.get(format).fork();
+                world.set_main(main_file)?;
+                Ok(Some(world))
+            }
+        }
+    }
+
+    /// Record the files read by the compilation of an output as its dependencies,
+    /// or forget them if the compilation failed (None)
+    fn record_dependencies(&mut self, key: OutputKey, dependencies: Option<Vec<PathBuf>>) {
+        if let CompilationMode::Incremental { state, .. } = self {
+            match dependencies {
+                Some(dependencies) => state.record(key, dependencies),
+                None => state.forget(&key),
+            }
+        }
+    }
+}
+
+/// A per-file output compiled by a (possibly parallel) job
+struct CompileJob {
+    /// Index of the source file in the project's files
+    index: usize,
+    /// Source file
+    file: PathBuf,
+    /// Output file
+    output_path: PathBuf,
+    /// Root directory for the compile options
+    root: PathBuf,
+    /// World of the job in incremental mode
+    world: Option<crate::world::RheoWorld>,
+    /// Settings of the world created in fresh mode
+    settings: WorldSettings,
+    /// HTML page options (None compiles a PDF)
+    html: Option<HtmlJob>,
+}
+
+/// HTML-specific part of a compile job
+struct HtmlJob {
+    options: HtmlOptions,
+    href: String,
+    /// Whether to build the page's search index entry
+    search: bool,
+}
+
+/// Result of a compile job
+struct JobOutcome {
+    index: usize,
+    file: PathBuf,
+    format: OutputFormat,
+    /// Page data of compiled HTML pages
+    result: Result<Option<PageRecord>>,
+    /// Files read by the compilation (incremental mode)
+    dependencies: Vec<PathBuf>,
+    /// Log and diagnostic output of the job, replayed in job order
+    output: crate::logging::CapturedOutput,
+}
+
+impl CompileJob {
+    /// Compile the output, capturing the job's output.
+    fn run(mut self) -> JobOutcome {
+        let format = match self.html {
+            Some(_) => OutputFormat::Html,
+            None => OutputFormat::Pdf,
+        };
+        let (result, output) = crate::logging::capture(|| {
+            let options = match &mut self.world {
+                Some(world) => RheoCompileOptions::incremental(
+                    &self.file,
+                    &self.output_path,
+                    &self.root,
+                    world,
+                ),
+                None => RheoCompileOptions::new(&self.file, &self.output_path, &self.root)
+                    .with_settings(std::mem::take(&mut self.settings)),
+            };
+            match self.html {
+                None => pdf::compile_pdf_new(options, None).map(|()| None),
+                Some(html) => html::compile_html_new(options, html.options).map(|document| {
+                    // Front matter was already validated while compiling the page
+                    let meta = PageMeta::query(&document.introspector).unwrap_or_default();
+                    let item = FeedItem::from_document(&document, &meta, &self.file, html.href);
+                    let search_entry = html
+                        .search
+                        .then(|| SearchEntry::from_document(&document, &item.href, &item.title));
+                    Some(PageRecord {
+                        feed_item: item,
+                        search_entry,
+                    })
+                }),
+            }
+        });
+        JobOutcome {
+            index: self.index,
+            file: self.file,
+            format,
+            result,
+            dependencies: self
+                .world
+                .map(|world| world.dependencies())
+                .unwrap_or_default(),
+            output,
+        }
+    }
+}
+
+/// Set the number of documents compiled in parallel.
+///
+/// # Arguments
+/// * `jobs` - Number of parallel jobs, or None for one per CPU
+///
+/// # Errors
+/// Returns an error if the thread pool can't be created
+fn init_jobs(jobs: Option<NonZeroUsize>) -> Result<()> {
+    let Some(jobs) = jobs else {
+        return Ok(());
+    };
+    debug!(jobs, "compiling with parallel jobs");
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.get())
+        .build_global()
+        .map_err(|e| {
+            crate::RheoError::project_config(format!(
+                "failed to start {} compile jobs: {}",
+                jobs, e
+            ))
+        })
+}
+
+/// Parse a `--input` argument of the form `key=value`.
+fn parse_input(arg: &str) -> std::result::Result<(String, String), String> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, found '{}'", arg))?;
+    if key.is_empty() {
+        return Err("input key must not be empty".to_string());
+    }
+    if key == TARGET_INPUT {
+        return Err(format!("input '{}' is set by rheo", TARGET_INPUT));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// CLI build settings (what the user passed on top of rheo.toml)
+#[derive(Debug, Clone)]
+struct SettingsFlags {
+    /// `--input` values, in order
+    inputs: Vec<(String, String)>,
+    /// `--font-path` directories (relative to the working directory)
+    font_paths: Vec<PathBuf>,
+    /// `--offline`
+    offline: bool,
+}
+
+impl SettingsFlags {
+    /// Merge the flags into the project's configuration.
+    ///
+    /// Inputs override configured `[inputs]`, font directories are searched
+    /// before configured `font_paths`, and `--offline` enables offline mode.
+    ///
+    /// # Arguments
+    /// * `project` - Project whose configuration receives the settings
+    fn apply(&self, project: &mut crate::project::ProjectConfig) {
+        project.config.inputs.extend(self.inputs.iter().cloned());
+        for (key, value) in &project.config.inputs {
+            debug!(key = %key, value = %value, "sys.inputs");
+        }
+
+        let font_paths = self.font_paths.iter().map(|dir| {
+            std::path::absolute(dir)
+                .unwrap_or_else(|_| dir.clone())
+                .to_string_lossy()
+                .into_owned()
+        });
+        project.config.font_paths.splice(0..0, font_paths);
+        for dir in &project.config.font_paths {
+            debug!(dir = %dir, "font path");
+        }
+
+        if self.offline {
+            project.config.offline = true;
+        }
+        if project.config.offline {
+            debug!("offline mode, packages are never downloaded");
+        }
+    }
+}
+
 /// Pre-compiled setup context for compilation commands
 struct CompilationContext {
     /// Loaded project configuration
@@ -116,6 +359,30 @@ pub enum Commands {
         /// Compile to EPUB only
         #[arg(long)]
         epub: bool,
+
+        /// Include draft documents (from `drafts` globs or `draft: true` metadata)
+        #[arg(long)]
+        drafts: bool,
+
+        /// Number of documents to compile in parallel (defaults to the number of CPUs)
+        #[arg(short, long, value_name = "N")]
+        jobs: Option<NonZeroUsize>,
+
+        /// Add a value to `sys.inputs` (overrides `[inputs]` in rheo.toml)
+        #[arg(long = "input", value_name = "KEY=VALUE", value_parser = parse_input)]
+        inputs: Vec<(String, String)>,
+
+        /// Add a directory to search for fonts (before `font_paths` in rheo.toml)
+        #[arg(long = "font-path", value_name = "DIR")]
+        font_paths: Vec<PathBuf>,
+
+        /// Never download packages (only use vendored and cached packages)
+        #[arg(long)]
+        offline: bool,
+
+        /// Rebuild all outputs, even if their inputs are unchanged since the last build
+        #[arg(long)]
+        force: bool,
     },
 
     /// Watch Typst documents and recompile on changes
@@ -146,6 +413,37 @@ pub enum Commands {
         /// Open output in appropriate viewer (HTML opens in browser with live reload)
         #[arg(long)]
         open: bool,
+
+        /// Number of documents to compile in parallel (defaults to the number of CPUs)
+        #[arg(short, long, value_name = "N")]
+        jobs: Option<NonZeroUsize>,
+
+        /// Add a value to `sys.inputs` (overrides `[inputs]` in rheo.toml)
+        #[arg(long = "input", value_name = "KEY=VALUE", value_parser = parse_input)]
+        inputs: Vec<(String, String)>,
+
+        /// Add a directory to search for fonts (before `font_paths` in rheo.toml)
+        #[arg(long = "font-path", value_name = "DIR")]
+        font_paths: Vec<PathBuf>,
+
+        /// Never download packages (only use vendored and cached packages)
+        #[arg(long)]
+        offline: bool,
+    },
+
+    /// Copy every Typst package the project imports into its packages_dir
+    Vendor {
+        /// Path to project directory or single .typ file (defaults to current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Path to custom rheo.toml config file
+        #[arg(long, value_name = "PATH")]
+        config: Option<PathBuf>,
+
+        /// Never download packages (only copy packages from Typst's package directories)
+        #[arg(long)]
+        offline: bool,
     },
 
     /// Clean build artifacts for a project
@@ -212,15 +510,92 @@ fn resolve_build_dir(
     }
 }
 
-/// Helper to extract output filename from .typ file path
-fn get_output_filename(typ_file: &std::path::Path) -> Result<String> {
-    typ_file
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .map(|s| s.to_string())
-        .ok_or_else(|| {
-            crate::RheoError::project_config(format!("invalid .typ filename: {:?}", typ_file))
+/// Compute the output path of a .typ file relative to a format's output directory
+///
+/// The returned path keeps the `.typ` extension; callers swap it for the format's
+/// extension with [`Path::with_extension`].
+///
+/// # Arguments
+/// * `typ_file` - Source file
+/// * `content_dir` - Directory the nested layout mirrors
+/// * `layout` - Output layout
+///
+/// # Returns
+/// * Nested: path relative to `content_dir` (e.g. `posts/intro.typ`)
+/// * Flat: the file name only (e.g. `intro.typ`)
+fn get_output_path(typ_file: &Path, content_dir: &Path, layout: OutputLayout) -> Result<PathBuf> {
+    let file_name = typ_file.file_name().ok_or_else(|| {
+        crate::RheoError::project_config(format!("invalid .typ filename: {:?}", typ_file))
+    })?;
+
+    match layout {
+        OutputLayout::Nested => Ok(typ_file
+            .strip_prefix(content_dir)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| PathBuf::from(file_name))),
+        OutputLayout::Flat => Ok(PathBuf::from(file_name)),
+    }
+}
+
+/// Compute the URL path of a file's HTML output relative to the HTML output directory
+///
+/// Uses forward slashes on every platform, e.g. `posts/intro.html`.
+fn get_html_href(typ_file: &Path, content_dir: &Path, layout: OutputLayout) -> Result<String> {
+    Ok(get_output_path(typ_file, content_dir, layout)?
+        .with_extension("html")
+        .to_string_lossy()
+        .replace('\\', "/"))
+}
+
+/// Ensure no two files are written to the same output path
+///
+/// Only the flat layout can produce collisions, e.g. `posts/intro.typ` and
+/// `docs/intro.typ` both map to `intro.html`.
+///
+/// # Errors
+/// Returns an error listing every colliding output and the sources mapping to it
+fn check_output_collisions<'a>(
+    files: impl IntoIterator<Item = &'a PathBuf>,
+    content_dir: &Path,
+    layout: OutputLayout,
+) -> Result<()> {
+    let mut outputs: BTreeMap<PathBuf, Vec<&Path>> = BTreeMap::new();
+    for file in files {
+        let output = get_output_path(file, content_dir, layout)?;
+        outputs.entry(output).or_default().push(file);
+    }
+
+    let collisions: Vec<String> = outputs
+        .iter()
+        .filter(|(_, sources)| sources.len() > 1)
+        .map(|(output, sources)| {
+            let mut sources: Vec<String> = sources
+                .iter()
+                .map(|source| {
+                    source
+                        .strip_prefix(content_dir)
+                        .unwrap_or(source)
+                        .display()
+                        .to_string()
+                })
+                .collect();
+            sources.sort();
+            format!(
+                "{} ← {}",
+                output.with_extension("").display(),
+                sources.join(", ")
+            )
         })
+        .collect();
+
+    if collisions.is_empty() {
+        Ok(())
+    } else {
+        Err(crate::RheoError::project_config(format!(
+            "layout = \"flat\" maps multiple files to the same output (use the default nested layout or rename them):\n  {}",
+            collisions.join("\n  ")
+        )))
+    }
 }
 
 /// Determine which formats should be compiled for a given file.
@@ -240,6 +615,85 @@ fn get_per_file_formats(
         .collect()
 }
 
+/// Build the site navigation from the HTML spine.
+///
+/// Pages are listed in vertebrae order and titled from their `#set document(title: ...)`,
+/// falling back to a readable version of the filename. Other pages reference a page
+/// by the label on its document set rule (`#set document(..) <about>`), or by its
+/// file stem like in merged PDFs.
+///
+/// # Returns
+/// * `Ok(None)` if no HTML spine is configured
+/// * `Ok(Some(SiteNav))` with one entry per spine file
+fn build_site_nav(
+    project: &crate::project::ProjectConfig,
+    content_dir: &Path,
+    layout: OutputLayout,
+) -> Result<Option<SiteNav>> {
+    let Some(spine) = &project.config.html.spine else {
+        return Ok(None);
+    };
+
+    let spine_files = generate_spine(
+        content_dir,
+        Some(spine as &dyn SpineConfig),
+        false,
+        &project.filter,
+    )?;
+    let mut entries = Vec::with_capacity(spine_files.len());
+    for file in spine_files {
+        let source = std::fs::read_to_string(&file)
+            .map_err(|e| crate::RheoError::io(e, format!("reading {}", file.display())))?;
+        let stem = file
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let href = get_html_href(&file, content_dir, layout)?;
+        let label = find_page_label(&typst::syntax::Source::detached(&source))
+            .map(|(label, _)| label)
+            .unwrap_or_else(|| sanitize_label_name(&stem));
+        entries.push(NavEntry {
+            label,
+            title: DocumentTitle::from_source(source, stem).extract(),
+            source: file,
+            href,
+        });
+    }
+
+    Ok(Some(SiteNav {
+        title: spine.title.clone(),
+        entries,
+    }))
+}
+
+/// Delete the per-file outputs of source files that were removed from the project
+///
+/// Used in watch mode, so deleted or renamed pages do not linger in the build directory.
+/// Merged outputs (EPUB, merged PDF) are regenerated from the spine instead.
+///
+/// # Arguments
+/// * `project` - Project the files were part of (determines the output layout)
+/// * `output_config` - Output directories
+/// * `removed` - Removed .typ files
+fn remove_stale_outputs(
+    project: &crate::project::ProjectConfig,
+    output_config: &crate::output::OutputConfig,
+    removed: &[PathBuf],
+) -> Result<()> {
+    let content_dir = project
+        .config
+        .resolve_content_dir(&project.root)
+        .unwrap_or_else(|| project.root.clone());
+    for file in removed {
+        let output_rel = get_output_path(file, &content_dir, project.config.layout)?;
+        let count = output_config.remove_outputs(&output_rel)?;
+        if count > 0 {
+            info!(file = %file.display(), outputs = count, "removed outputs of deleted file");
+        }
+    }
+    Ok(())
+}
+
 /// Returns the set of files to compile for a given format based on spine config.
 /// If no spine is configured, returns all project files.
 fn get_files_for_format<'a>(
@@ -261,8 +715,12 @@ fn get_files_for_format<'a>(
             None => Ok(project.typ_files.iter().collect()),
             Some(spine) if spine.merge == Some(true) => Ok(HashSet::new()),
             Some(spine) => {
-                let spine_files =
-                    generate_spine(&content_dir, Some(spine as &dyn SpineConfig), false)?;
+                let spine_files = generate_spine(
+                    &content_dir,
+                    Some(spine as &dyn SpineConfig),
+                    false,
+                    &project.filter,
+                )?;
                 let spine_set: HashSet<_> = spine_files.iter().collect();
                 Ok(project
                     .typ_files
@@ -274,8 +732,12 @@ fn get_files_for_format<'a>(
         OutputFormat::Html => match &project.config.html.spine {
             None => Ok(project.typ_files.iter().collect()),
             Some(spine) => {
-                let spine_files =
-                    generate_spine(&content_dir, Some(spine as &dyn SpineConfig), false)?;
+                let spine_files = generate_spine(
+                    &content_dir,
+                    Some(spine as &dyn SpineConfig),
+                    false,
+                    &project.filter,
+                )?;
                 let spine_set: HashSet<_> = spine_files.iter().collect();
                 Ok(project
                     .typ_files
@@ -325,180 +787,398 @@ fn perform_compilation<'a>(
     let pdf_files = get_files_for_format(OutputFormat::Pdf, project, &per_file_formats)?;
     let html_files = get_files_for_format(OutputFormat::Html, project, &per_file_formats)?;
 
-    // Copy HTML assets (style.css) if HTML compilation is requested
+    let content_dir = project
+        .config
+        .resolve_content_dir(&project.root)
+        .unwrap_or_else(|| project.root.clone());
+    let layout = project.config.layout;
+
+    // Refuse to silently overwrite outputs in the flat layout
+    check_output_collisions(pdf_files.union(&html_files).copied(), &content_dir, layout)?;
+
+    // Custom inputs and fonts of worlds created for fresh compilation
+    let settings = project.world_settings()?;
+
+    // Copy HTML assets (style.css and configured static assets) if HTML compilation is requested
+    let copy_html_fonts =
+        !html_files.is_empty() && project.config.html.copy_fonts && !settings.font_paths.is_empty();
     if !html_files.is_empty() {
         output_config.copy_html_assets(project.style_css.as_deref())?;
+        output_config.copy_static_assets(&content_dir, &project.config.html.assets)?;
+    }
+    if copy_html_fonts {
+        let project_fonts = FontCache::with_font_paths(&settings.font_paths).project_fonts();
+        fonts::copy_web_fonts(&project_fonts, &output_config.html_dir)?;
     }
 
-    // Per-file compilation
-    for typ_file in &project.typ_files {
-        let filename = get_output_filename(typ_file)?;
+    // Site navigation is shared by all HTML pages
+    let site_nav = if html_files.is_empty() {
+        None
+    } else {
+        build_site_nav(project, &content_dir, layout)?
+    };
+
+    // Page layouts are loaded once and selected per file
+    let page_layouts = if html_files.is_empty() {
+        PageLayouts::default()
+    } else {
+        PageLayouts::load(&project.root, &project.config.html.layouts)?
+    };
+
+    // Pages matching the feed patterns (relative to the content directory) are
+    // collected as they compile
+    let feed_config = project.config.html.feed.as_ref();
+    let feed_matcher = feed_config
+        .map(|feed| build_glob_set("html.feed.entries", &feed.entries))
+        .transpose()?;
+    let mut feed_items = Vec::new();
+    let mut page_dates = HashMap::new();
+
+    // Every compiled page is indexed when search is enabled
+    let search_config = project.config.html.search.as_ref();
+    let mut search_entries = Vec::new();
+
+    // In incremental mode, only outputs whose dependencies changed are compiled.
+    // The site navigation and the layouts are part of every page, so a changed
+    // navigation or layout template rebuilds all HTML pages.
+    let changed = mode.changed();
+    let layout_changed = changed.is_some_and(|changed| {
+        let templates = project.config.resolve_layout_templates(&project.root);
+        changed.iter().any(|path| templates.contains(path))
+    });
+    let html_changed = match &mut mode {
+        CompilationMode::Incremental { state, .. } => {
+            if state.update_nav(site_nav.as_ref()) || layout_changed {
+                None
+            } else {
+                changed
+            }
+        }
+        CompilationMode::Fresh { .. } => None,
+    };
+
+    // Plan the per-file outputs: outputs that are up to date reuse their page
+    // data, the others become compile jobs
+    let mut jobs = Vec::new();
+    let mut pages = BTreeMap::new();
+    for (index, typ_file) in project.typ_files.iter().enumerate() {
+        let output_rel = get_output_path(typ_file, &content_dir, layout)?;
 
         // Skip files not in either filtered set
         if !pdf_files.contains(typ_file) && !html_files.contains(typ_file) {
             continue;
         }
 
-        // For incremental mode, prepare the World for compiling this specific file
-        // 1. set_main() tells the World which file we're compiling (updates main file ID)
-        // 2. reset() clears file caches while preserving fonts/packages (enables incremental compilation)
-        if let CompilationMode::Incremental { world } = &mut mode {
-            world.set_main(typ_file)?;
-            world.reset();
+        // Outputs are also compiled if they were deleted since the last build
+        let pdf_path = output_config
+            .pdf_dir
+            .join(&output_rel)
+            .with_extension("pdf");
+        let html_path = output_config
+            .html_dir
+            .join(&output_rel)
+            .with_extension("html");
+        let pdf_key = OutputKey::Page(OutputFormat::Pdf, typ_file.clone());
+        let html_key = OutputKey::Page(OutputFormat::Html, typ_file.clone());
+        let compile_pdf = pdf_files.contains(typ_file)
+            && (mode.needs_rebuild(&pdf_key, changed) || !pdf_path.is_file());
+        let compile_html = html_files.contains(typ_file)
+            && (mode.needs_rebuild(&html_key, html_changed) || !html_path.is_file());
+        if pdf_files.contains(typ_file) && !compile_pdf {
+            results.record_fresh(OutputFormat::Pdf);
         }
 
         // Compile to PDF (per-file mode)
-        if pdf_files.contains(typ_file) {
-            let output_path = output_config.pdf_dir.join(&filename).with_extension("pdf");
-            let options = match &mode {
-                CompilationMode::Fresh { root } => {
-                    RheoCompileOptions::new(typ_file, &output_path, root)
-                }
-                CompilationMode::Incremental { .. } => {
-                    if let CompilationMode::Incremental { world } = &mut mode {
-                        RheoCompileOptions::incremental(
-                            typ_file,
-                            &output_path,
-                            &project.root,
-                            world,
-                        )
-                    } else {
-                        unreachable!()
-                    }
-                }
+        if compile_pdf {
+            crate::output::ensure_parent_dir(&pdf_path)?;
+            jobs.push(CompileJob {
+                index,
+                file: typ_file.clone(),
+                output_path: pdf_path,
+                root: mode.job_root(project),
+                world: mode.job_world(Some(OutputFormat::Pdf), typ_file)?,
+                settings: settings.clone(),
+                html: None,
+            });
+        }
+
+        // Compile to HTML, or reuse the page data of the previous build
+        if compile_html {
+            crate::output::ensure_parent_dir(&html_path)?;
+            let href = get_html_href(typ_file, &content_dir, layout)?;
+            // Get HTML options from config, loading copied fonts before other stylesheets
+            let stylesheets = copy_html_fonts
+                .then(|| relative_href(&href, fonts::FONTS_CSS_FILE))
+                .into_iter()
+                .chain(project.config.html.stylesheets.iter().cloned())
+                .collect();
+            let html_options = HtmlOptions {
+                stylesheets,
+                fonts: project.config.html.fonts.clone(),
+                layout,
+                nav: site_nav.clone(),
+                page_layout: page_layouts
+                    .select(typ_file.strip_prefix(&content_dir).unwrap_or(typ_file))
+                    .cloned(),
+                search_script: search_config
+                    .filter(|search| search.widget)
+                    .map(|_| relative_href(&href, search::SEARCH_WIDGET_FILE)),
             };
-            match pdf::compile_pdf_new(options, None) {
-                Ok(_) => results.record_success(OutputFormat::Pdf),
-                Err(e) => {
-                    error!(file = %typ_file.display(), error = %e, "PDF compilation failed");
-                    results.record_failure(OutputFormat::Pdf);
-                }
+            jobs.push(CompileJob {
+                index,
+                file: typ_file.clone(),
+                output_path: html_path,
+                root: mode.job_root(project),
+                world: mode.job_world(Some(OutputFormat::Html), typ_file)?,
+                settings: settings.clone(),
+                html: Some(HtmlJob {
+                    options: html_options,
+                    href,
+                    search: search_config.is_some(),
+                }),
+            });
+        } else if html_files.contains(typ_file) {
+            results.record_fresh(OutputFormat::Html);
+            if let CompilationMode::Incremental { state, .. } = &mode
+                && let Some(page) = state.page(typ_file)
+            {
+                pages.insert(index, page.clone());
             }
         }
+    }
 
-        // Compile to HTML
-        if html_files.contains(typ_file) {
-            let output_path = output_config
-                .html_dir
-                .join(&filename)
-                .with_extension("html");
-            let options = match &mode {
-                CompilationMode::Fresh { root } => {
-                    RheoCompileOptions::new(typ_file, &output_path, root)
-                }
-                CompilationMode::Incremental { .. } => {
-                    if let CompilationMode::Incremental { world } = &mut mode {
-                        RheoCompileOptions::incremental(
-                            typ_file,
-                            &output_path,
-                            &project.root,
-                            world,
-                        )
-                    } else {
-                        unreachable!()
+    // Compile the jobs in parallel. Outcomes come back in job order, so results
+    // and output are reported exactly as with a sequential build.
+    let outcomes: Vec<JobOutcome> = jobs.into_par_iter().map(CompileJob::run).collect();
+    for outcome in outcomes {
+        outcome.output.replay();
+        let key = OutputKey::Page(outcome.format, outcome.file.clone());
+        match outcome.result {
+            Ok(page) => {
+                results.record_success(outcome.format);
+                mode.record_dependencies(key, Some(outcome.dependencies));
+                if let Some(page) = page {
+                    if let CompilationMode::Incremental { state, .. } = &mut mode {
+                        state.record_page(&outcome.file, page.clone());
                     }
+                    pages.insert(outcome.index, page);
                 }
-            };
-            // Get HTML options from config
-            let html_options = HtmlOptions {
-                stylesheets: project.config.html.stylesheets.clone(),
-                fonts: project.config.html.fonts.clone(),
-            };
-            match html::compile_html_new(options, html_options) {
-                Ok(_) => results.record_success(OutputFormat::Html),
-                Err(e) => {
-                    error!(file = %typ_file.display(), error = %e, "HTML compilation failed");
-                    results.record_failure(OutputFormat::Html);
+            }
+            Err(e) => {
+                match outcome.format {
+                    OutputFormat::Pdf => {
+                        error!(file = %outcome.file.display(), error = %e, "PDF compilation failed")
+                    }
+                    _ => {
+                        error!(file = %outcome.file.display(), error = %e, "HTML compilation failed")
+                    }
                 }
+                results.record_failure(outcome.format);
+                mode.record_dependencies(key, None);
             }
         }
     }
 
+    // Collect the page data of all pages, in file order
+    for (index, page) in pages {
+        let typ_file = &project.typ_files[index];
+        if let Some(entry) = page.search_entry {
+            search_entries.push(entry);
+        }
+        if let Some(date) = page.feed_item.date {
+            page_dates.insert(typ_file.clone(), date.date_naive());
+        }
+        let rel_path = typ_file.strip_prefix(&content_dir).unwrap_or(typ_file);
+        if feed_matcher
+            .as_ref()
+            .is_some_and(|matcher| matcher.is_match(rel_path))
+        {
+            feed_items.push(page.feed_item);
+        }
+    }
+
+    // Write sitemap.xml and robots.txt for the compiled HTML pages
+    let html_config = &project.config.html;
+    if !html_files.is_empty() && (html_config.base_url.is_some() || html_config.robots) {
+        let mut pages = html_files
+            .iter()
+            .map(|file| {
+                Ok(SitemapPage {
+                    source: file.to_path_buf(),
+                    href: get_html_href(file, &content_dir, layout)?,
+                    date: page_dates.get(*file).copied(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        pages.sort_by(|a, b| a.href.cmp(&b.href));
+        sitemap::write_site_files(
+            &output_config.html_dir,
+            html_config.base_url.as_deref(),
+            html_config.robots,
+            &pages,
+            settings.build_date,
+        )?;
+    }
+
+    // Write search-index.json (and the widget) for the compiled pages
+    if let Some(search_config) = search_config
+        && !html_files.is_empty()
+    {
+        search_entries.sort_by(|a, b| a.url.cmp(&b.url));
+        search::write_search_files(
+            &output_config.html_dir,
+            &search_entries,
+            search_config.widget,
+        )?;
+    }
+
+    // Write feed.xml for the compiled feed entries (validation ensures base_url is set)
+    if let (Some(feed_config), Some(base_url)) = (feed_config, &html_config.base_url)
+        && !html_files.is_empty()
+    {
+        let title = feed_config
+            .title
+            .as_deref()
+            .or_else(|| html_config.spine.as_ref().and_then(|s| s.title.as_deref()))
+            .unwrap_or(&project.name);
+        let author = feed_config.author.as_deref().unwrap_or(&project.name);
+        feed::write_feed(
+            &output_config.html_dir,
+            title,
+            author,
+            base_url,
+            feed_items,
+            feed_config.limit,
+            settings.build_date,
+        )?;
+    }
+
     // Generate merged PDF if configured with merge = true
-    if formats.contains(&OutputFormat::Pdf)
+    let merged_key = OutputKey::Merged(OutputFormat::Pdf);
+    let merge_pdf = formats.contains(&OutputFormat::Pdf)
         && project
             .config
             .pdf
             .spine
             .as_ref()
             .and_then(|s| s.merge)
-            .unwrap_or(false)
-    {
-        let pdf_filename = format!("{}.pdf", project.name);
-        let pdf_path = output_config.pdf_dir.join(&pdf_filename);
-
+            .unwrap_or(false);
+    let pdf_path = output_config.pdf_dir.join(format!("{}.pdf", project.name));
+    if merge_pdf && !mode.needs_rebuild(&merged_key, changed) && pdf_path.is_file() {
+        results.record_fresh(OutputFormat::Pdf);
+    } else if merge_pdf {
         let compilation_root = project
             .config
             .resolve_content_dir(&project.root)
             .unwrap_or_else(|| project.root.clone());
 
-        let options = match &mode {
+        let options = match &mut mode {
             CompilationMode::Fresh { root: _ } => {
                 RheoCompileOptions::new(PathBuf::new(), &pdf_path, &compilation_root)
+                    .with_filter(project.filter.clone())
+                    .with_settings(settings.clone())
             }
-            CompilationMode::Incremental { .. } => {
-                if let CompilationMode::Incremental { world } = &mut mode {
-                    RheoCompileOptions::incremental(
-                        PathBuf::new(),
-                        &pdf_path,
-                        &compilation_root,
-                        world,
-                    )
-                } else {
-                    unreachable!()
-                }
+            CompilationMode::Incremental { worlds, .. } => {
+                // The merged document is compiled without link transformations,
+                // and only the files it reads are recorded
+                let world = worlds.get(None);
+                world.reset();
+                RheoCompileOptions::incremental(PathBuf::new(), &pdf_path, &compilation_root, world)
+                    .with_filter(project.filter.clone())
             }
         };
-        match pdf::compile_pdf_new(options, Some(&project.config.pdf)) {
+        let succeeded = match pdf::compile_pdf_new(options, Some(&project.config.pdf)) {
             Ok(_) => {
                 results.record_success(OutputFormat::Pdf);
                 info!(output = %pdf_path.display(), "PDF merge complete");
+                true
             }
             Err(e) => {
                 error!(error = %e, "PDF merge failed");
                 results.record_failure(OutputFormat::Pdf);
+                false
             }
-        }
+        };
+        let dependencies = match &mut mode {
+            CompilationMode::Incremental { worlds, .. } if succeeded => {
+                // The spine files are concatenated into the virtual main file, so
+                // the world doesn't see them as dependencies
+                let mut dependencies = worlds.get(None).dependencies();
+                dependencies.extend(generate_spine(
+                    &compilation_root,
+                    project
+                        .config
+                        .pdf
+                        .spine
+                        .as_ref()
+                        .map(|spine| spine as &dyn SpineConfig),
+                    false,
+                    &project.filter,
+                )?);
+                Some(dependencies)
+            }
+            _ => None,
+        };
+        mode.record_dependencies(merged_key, dependencies);
     }
 
     // Generate EPUB if requested
-    if formats.contains(&OutputFormat::Epub) {
-        let epub_filename = format!("{}.epub", project.name);
-        let epub_path = output_config.epub_dir.join(&epub_filename);
-
+    let epub_key = OutputKey::Merged(OutputFormat::Epub);
+    let epub_path = output_config
+        .epub_dir
+        .join(format!("{}.epub", project.name));
+    let generate_epub = formats.contains(&OutputFormat::Epub);
+    if generate_epub && !mode.needs_rebuild(&epub_key, changed) && epub_path.is_file() {
+        results.record_fresh(OutputFormat::Epub);
+    } else if generate_epub {
         let compilation_root = project
             .config
             .resolve_content_dir(&project.root)
             .unwrap_or_else(|| project.root.clone());
 
-        let options = match &mode {
+        let mut epub_options = EpubOptions::from(&project.config.epub);
+        if project.config.epub.copy_fonts {
+            epub_options.fonts = FontCache::with_font_paths(&settings.font_paths).project_fonts();
+        }
+        let result = match &mut mode {
             CompilationMode::Fresh { root: _ } => {
-                RheoCompileOptions::new(PathBuf::new(), &epub_path, &compilation_root)
+                let options =
+                    RheoCompileOptions::new(PathBuf::new(), &epub_path, &compilation_root)
+                        .with_filter(project.filter.clone())
+                        .with_settings(settings.clone());
+                epub::compile_epub_new(options, epub_options, None)
             }
-            CompilationMode::Incremental { .. } => {
-                if let CompilationMode::Incremental { world } = &mut mode {
-                    RheoCompileOptions::incremental(
-                        PathBuf::new(),
-                        &epub_path,
-                        &compilation_root,
-                        world,
-                    )
-                } else {
-                    unreachable!()
-                }
+            CompilationMode::Incremental { worlds, state, .. } => {
+                // Unchanged chapters are reused from previous builds
+                let options = RheoCompileOptions::incremental(
+                    PathBuf::new(),
+                    &epub_path,
+                    &compilation_root,
+                    worlds.get(Some(OutputFormat::Epub)),
+                )
+                .with_filter(project.filter.clone());
+                epub::compile_epub_new(options, epub_options, Some(state.epub_cache()))
             }
         };
-        let epub_options = EpubOptions::from(&project.config.epub);
-        match epub::compile_epub_new(options, epub_options) {
+        let succeeded = match result {
             Ok(_) => {
                 results.record_success(OutputFormat::Epub);
                 info!(output = %epub_path.display(), "EPUB generation complete");
+                true
             }
             Err(e) => {
                 error!(error = %e, "EPUB generation failed");
                 results.record_failure(OutputFormat::Epub);
+                false
             }
-        }
+        };
+        let dependencies = match &mut mode {
+            CompilationMode::Incremental { state, .. } if succeeded => {
+                Some(state.epub_cache().dependencies())
+            }
+            _ => None,
+        };
+        mode.record_dependencies(epub_key, dependencies);
     }
 
     // Report results with per-format summary
@@ -554,6 +1234,8 @@ impl Cli {
     /// * `config_path` - Optional custom rheo.toml path
     /// * `build_dir` - Optional custom build directory (overrides config)
     /// * `format_flags` - CLI format flags (pdf, html, epub)
+    /// * `include_drafts` - Whether draft documents are compiled
+    /// * `settings_flags` - Build settings from the command line
     ///
     /// # Returns
     /// * `CompilationContext` with all resolved settings
@@ -562,10 +1244,14 @@ impl Cli {
         config_path: Option<&Path>,
         build_dir: Option<PathBuf>,
         format_flags: FormatFlags,
+        include_drafts: bool,
+        settings_flags: &SettingsFlags,
     ) -> Result<CompilationContext> {
         // 1. Load project
         info!(path = %path.display(), "loading project");
-        let project = crate::project::ProjectConfig::from_path(path, config_path)?;
+        let mut project =
+            crate::project::ProjectConfig::from_path(path, config_path, include_drafts)?;
+        settings_flags.apply(&mut project);
         let file_word = if project.typ_files.len() == 1 {
             "file"
         } else {
@@ -613,19 +1299,67 @@ impl Cli {
                 pdf,
                 html,
                 epub,
+                drafts,
+                jobs,
+                inputs,
+                font_paths,
+                offline,
+                force,
             } => {
+                init_jobs(jobs)?;
+
                 // Setup compilation context
                 let flags = FormatFlags { pdf, html, epub };
-                let ctx =
-                    Self::setup_compilation_context(&path, config.as_deref(), build_dir, flags)?;
+                let settings_flags = SettingsFlags {
+                    inputs,
+                    font_paths,
+                    offline,
+                };
+                let ctx = Self::setup_compilation_context(
+                    &path,
+                    config.as_deref(),
+                    build_dir,
+                    flags,
+                    drafts,
+                    &settings_flags,
+                )?;
 
-                // Create compilation mode (Fresh)
-                let mode = CompilationMode::Fresh {
-                    root: ctx.compilation_root,
+                // With --force, compile everything fresh and leave the build cache alone
+                let initial_main = match ctx.project.typ_files.first() {
+                    Some(main) if !force => main,
+                    _ => {
+                        let mode = CompilationMode::Fresh {
+                            root: ctx.compilation_root,
+                        };
+                        return perform_compilation(
+                            mode,
+                            &ctx.project,
+                            &ctx.output_config,
+                            &ctx.formats,
+                        );
+                    }
                 };
 
-                // Perform compilation
-                perform_compilation(mode, &ctx.project, &ctx.output_config, &ctx.formats)
+                // Otherwise, skip outputs whose inputs are unchanged since the last build
+                let cache_path = ctx.output_config.build_dir.join(BUILD_CACHE_FILE);
+                let fingerprint = build_cache::fingerprint(&ctx.project)?;
+                let (mut state, changed) = build_cache::load(&cache_path, &fingerprint);
+                let mut worlds = FormatWorlds::new(
+                    &ctx.compilation_root,
+                    initial_main,
+                    &ctx.project.world_settings()?,
+                )?;
+                let mode = CompilationMode::Incremental {
+                    worlds: &mut worlds,
+                    state: &mut state,
+                    changed: changed.as_deref(),
+                };
+                let result =
+                    perform_compilation(mode, &ctx.project, &ctx.output_config, &ctx.formats);
+                if let Err(e) = build_cache::save(&cache_path, &fingerprint, &state) {
+                    warn!(error = %e, "failed to save build cache");
+                }
+                result
             }
             Commands::Watch {
                 path,
@@ -635,16 +1369,52 @@ impl Cli {
                 html,
                 epub,
                 open,
+                jobs,
+                inputs,
+                font_paths,
+                offline,
             } => {
-                // Setup compilation context
+                init_jobs(jobs)?;
+
+                // Setup compilation context (drafts are always previewed in watch mode)
                 let flags = FormatFlags { pdf, html, epub };
-                let ctx =
-                    Self::setup_compilation_context(&path, config.as_deref(), build_dir, flags)?;
+                let settings_flags = SettingsFlags {
+                    inputs,
+                    font_paths,
+                    offline,
+                };
+                let ctx = Self::setup_compilation_context(
+                    &path,
+                    config.as_deref(),
+                    build_dir,
+                    flags,
+                    true,
+                    &settings_flags,
+                )?;
 
-                // Perform initial compilation (Fresh mode)
+                // Create a RheoWorld per format for incremental compilation (reused across
+                // file changes), so watch output matches a fresh build of each format.
+                // Use first .typ file as initial main (will be updated for each compilation)
+                let initial_main = ctx
+                    .project
+                    .typ_files
+                    .first()
+                    .ok_or_else(|| crate::RheoError::project_config("no .typ files found"))?;
+
+                let mut worlds = FormatWorlds::new(
+                    &ctx.compilation_root,
+                    initial_main,
+                    &ctx.project.world_settings()?,
+                )?;
+                // Dependencies of each output, so changes only recompile affected outputs
+                let mut state = IncrementalState::new();
+
+                // Perform initial compilation, recording the dependencies of every output
                 info!("compiling project");
-                let mode = CompilationMode::Fresh {
-                    root: ctx.compilation_root.clone(),
+                let mode = CompilationMode::Incremental {
+                    worlds: &mut worlds,
+                    state: &mut state,
+                    changed: None,
                 };
                 if let Err(e) =
                     perform_compilation(mode, &ctx.project, &ctx.output_config, &ctx.formats)
@@ -696,31 +1466,8 @@ impl Cli {
                 use std::cell::RefCell;
                 let project_cell = RefCell::new(project);
 
-                // Create RheoWorld for incremental compilation (reused across file changes)
-                let borrowed_project = project_cell.borrow();
-                let compilation_root = borrowed_project
-                    .config
-                    .resolve_content_dir(&borrowed_project.root)
-                    .unwrap_or_else(|| borrowed_project.root.clone());
-
-                // Use first .typ file as initial main (will be updated for each compilation)
-                let initial_main = borrowed_project
-                    .typ_files
-                    .first()
-                    .ok_or_else(|| crate::RheoError::project_config("no .typ files found"))?;
-
-                // For watch mode: if compiling HTML, keep .typ links for transformation
-                // If compiling only PDF/EPUB, remove .typ links at source level
-                let output_format = if formats.contains(&OutputFormat::Html) {
-                    Some(OutputFormat::Html)
-                } else {
-                    None
-                };
-                let world =
-                    crate::world::RheoWorld::new(&compilation_root, initial_main, output_format)?;
-                drop(borrowed_project); // Release borrow before moving into RefCell
-
-                let world_cell = RefCell::new(world);
+                let worlds_cell = RefCell::new(worlds);
+                let state_cell = RefCell::new(state);
 
                 // Canonicalize build directory for reliable path comparison in watcher
                 // This prevents the watcher from triggering on its own output files
@@ -740,105 +1487,151 @@ impl Cli {
                     })?;
 
                 info!("watching for changes");
-                crate::watch::watch_project(
-                    &project_cell.borrow(),
-                    &canonical_build_dir,
-                    |event| {
-                        let result = match event {
-                            crate::watch::WatchEvent::FilesChanged => {
+                crate::watch::watch_project(&project_cell, &canonical_build_dir, |event| {
+                    let result = match event {
+                        crate::watch::WatchEvent::FilesChanged(changed) => {
+                            info!("change detected, recompiling");
+                            let mode = CompilationMode::Incremental {
+                                worlds: &mut worlds_cell.borrow_mut(),
+                                state: &mut state_cell.borrow_mut(),
+                                changed: Some(&changed),
+                            };
+                            perform_compilation(
+                                mode,
+                                &project_cell.borrow(),
+                                &output_config,
+                                &formats,
+                            )
+                        }
+                        crate::watch::WatchEvent::FilesAddedOrRemoved(changed) => {
+                            let changes = project_cell.borrow_mut().rediscover();
+                            let changed = if changes.added.is_empty() && changes.removed.is_empty()
+                            {
+                                // e.g. an editor saving by renaming a temporary file over
+                                // the original, rebuild incrementally
                                 info!("change detected, recompiling");
-                                let mode = CompilationMode::Incremental {
-                                    world: &mut world_cell.borrow_mut(),
-                                };
-                                perform_compilation(
-                                    mode,
+                                Some(changed)
+                            } else {
+                                info!(
+                                    added = changes.added.len(),
+                                    removed = changes.removed.len(),
+                                    "project files changed, recompiling"
+                                );
+                                if let Err(e) = remove_stale_outputs(
                                     &project_cell.borrow(),
                                     &output_config,
-                                    &formats,
-                                )
-                            }
-                            crate::watch::WatchEvent::ConfigChanged => {
-                                info!("configuration changed, reloading");
-                                // Reload project configuration
-                                match crate::project::ProjectConfig::from_path(
-                                    &path,
-                                    config.as_deref(),
+                                    &changes.removed,
                                 ) {
-                                    Ok(new_project) => {
-                                        *project_cell.borrow_mut() = new_project;
-                                        let borrowed = project_cell.borrow();
-                                        let file_word = if borrowed.typ_files.len() == 1 {
-                                            "file"
-                                        } else {
-                                            "files"
-                                        };
-                                        info!(name = %borrowed.name, files = borrowed.typ_files.len(), "reloaded ({} {})", borrowed.typ_files.len(), file_word);
-
-                                        // Recreate World with new configuration
-                                        let new_compilation_root = borrowed
-                                            .config
-                                            .resolve_content_dir(&borrowed.root)
-                                            .unwrap_or_else(|| borrowed.root.clone());
-                                        let new_initial_main =
-                                            borrowed.typ_files.first().ok_or_else(|| {
-                                                crate::RheoError::project_config(
-                                                    "no .typ files found",
-                                                )
-                                            })?;
-
-                                        // Use same output_format setting as initial World creation
-                                        let output_format = if formats.contains(&OutputFormat::Html)
-                                        {
-                                            Some(OutputFormat::Html)
-                                        } else {
-                                            None
-                                        };
-                                        match crate::world::RheoWorld::new(
+                                    warn!(error = %e, "failed to remove stale outputs");
+                                }
+                                // The set of pages (and the navigation) changed, rebuild everything
+                                None
+                            };
+                            let mode = CompilationMode::Incremental {
+                                worlds: &mut worlds_cell.borrow_mut(),
+                                state: &mut state_cell.borrow_mut(),
+                                changed: changed.as_deref(),
+                            };
+                            perform_compilation(
+                                mode,
+                                &project_cell.borrow(),
+                                &output_config,
+                                &formats,
+                            )
+                        }
+                        crate::watch::WatchEvent::ConfigChanged => {
+                            info!("configuration changed, reloading");
+                            // Reload project configuration
+                            match crate::project::ProjectConfig::from_path(
+                                &path,
+                                config.as_deref(),
+                                true,
+                            ) {
+                                Ok(mut new_project) => {
+                                    settings_flags.apply(&mut new_project);
+                                    // Outputs of files no longer in the project are stale
+                                    let removed: Vec<PathBuf> = project_cell
+                                        .borrow()
+                                        .typ_files
+                                        .iter()
+                                        .filter(|file| !new_project.typ_files.contains(file))
+                                        .cloned()
+                                        .collect();
+                                    if let Err(e) = remove_stale_outputs(
+                                        &project_cell.borrow(),
+                                        &output_config,
+                                        &removed,
+                                    ) {
+                                        warn!(error = %e, "failed to remove stale outputs");
+                                    }
+                                    *project_cell.borrow_mut() = new_project;
+                                    let borrowed = project_cell.borrow();
+                                    let file_word = if borrowed.typ_files.len() == 1 {
+                                        "file"
+                                    } else {
+                                        "files"
+                                    };
+                                    info!(name = %borrowed.name, files = borrowed.typ_files.len(), "reloaded ({} {})", borrowed.typ_files.len(), file_word);
+
+                                    // Recreate the Worlds with new configuration
+                                    let new_compilation_root = borrowed
+                                        .config
+                                        .resolve_content_dir(&borrowed.root)
+                                        .unwrap_or_else(|| borrowed.root.clone());
+                                    let new_initial_main =
+                                        borrowed.typ_files.first().ok_or_else(|| {
+                                            crate::RheoError::project_config("no .typ files found")
+                                        })?;
+                                    match borrowed.world_settings().and_then(|settings| {
+                                        FormatWorlds::new(
                                             &new_compilation_root,
                                             new_initial_main,
-                                            output_format,
-                                        ) {
-                                            Ok(new_world) => {
-                                                *world_cell.borrow_mut() = new_world;
-                                                let mode = CompilationMode::Incremental {
-                                                    world: &mut world_cell.borrow_mut(),
-                                                };
-                                                perform_compilation(
-                                                    mode,
-                                                    &borrowed,
-                                                    &output_config,
-                                                    &formats,
-                                                )
-                                            }
-                                            Err(e) => {
-                                                error!(error = %e, "failed to recreate World after config change");
-                                                Err(e)
-                                            }
+                                            &settings,
+                                        )
+                                    }) {
+                                        Ok(new_worlds) => {
+                                            *worlds_cell.borrow_mut() = new_worlds;
+                                            *state_cell.borrow_mut() = IncrementalState::new();
+                                            let mode = CompilationMode::Incremental {
+                                                worlds: &mut worlds_cell.borrow_mut(),
+                                                state: &mut state_cell.borrow_mut(),
+                                                changed: None,
+                                            };
+                                            perform_compilation(
+                                                mode,
+                                                &borrowed,
+                                                &output_config,
+                                                &formats,
+                                            )
+                                        }
+                                        Err(e) => {
+                                            error!(error = %e, "failed to recreate World after config change");
+                                            Err(e)
                                         }
-                                    }
-                                    Err(e) => {
-                                        error!(error = %e, "failed to reload project config");
-                                        Err(e)
                                     }
                                 }
+                                Err(e) => {
+                                    error!(error = %e, "failed to reload project config");
+                                    Err(e)
+                                }
                             }
-                        };
+                        }
+                    };
 
-                        // Send reload event if compilation succeeded and we have a server
-                        if result.is_ok() {
-                            // Evict old entries from the comemo cache to prevent unbounded memory growth
-                            // during long watch sessions. This matches Typst CLI's behavior.
-                            comemo::evict(10);
+                    // Send reload event if compilation succeeded and we have a server
+                    if result.is_ok() {
+                        // Evict old entries from the comemo cache to prevent unbounded memory growth
+                        // during long watch sessions. This matches Typst CLI's behavior.
+                        comemo::evict(10);
 
-                            if let Some((_, _, reload_tx)) = &server_info {
-                                // Ignore errors if no clients are connected
-                                let _ = reload_tx.send(());
-                            }
+                        if let Some((_, _, reload_tx)) = &server_info {
+                            // Ignore errors if no clients are connected
+                            let _ = reload_tx.send(());
                         }
+                    }
 
-                        result
-                    },
-                )?;
+                    result
+                })?;
 
                 // Server will be dropped and cleaned up automatically here
 
@@ -850,7 +1643,8 @@ impl Cli {
                 build_dir,
             } => {
                 info!(path = %path.display(), "loading project");
-                let project = crate::project::ProjectConfig::from_path(&path, config.as_deref())?;
+                let project =
+                    crate::project::ProjectConfig::from_path(&path, config.as_deref(), true)?;
 
                 // Resolve build directory
                 let resolved_build_dir = resolve_build_dir(&project, build_dir)?;
@@ -862,6 +1656,20 @@ impl Cli {
                 info!(project = %project.name, "build artifacts removed");
                 Ok(())
             }
+            Commands::Vendor {
+                path,
+                config,
+                offline,
+            } => {
+                info!(path = %path.display(), "loading project");
+                // Drafts are included, since watch mode compiles them
+                let mut project =
+                    crate::project::ProjectConfig::from_path(&path, config.as_deref(), true)?;
+                project.config.offline |= offline;
+                let vendored = crate::vendor::vendor_packages(&project)?;
+                info!(project = %project.name, packages = vendored, "vendored packages");
+                Ok(())
+            }
             Commands::Init { path } => crate::init::init_project(&path),
         }
     }
@@ -946,4 +1754,216 @@ mod tests {
         assert!(formats.contains(&OutputFormat::Html));
         assert!(formats.contains(&OutputFormat::Epub));
     }
+
+    #[test]
+    fn test_get_output_path_nested() {
+        let content_dir = Path::new("/project/content");
+        let file = PathBuf::from("/project/content/posts/intro.typ");
+        let path = get_output_path(&file, content_dir, OutputLayout::Nested).unwrap();
+        assert_eq!(path, PathBuf::from("posts/intro.typ"));
+        assert_eq!(
+            path.with_extension("html"),
+            PathBuf::from("posts/intro.html")
+        );
+    }
+
+    #[test]
+    fn test_get_output_path_flat() {
+        let content_dir = Path::new("/project/content");
+        let file = PathBuf::from("/project/content/posts/intro.typ");
+        let path = get_output_path(&file, content_dir, OutputLayout::Flat).unwrap();
+        assert_eq!(path, PathBuf::from("intro.typ"));
+    }
+
+    #[test]
+    fn test_check_output_collisions() {
+        let content_dir = Path::new("/project");
+        let files = vec![
+            PathBuf::from("/project/posts/intro.typ"),
+            PathBuf::from("/project/docs/intro.typ"),
+            PathBuf::from("/project/index.typ"),
+        ];
+
+        // Nested layout keeps both files apart
+        assert!(check_output_collisions(&files, content_dir, OutputLayout::Nested).is_ok());
+
+        // Flat layout lists the collision and every source mapping to it
+        let err = check_output_collisions(&files, content_dir, OutputLayout::Flat)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("intro ← docs/intro.typ, posts/intro.typ"));
+        assert!(!err.contains("index"));
+    }
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!(
+            parse_input("edition=second=draft").unwrap(),
+            ("edition".to_string(), "second=draft".to_string())
+        );
+        assert!(parse_input("edition").is_err());
+        assert!(parse_input("=second").is_err());
+        assert!(parse_input("rheo-target=pdf").is_err());
+    }
+
+    #[test]
+    fn test_cli_inputs_override_config() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::write(
+            root.join("rheo.toml"),
+            "version = \"0.1.2\"\nformats = [\"html\"]\n\n[inputs]\nedition = \"first\"\naudience = \"all\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("a.typ"),
+            "#sys.inputs.edition #sys.inputs.audience #sys.inputs.rheo-target\n",
+        )
+        .unwrap();
+
+        let build = root.join("build");
+        let args = [
+            "rheo",
+            "compile",
+            root.to_str().unwrap(),
+            "--build-dir",
+            build.to_str().unwrap(),
+            "--input",
+            "edition=second",
+        ];
+        Cli::try_parse_from(args).unwrap().run().unwrap();
+
+        let html = std::fs::read_to_string(build.join("html/a.html")).unwrap();
+        assert!(html.contains("second all html"), "{}", html);
+    }
+
+    #[test]
+    fn test_cli_font_paths_precede_config() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::write(
+            root.join("rheo.toml"),
+            "version = \"0.1.2\"\nfont_paths = [\"fonts\"]\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("a.typ"), "= A\n").unwrap();
+        let mut project = crate::project::ProjectConfig::from_path(&root, None, false).unwrap();
+
+        let flags = SettingsFlags {
+            inputs: vec![],
+            font_paths: vec![root.join("brand")],
+            offline: false,
+        };
+        flags.apply(&mut project);
+        let brand = root.join("brand").to_string_lossy().into_owned();
+        assert_eq!(project.config.font_paths, vec![brand, "fonts".to_string()]);
+
+        // Font paths must exist
+        assert!(project.world_settings().is_err());
+        std::fs::create_dir(root.join("brand")).unwrap();
+        std::fs::create_dir(root.join("fonts")).unwrap();
+        let settings = project.world_settings().unwrap();
+        assert_eq!(
+            settings.font_paths,
+            vec![root.join("brand"), root.join("fonts")]
+        );
+    }
+
+    #[test]
+    fn test_compile_skips_unchanged_outputs() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::write(
+            root.join("rheo.toml"),
+            "version = \"0.1.2\"\nformats = [\"html\"]\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("a.typ"), "= A\n").unwrap();
+        std::fs::write(root.join("b.typ"), "= B\n").unwrap();
+
+        let build = root.join("build");
+        let compile = |force: bool| {
+            let mut args = vec!["rheo", "compile", root.to_str().unwrap()];
+            args.extend(["--build-dir", build.to_str().unwrap()]);
+            if force {
+                args.push("--force");
+            }
+            Cli::try_parse_from(args).unwrap().run().unwrap();
+        };
+        let marker = "<!-- kept -->";
+        let mark = |page: &str| std::fs::write(build.join("html").join(page), marker).unwrap();
+        let kept =
+            |page: &str| std::fs::read_to_string(build.join("html").join(page)).unwrap() == marker;
+
+        compile(false);
+        assert!(build.join(BUILD_CACHE_FILE).is_file());
+
+        // Unchanged pages are not rewritten
+        mark("a.html");
+        mark("b.html");
+        compile(false);
+        assert!(kept("a.html") && kept("b.html"));
+
+        // Only the page whose source changed is recompiled
+        std::fs::write(root.join("b.typ"), "= B changed\n").unwrap();
+        compile(false);
+        assert!(kept("a.html") && !kept("b.html"));
+
+        // --force recompiles everything
+        compile(true);
+        assert!(!kept("a.html"));
+    }
+
+    #[test]
+    fn test_incremental_compilation_matches_fresh() {
+        // Watch mode reuses Worlds, but each format must still see its own
+        // sys.inputs and link targets, exactly like `rheo compile`
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::write(
+            root.join("rheo.toml"),
+            "version = \"0.1.2\"\nformats = [\"html\", \"pdf\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("a.typ"),
+            "#sys.inputs.at(\"rheo-target\")\n\n#link(\"b.typ\")[B]\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("b.typ"), "= B\n").unwrap();
+
+        let project = crate::project::ProjectConfig::from_path(&root, None, false).unwrap();
+        let formats = [OutputFormat::Html, OutputFormat::Pdf];
+
+        let fresh = crate::output::OutputConfig::new(&root, Some(root.join("fresh")));
+        fresh.create_dirs().unwrap();
+        let mode = CompilationMode::Fresh { root: root.clone() };
+        perform_compilation(mode, &project, &fresh, &formats).unwrap();
+
+        let incremental = crate::output::OutputConfig::new(&root, Some(root.join("incremental")));
+        incremental.create_dirs().unwrap();
+        let mut worlds = FormatWorlds::new(
+            &root,
+            &project.typ_files[0],
+            &project.world_settings().unwrap(),
+        )
+        .unwrap();
+        let mut state = IncrementalState::new();
+        let mode = CompilationMode::Incremental {
+            worlds: &mut worlds,
+            state: &mut state,
+            changed: None,
+        };
+        perform_compilation(mode, &project, &incremental, &formats).unwrap();
+
+        for output in ["html/a.html", "html/b.html", "pdf/a.pdf", "pdf/b.pdf"] {
+            let fresh = std::fs::read(root.join("fresh").join(output)).unwrap();
+            let incremental = std::fs::read(root.join("incremental").join(output)).unwrap();
+            assert!(
+                fresh == incremental,
+                "{} differs from a fresh build",
+                output
+            );
+        }
+    }
 }
@@ -1,4 +1,5 @@
-use crate::world::RheoWorld;
+use crate::discovery::ContentFilter;
+use crate::world::{RheoWorld, WorldSettings};
 use std::path::PathBuf;
 
 /// Common compilation options used across all output formats.
@@ -8,6 +9,8 @@ use std::path::PathBuf;
 /// - Output file (where to write the result)
 /// - Root directory (for resolving imports)
 /// - Optional RheoWorld (for incremental compilation)
+/// - Content filter (for spine globbing in merged outputs)
+/// - World settings such as custom `sys.inputs` (for fresh compilation)
 pub struct RheoCompileOptions<'a> {
     /// The input .typ file to compile
     pub input: PathBuf,
@@ -17,6 +20,10 @@ pub struct RheoCompileOptions<'a> {
     pub root: PathBuf,
     /// Optional existing RheoWorld for incremental compilation
     pub world: Option<&'a mut RheoWorld>,
+    /// Filter applied to spine files (e.g. to skip drafts)
+    pub filter: ContentFilter,
+    /// Settings of worlds created for fresh compilation
+    pub settings: WorldSettings,
 }
 
 impl<'a> RheoCompileOptions<'a> {
@@ -36,6 +43,8 @@ impl<'a> RheoCompileOptions<'a> {
             output: output.into(),
             root: root.into(),
             world: None,
+            filter: ContentFilter::default(),
+            settings: WorldSettings::default(),
         }
     }
 
@@ -59,8 +68,24 @@ impl<'a> RheoCompileOptions<'a> {
             output: output.into(),
             root: root.into(),
             world: Some(world),
+            filter: ContentFilter::default(),
+            settings: WorldSettings::default(),
         }
     }
+
+    /// Set the filter applied to spine files of merged outputs (PDF and EPUB).
+    pub fn with_filter(mut self, filter: ContentFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Set the world settings (custom `sys.inputs`, fonts) of fresh compilation.
+    ///
+    /// Incremental compilation uses the settings of the existing world instead.
+    pub fn with_settings(mut self, settings: WorldSettings) -> Self {
+        self.settings = settings;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -99,6 +124,17 @@ Content here."#;
         assert_eq!(title, "My Great Title");
     }
 
+    #[test]
+    fn test_extract_document_title_from_string() {
+        let source = r#"#set document(title: "doc1")
+
+= A
+Content with [brackets]."#;
+
+        let title = pdf::DocumentTitle::from_source(source, "fallback").extract();
+        assert_eq!(title, "doc1");
+    }
+
     #[test]
     fn test_extract_document_title_fallback() {
         let source = r#"= Chapter 1
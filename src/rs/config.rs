@@ -1,9 +1,12 @@
+use crate::fonts::ProjectFont;
 use crate::manifest_version::ManifestVersion;
+use crate::postprocess::{PageLayout, SiteNav};
 use crate::validation::ValidateConfig;
-use crate::{OutputFormat, Result};
+use crate::{Glob, GlobSet, GlobSetBuilder, OutputFormat, Result, RheoError};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use tracing::debug;
 
 /// HTML compilation options.
@@ -15,6 +18,14 @@ pub struct HtmlOptions {
     pub stylesheets: Vec<String>,
     /// Font URLs to inject
     pub fonts: Vec<String>,
+    /// Output layout, which determines how cross-directory links are rewritten
+    pub layout: OutputLayout,
+    /// Site navigation to inject (only when an HTML spine is configured)
+    pub nav: Option<SiteNav>,
+    /// Page layout wrapping the compiled body (selected from `[[html.layouts]]`)
+    pub page_layout: Option<PageLayout>,
+    /// Search widget script to load, relative to the page (when `[html.search] widget = true`)
+    pub search_script: Option<String>,
 }
 
 impl Default for HtmlOptions {
@@ -22,10 +33,26 @@ impl Default for HtmlOptions {
         Self {
             stylesheets: default_stylesheets(),
             fonts: default_fonts(),
+            layout: OutputLayout::default(),
+            nav: None,
+            page_layout: None,
+            search_script: None,
         }
     }
 }
 
+/// Layout of per-file outputs (HTML pages and individual PDFs) in the build directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputLayout {
+    /// Mirror the content directory, e.g. `posts/intro.typ` → `html/posts/intro.html`.
+    #[default]
+    Nested,
+    /// Write every output directly into the format directory, e.g. `html/intro.html`.
+    /// Files sharing a name in different directories are rejected.
+    Flat,
+}
+
 fn default_stylesheets() -> Vec<String> {
     vec!["style.css".to_string()]
 }
@@ -41,16 +68,23 @@ fn default_fonts() -> Vec<String> {
 pub struct EpubOptions {
     /// Reference to the EPUB configuration
     pub config: EpubConfig,
+    /// Project fonts to embed (when `[epub] copy_fonts = true`)
+    pub fonts: Vec<ProjectFont>,
 }
 
 impl From<&EpubConfig> for EpubOptions {
     fn from(config: &EpubConfig) -> Self {
         Self {
             config: config.clone(),
+            fonts: Vec::new(),
         }
     }
 }
 
+fn default_respect_ignore() -> bool {
+    true
+}
+
 fn default_formats() -> Vec<OutputFormat> {
     OutputFormat::all_variants()
 }
@@ -71,11 +105,75 @@ pub struct RheoConfig {
     /// Examples: "output", "../shared-build", "/tmp/rheo-build"
     pub build_dir: Option<String>,
 
+    /// Layout of per-file outputs in the build directory.
+    /// Defaults to "nested", which mirrors the content directory structure.
+    /// Example: "flat"
+    #[serde(default)]
+    pub layout: OutputLayout,
+
+    /// Glob patterns (relative to content_dir) for files that are not compiled as pages,
+    /// e.g. template libraries imported by other documents.
+    /// Files and directories starting with `_` are always excluded.
+    /// Example: ["lib/**", "templates/*.typ"]
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Whether discovery and watching skip paths ignored by `.gitignore`/`.ignore`
+    /// files, `.git/info/exclude` and git's global excludes file, as well as hidden
+    /// files and directories.
+    /// Defaults to true.
+    #[serde(default = "default_respect_ignore")]
+    pub respect_ignore: bool,
+
+    /// Glob patterns (relative to content_dir) for draft documents.
+    /// Drafts are skipped unless built with `rheo compile --drafts` or `rheo watch`.
+    /// Documents can also mark themselves with `#metadata((draft: true)) <rheo>`.
+    /// Example: ["drafts/**", "**/*.wip.typ"]
+    #[serde(default)]
+    pub drafts: Vec<String>,
+
     /// Default formats to compile (if none specified via CLI).
     /// Example: ["pdf", "html", "epub"]
     #[serde(default = "default_formats")]
     pub formats: Vec<OutputFormat>,
 
+    /// Custom values for `sys.inputs`, available to documents of every format.
+    /// Values passed with `--input key=value` take precedence.
+    /// Example: { edition = "second", audience = "students" }
+    #[serde(default)]
+    pub inputs: BTreeMap<String, String>,
+
+    /// Directories (relative to project root unless absolute) searched recursively
+    /// for fonts, which take precedence over system fonts.
+    /// Directories passed with `--font-path` are searched first.
+    /// Example: ["fonts"]
+    #[serde(default)]
+    pub font_paths: Vec<String>,
+
+    /// Directory (relative to project root unless absolute) with project-local
+    /// Typst packages, laid out as `{namespace}/{name}/{version}` like Typst's
+    /// package directories. Packages in it take precedence, and `rheo vendor`
+    /// copies every imported package into it.
+    /// Example: "packages"
+    pub packages_dir: Option<String>,
+
+    /// Whether to never download packages; imports of packages that are neither
+    /// vendored nor in Typst's package directories fail.
+    /// Can also be enabled with `--offline`.
+    #[serde(default)]
+    pub offline: bool,
+
+    /// Whether to import rheo's helpers and apply `rheo_template` in every
+    /// document, as if it started with
+    /// `#import "@rheo/rheo:0.1.0": *` and `#show: rheo_template`.
+    /// Documents can always import the `@rheo/rheo:0.1.0` package themselves.
+    #[serde(default)]
+    pub inject_template: bool,
+
+    /// Build configuration
+    #[serde(default)]
+    pub build: BuildConfig,
+
     /// HTML-specific configuration
     #[serde(default)]
     pub html: HtmlConfig,
@@ -95,7 +193,17 @@ impl Default for RheoConfig {
             version: ManifestVersion::current(),
             content_dir: Some("./".to_string()),
             build_dir: Some("./build".to_string()),
+            layout: OutputLayout::default(),
+            exclude: Vec::new(),
+            respect_ignore: default_respect_ignore(),
+            drafts: Vec::new(),
             formats: default_formats(),
+            inputs: BTreeMap::new(),
+            font_paths: Vec::new(),
+            packages_dir: None,
+            offline: false,
+            inject_template: false,
+            build: BuildConfig::default(),
             html: HtmlConfig::default(),
             pdf: PdfConfig::default(),
             epub: EpubConfig::default(),
@@ -151,6 +259,56 @@ pub struct HtmlSpine {
     pub vertebrae: Vec<String>,
 }
 
+/// HTML page layout configuration (`[[html.layouts]]`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HtmlLayout {
+    /// Layout template path, relative to the project root.
+    /// Example: "layouts/post.html"
+    pub template: String,
+
+    /// Glob patterns for files using this layout.
+    /// Patterns are evaluated relative to content_dir (or project root if content_dir not set).
+    /// The first matching layout is used; a layout without patterns matches every file.
+    /// Example: ["posts/**"]
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+/// Client-side search configuration for HTML output (`[html.search]`).
+/// When present, search-index.json is written to the HTML output directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HtmlSearch {
+    /// Whether to bundle the search widget (rheo-search.js) and load it on every page.
+    /// The widget renders into an element with id "rheo-search" if the page has one.
+    #[serde(default)]
+    pub widget: bool,
+}
+
+/// Atom feed configuration for HTML output (`[html.feed]`).
+/// Requires `html.base_url`, since feed links must be absolute.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HtmlFeed {
+    /// Title of the feed. Defaults to the HTML spine title, then the project name.
+    pub title: Option<String>,
+
+    /// Author of the feed, for entries whose documents don't set an author.
+    /// Defaults to the project name.
+    pub author: Option<String>,
+
+    /// Glob patterns for files to include as feed entries.
+    /// Patterns are evaluated relative to content_dir (or project root if content_dir not set).
+    /// Example: ["posts/**/*.typ"]
+    pub entries: Vec<String>,
+
+    /// Maximum number of entries, newest first.
+    #[serde(default = "default_feed_limit")]
+    pub limit: usize,
+}
+
+fn default_feed_limit() -> usize {
+    20
+}
+
 /// Common interface for spine configurations across output formats.
 ///
 /// This trait provides uniform access to spine fields, allowing generic
@@ -214,6 +372,40 @@ pub struct HtmlConfig {
     #[serde(default = "default_fonts")]
     pub fonts: Vec<String>,
 
+    /// Glob patterns for static assets to copy into the HTML output directory.
+    /// Patterns are evaluated relative to content_dir (or project root if content_dir not set),
+    /// and matched files keep their relative path.
+    /// Files referenced from compiled pages (e.g. `<img src>`) are copied automatically.
+    /// Example: ["img/**", "static/**"]
+    #[serde(default)]
+    pub assets: Vec<String>,
+
+    /// Absolute URL the HTML output is published at.
+    /// Required for sitemap.xml generation.
+    /// Example: "https://example.com/blog"
+    pub base_url: Option<String>,
+
+    /// Whether to write a robots.txt allowing all crawlers (and pointing to the sitemap).
+    #[serde(default)]
+    pub robots: bool,
+
+    /// Whether to copy the fonts from `font_paths` into the output and load them
+    /// with generated `@font-face` rules (fonts.css).
+    #[serde(default)]
+    pub copy_fonts: bool,
+
+    /// Page layouts wrapping compiled pages, in priority order.
+    #[serde(default)]
+    pub layouts: Vec<HtmlLayout>,
+
+    /// Configuration for the client-side search index and widget.
+    #[serde(default)]
+    pub search: Option<HtmlSearch>,
+
+    /// Configuration for an Atom feed (feed.xml) of dated pages.
+    #[serde(default)]
+    pub feed: Option<HtmlFeed>,
+
     /// Configuration for an HTML spine (sitemap/navbar).
     /// HTML never merges vertebrae.
     #[serde(default)]
@@ -225,11 +417,62 @@ impl Default for HtmlConfig {
         Self {
             stylesheets: default_stylesheets(),
             fonts: default_fonts(),
+            assets: Vec::new(),
+            base_url: None,
+            robots: false,
+            copy_fonts: false,
+            layouts: Vec::new(),
+            search: None,
+            feed: None,
             spine: None,
         }
     }
 }
 
+/// Environment variable with a fixed build date in seconds since the Unix epoch.
+///
+/// See: SOURCE_DATE_EPOCH specification <https://reproducible-builds.org/specs/source-date-epoch/>
+pub const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// Build configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BuildConfig {
+    /// Fixed date of the build, used instead of the current time for
+    /// `datetime.today()`, EPUB modification dates, PDF timestamps and archive
+    /// entries, so that rebuilds of the same sources are byte-identical.
+    /// `SOURCE_DATE_EPOCH` takes precedence.
+    /// Example: "2024-01-01T00:00:00Z"
+    pub date: Option<DateTime<Utc>>,
+}
+
+impl BuildConfig {
+    /// Resolve the fixed build date: `SOURCE_DATE_EPOCH` if set, otherwise `date`.
+    ///
+    /// # Errors
+    /// Returns an error if `SOURCE_DATE_EPOCH` is not a number of seconds
+    pub fn resolve_date(&self) -> Result<Option<DateTime<Utc>>> {
+        match std::env::var(SOURCE_DATE_EPOCH) {
+            Ok(epoch) if !epoch.trim().is_empty() => parse_source_date_epoch(&epoch).map(Some),
+            _ => Ok(self.date),
+        }
+    }
+}
+
+/// Parse a `SOURCE_DATE_EPOCH` value.
+fn parse_source_date_epoch(epoch: &str) -> Result<DateTime<Utc>> {
+    epoch
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .ok_or_else(|| {
+            crate::RheoError::project_config(format!(
+                "invalid {}: '{}' is not a number of seconds since the Unix epoch",
+                SOURCE_DATE_EPOCH, epoch
+            ))
+        })
+}
+
 /// PDF output configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PdfConfig {
@@ -242,6 +485,10 @@ pub struct PdfConfig {
 pub struct EpubConfig {
     /// Unique global identifier for the EPUB document.
     ///
+    /// Setting this is recommended. Without it, rheo derives a UUID from the title and the
+    /// spine files, so retitling the book or renaming, adding or removing a spine file
+    /// changes the identifier and reading systems treat the result as a different book.
+    ///
     /// See: EPUB 3.3, The `dc:identifier` element <https://www.w3.org/TR/epub-33/#sec-opf-dcidentifier>
     pub identifier: Option<String>,
 
@@ -253,6 +500,11 @@ pub struct EpubConfig {
     /// See: EPUB 3.3, The `dc:date` element <https://www.w3.org/TR/epub-33/#sec-opf-dcdate>
     pub date: Option<DateTime<Utc>>,
 
+    /// Whether to embed the fonts from `font_paths` in the EPUB and load them
+    /// with generated `@font-face` rules (fonts.css).
+    #[serde(default)]
+    pub copy_fonts: bool,
+
     /// Configuration for an EPUB spine with multiple chapters.
     pub spine: Option<EpubSpine>,
 }
@@ -325,6 +577,35 @@ impl RheoConfig {
         Ok(config)
     }
 
+    /// Resolve the font directories relative to a base directory.
+    ///
+    /// # Arguments
+    /// * `base_dir` - Directory relative font paths are resolved against (project root)
+    pub fn resolve_font_paths(&self, base_dir: &Path) -> Vec<PathBuf> {
+        self.font_paths
+            .iter()
+            .map(|dir| base_dir.join(dir))
+            .collect()
+    }
+
+    /// Resolve the `[[html.layouts]]` templates relative to a base directory.
+    ///
+    /// Paths are canonicalized where possible, so they can be compared with the
+    /// paths reported by the file watcher.
+    ///
+    /// # Arguments
+    /// * `base_dir` - Directory the templates are resolved against (project root)
+    pub fn resolve_layout_templates(&self, base_dir: &Path) -> Vec<PathBuf> {
+        self.html
+            .layouts
+            .iter()
+            .map(|layout| {
+                let path = base_dir.join(&layout.template);
+                path.canonicalize().unwrap_or(path)
+            })
+            .collect()
+    }
+
     /// Resolve content_dir to an absolute path if configured
     ///
     /// # Arguments
@@ -356,6 +637,27 @@ impl RheoConfig {
     }
 }
 
+/// Compile a list of glob patterns from rheo.toml.
+///
+/// # Arguments
+/// * `key` - Config key the patterns come from, for error messages
+/// * `patterns` - Glob patterns
+///
+/// # Errors
+/// Returns `RheoError::ProjectConfig` if a pattern is invalid.
+pub fn build_glob_set(key: &str, patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| {
+            RheoError::project_config(format!("invalid glob pattern '{}': {}", pattern, e))
+        })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| RheoError::project_config(format!("failed to build {} patterns: {}", key, e)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -392,6 +694,51 @@ mod tests {
         assert_eq!(config.formats, vec![OutputFormat::Pdf]);
     }
 
+    #[test]
+    fn test_inputs_from_config() {
+        let toml = versioned_toml("[inputs]\nedition = \"second\"\naudience = \"students\"");
+        let config: RheoConfig = toml::from_str(&toml).unwrap();
+        assert_eq!(config.inputs["edition"], "second");
+        assert_eq!(config.inputs["audience"], "students");
+        assert!(RheoConfig::default().inputs.is_empty());
+    }
+
+    #[test]
+    fn test_font_paths_from_config() {
+        let toml = versioned_toml(
+            "font_paths = [\"fonts\", \"/usr/share/brand\"]\n\n[html]\ncopy_fonts = true",
+        );
+        let config: RheoConfig = toml::from_str(&toml).unwrap();
+        assert_eq!(
+            config.resolve_font_paths(Path::new("/project")),
+            vec![
+                PathBuf::from("/project/fonts"),
+                PathBuf::from("/usr/share/brand")
+            ]
+        );
+        assert!(config.html.copy_fonts);
+        assert!(!config.epub.copy_fonts);
+    }
+
+    #[test]
+    fn test_build_date() {
+        let config: RheoConfig = toml::from_str(&versioned_toml("")).unwrap();
+        assert_eq!(config.build.date, None);
+
+        let toml = versioned_toml("[build]\ndate = \"2024-01-02T03:04:05Z\"");
+        let config: RheoConfig = toml::from_str(&toml).unwrap();
+        assert_eq!(
+            config.build.date,
+            DateTime::from_timestamp(1_704_164_645, 0)
+        );
+
+        assert_eq!(
+            parse_source_date_epoch(" 1704164645\n").unwrap(),
+            DateTime::from_timestamp(1_704_164_645, 0).unwrap()
+        );
+        assert!(parse_source_date_epoch("yesterday").is_err());
+    }
+
     #[test]
     fn test_formats_defaults_when_not_specified() {
         let toml = versioned_toml("");
@@ -462,6 +809,23 @@ mod tests {
         assert!(err_msg.contains("invalid config file"));
     }
 
+    #[test]
+    fn test_layout_defaults_to_nested() {
+        let toml = versioned_toml("");
+        let config: RheoConfig = toml::from_str(&toml).unwrap();
+        assert_eq!(config.layout, OutputLayout::Nested);
+    }
+
+    #[test]
+    fn test_layout_flat() {
+        let toml = versioned_toml(r#"layout = "flat""#);
+        let config: RheoConfig = toml::from_str(&toml).unwrap();
+        assert_eq!(config.layout, OutputLayout::Flat);
+
+        let toml = versioned_toml(r#"layout = "sideways""#);
+        assert!(toml::from_str::<RheoConfig>(&toml).is_err());
+    }
+
     #[test]
     fn test_html_config_defaults() {
         let config = HtmlConfig::default();
@@ -469,6 +833,44 @@ mod tests {
         assert_eq!(config.fonts.len(), 0);
     }
 
+    #[test]
+    fn test_html_feed_config() {
+        let toml = versioned_toml(
+            "[html]\nbase_url = \"https://example.com\"\n[html.feed]\nentries = [\"posts/*.typ\"]",
+        );
+        let config: RheoConfig = toml::from_str(&toml).unwrap();
+        let feed = config.html.feed.unwrap();
+        assert_eq!(feed.entries, vec!["posts/*.typ"]);
+        assert_eq!(feed.title, None);
+        assert_eq!(feed.limit, 20);
+    }
+
+    #[test]
+    fn test_html_layouts_config() {
+        let toml = versioned_toml(
+            "[[html.layouts]]\ntemplate = \"layouts/post.html\"\nfiles = [\"posts/**\"]\n\n[[html.layouts]]\ntemplate = \"layouts/page.html\"",
+        );
+        let config: RheoConfig = toml::from_str(&toml).unwrap();
+        assert_eq!(config.html.layouts.len(), 2);
+        assert_eq!(config.html.layouts[0].template, "layouts/post.html");
+        assert_eq!(config.html.layouts[0].files, vec!["posts/**"]);
+        assert!(config.html.layouts[1].files.is_empty());
+    }
+
+    #[test]
+    fn test_html_search_config() {
+        let config: RheoConfig = toml::from_str(&versioned_toml("")).unwrap();
+        assert!(config.html.search.is_none());
+
+        let toml = versioned_toml("[html.search]");
+        let config: RheoConfig = toml::from_str(&toml).unwrap();
+        assert!(!config.html.search.unwrap().widget);
+
+        let toml = versioned_toml("[html.search]\nwidget = true");
+        let config: RheoConfig = toml::from_str(&toml).unwrap();
+        assert!(config.html.search.unwrap().widget);
+    }
+
     #[test]
     fn test_html_config_custom_stylesheets() {
         let toml = versioned_toml("[html]\nstylesheets = [\"custom.css\", \"theme.css\"]");
@@ -498,6 +900,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_html_config_assets() {
+        let toml = versioned_toml("[html]\nassets = [\"img/**\", \"static/**\"]");
+        let config: RheoConfig = toml::from_str(&toml).unwrap();
+        assert_eq!(config.html.assets, vec!["img/**", "static/**"]);
+        assert!(HtmlConfig::default().assets.is_empty());
+    }
+
+    #[test]
+    fn test_html_config_base_url_and_robots() {
+        let toml = versioned_toml("[html]\nbase_url = \"https://example.com\"\nrobots = true");
+        let config: RheoConfig = toml::from_str(&toml).unwrap();
+        assert_eq!(config.html.base_url.as_deref(), Some("https://example.com"));
+        assert!(config.html.robots);
+
+        let defaults = HtmlConfig::default();
+        assert!(defaults.base_url.is_none());
+        assert!(!defaults.robots);
+    }
+
     #[test]
     fn test_pdf_spine_with_merge_true() {
         let toml = versioned_toml(
@@ -0,0 +1,432 @@
+//! Filtering of content files found during project discovery and spine globbing.
+//!
+//! Files and directories whose name starts with `_` (e.g. `_partials/header.typ`)
+//! and files matching the `exclude` globs in rheo.toml are never compiled as
+//! pages; they can still be imported by other documents.
+//!
+//! Unless `respect_ignore = false` is set in rheo.toml, paths ignored by
+//! `.gitignore`/`.ignore` files (up to the enclosing git repository), the
+//! repository's `.git/info/exclude` and git's global excludes file, and hidden
+//! files and directories (e.g. `node_modules/`, `.direnv/`) are skipped entirely.
+//! Discovery and the watcher use the same rules (see [`ContentFilter::is_ignored`]);
+//! the watcher also applies them to the rest of the project root (see
+//! [`ContentFilter::is_ignored_in_project`]).
+//!
+//! A document is a draft if it matches one of the `drafts` globs in rheo.toml
+//! (relative to content_dir) or declares `draft: true` in its `<rheo>` front
+//! matter (see [`crate::page_meta`]). Drafts are skipped unless explicitly
+//! included, e.g. with `rheo compile --drafts` or in watch mode.
+
+use crate::config::build_glob_set;
+use crate::{GlobSet, PageMeta, Result, RheoConfig};
+use ignore::WalkBuilder;
+use ignore::gitignore::{Gitignore, GitignoreBuilder, gitconfig_excludes_path};
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Names of the ignore files honoured during discovery.
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// Decides which discovered .typ files take part in a build.
+#[derive(Debug, Clone, Default)]
+pub struct ContentFilter {
+    /// Directory the globs are relative to
+    content_dir: PathBuf,
+    /// Project root containing the content directory
+    project_root: PathBuf,
+    /// Compiled `exclude` globs
+    exclude: GlobSet,
+    /// Whether `_`-prefixed files and directories are excluded
+    skip_underscored: bool,
+    /// Whether ignore files and hidden paths are respected
+    respect_ignore: bool,
+    /// Matchers of the ignore files, highest precedence first
+    ignore: Vec<Gitignore>,
+    /// Compiled `drafts` globs
+    drafts: GlobSet,
+    /// Whether drafts are built
+    include_drafts: bool,
+}
+
+impl ContentFilter {
+    /// Create a filter from the project configuration.
+    ///
+    /// The default filter (`ContentFilter::default()`) includes every file.
+    ///
+    /// # Arguments
+    /// * `content_dir` - Directory the `exclude` and `drafts` globs are relative to
+    /// * `config` - Project configuration
+    /// * `include_drafts` - Whether drafts should be built
+    ///
+    /// # Errors
+    /// Returns `RheoError::ProjectConfig` if an `exclude` or `drafts` glob is invalid.
+    pub fn new(content_dir: &Path, config: &RheoConfig, include_drafts: bool) -> Result<Self> {
+        let ignore = if config.respect_ignore {
+            load_ignore_files(content_dir)
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            content_dir: content_dir.to_path_buf(),
+            project_root: content_dir.to_path_buf(),
+            exclude: build_glob_set("exclude", &config.exclude)?,
+            skip_underscored: true,
+            respect_ignore: config.respect_ignore,
+            ignore,
+            drafts: build_glob_set("drafts", &config.drafts)?,
+            include_drafts,
+        })
+    }
+
+    /// Set the project root, when it differs from the content directory.
+    ///
+    /// Ignore files between the project root and the content directory are loaded
+    /// too, so that [`Self::is_ignored_in_project`] applies them.
+    pub fn with_project_root(mut self, project_root: &Path) -> Self {
+        if project_root != self.project_root {
+            self.project_root = project_root.to_path_buf();
+            if self.respect_ignore {
+                self.ignore = load_ignore_files(project_root);
+            }
+        }
+        self
+    }
+
+    /// Whether drafts are included in the build.
+    pub fn include_drafts(&self) -> bool {
+        self.include_drafts
+    }
+
+    /// Find all .typ files in the content directory that take part in the build.
+    ///
+    /// Ignored and hidden directories are not descended into.
+    pub fn discover_typ_files(&self) -> Vec<PathBuf> {
+        // The walk applies `is_ignored` itself, so the watcher agrees with discovery
+        let filter = self.clone();
+        WalkBuilder::new(&self.content_dir)
+            .standard_filters(false)
+            .filter_entry(move |entry| !filter.is_ignored(entry.path()))
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().and_then(|s| s.to_str()) == Some("typ"))
+            .map(|entry| entry.into_path())
+            .filter(|path| self.includes(path))
+            .collect()
+    }
+
+    /// Check whether a path is hidden or ignored by an ignore file.
+    ///
+    /// Ignore files are `.gitignore`/`.ignore` files, `.git/info/exclude` and git's
+    /// global excludes file. Paths outside the content directory are never ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.is_ignored_below(path, &self.content_dir)
+    }
+
+    /// Check whether a path in the project is hidden or ignored by an ignore file.
+    ///
+    /// Like [`Self::is_ignored`], but paths outside the content directory are checked
+    /// relative to the project root (e.g. `.git/` or `node_modules/` next to the
+    /// content directory). Paths outside the project root are never ignored.
+    pub fn is_ignored_in_project(&self, path: &Path) -> bool {
+        if path.starts_with(&self.content_dir) {
+            self.is_ignored(path)
+        } else {
+            self.is_ignored_below(path, &self.project_root)
+        }
+    }
+
+    /// Check whether a path below `base` is hidden or ignored by an ignore file.
+    fn is_ignored_below(&self, path: &Path, base: &Path) -> bool {
+        if !self.respect_ignore {
+            return false;
+        }
+        let Ok(rel_path) = path.strip_prefix(base) else {
+            return false;
+        };
+        let hidden = rel_path.components().any(|component| {
+            component
+                .as_os_str()
+                .to_str()
+                .is_some_and(|name| name.starts_with('.'))
+        });
+        if hidden {
+            return true;
+        }
+
+        // Like the directory walk, a path is ignored if it or one of its parent
+        // directories below the base directory is. For each of them, the
+        // ignore file with the highest precedence and a matching rule decides.
+        let is_ignored_entry = |entry: &Path, is_dir: bool| {
+            self.ignore
+                .iter()
+                .filter(|matcher| entry.starts_with(matcher.path()))
+                .map(|matcher| matcher.matched(entry, is_dir))
+                .find(|matched| !matched.is_none())
+                .is_some_and(|matched| matched.is_ignore())
+        };
+        let depth = rel_path.components().count();
+        path.ancestors()
+            .take(depth)
+            .enumerate()
+            .any(|(i, entry)| is_ignored_entry(entry, i > 0 || path.is_dir()))
+    }
+
+    /// Check whether a path is excluded by the `_` convention or an `exclude` glob.
+    ///
+    /// Paths outside the content directory are never excluded.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let Ok(rel_path) = path.strip_prefix(&self.content_dir) else {
+            return false;
+        };
+        let underscored = self.skip_underscored
+            && rel_path.components().any(|component| {
+                component
+                    .as_os_str()
+                    .to_str()
+                    .is_some_and(|name| name.starts_with('_'))
+            });
+        underscored || self.exclude.is_match(rel_path)
+    }
+
+    /// Check whether a .typ file is a draft, by glob or by its front matter.
+    pub fn is_draft(&self, path: &Path) -> bool {
+        let rel_path = path.strip_prefix(&self.content_dir).unwrap_or(path);
+        self.drafts.is_match(rel_path)
+            || std::fs::read_to_string(path).is_ok_and(|source| PageMeta::declares_draft(&source))
+    }
+
+    /// Check whether a .typ file takes part in the build.
+    pub fn includes(&self, path: &Path) -> bool {
+        if self.is_ignored(path) {
+            debug!(file = %path.display(), "skipping ignored file");
+            return false;
+        }
+        if self.is_excluded(path) {
+            debug!(file = %path.display(), "skipping excluded file");
+            return false;
+        }
+        if self.include_drafts || !self.is_draft(path) {
+            return true;
+        }
+        debug!(file = %path.display(), "skipping draft");
+        false
+    }
+}
+
+/// Load the ignore files that apply to a directory, like git does.
+///
+/// This includes `.gitignore`/`.ignore` files in ancestor directories up to the
+/// enclosing git repository (if any), those below the directory that are
+/// not themselves in an ignored or hidden directory, and finally the repository's
+/// `.git/info/exclude` and git's global excludes file (`core.excludesFile`).
+///
+/// # Returns
+/// The matchers, highest precedence (deepest directory) first
+fn load_ignore_files(content_dir: &Path) -> Vec<Gitignore> {
+    let mut files = Vec::new();
+    let repo_root = content_dir
+        .ancestors()
+        .find(|dir| dir.join(".git").exists());
+
+    // Ancestors, stopping at the repository root
+    for dir in content_dir.ancestors().skip(1) {
+        if repo_root.is_some_and(|root| !dir.starts_with(root)) {
+            break;
+        }
+        files.extend(
+            IGNORE_FILES
+                .iter()
+                .map(|name| dir.join(name))
+                .filter(|file| file.is_file()),
+        );
+    }
+
+    // The content directory and below
+    let walker = WalkBuilder::new(content_dir)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| {
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            entry.depth() == 0 || !(hidden && is_dir)
+        })
+        .build();
+    files.extend(
+        walker
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                IGNORE_FILES
+                    .iter()
+                    .any(|name| entry.file_name() == std::ffi::OsStr::new(name))
+            })
+            .map(|entry| entry.into_path()),
+    );
+
+    let mut matchers: Vec<Gitignore> = files
+        .iter()
+        .map(|file| {
+            let (matcher, error) = Gitignore::new(file);
+            if let Some(e) = error {
+                warn!(file = %file.display(), error = %e, "invalid ignore file");
+            }
+            matcher
+        })
+        .collect();
+    matchers.sort_by_key(|matcher| std::cmp::Reverse(matcher.path().components().count()));
+
+    // Repository and global excludes have the lowest precedence
+    let base = repo_root.unwrap_or(content_dir);
+    let excludes = repo_root.map(|root| root.join(".git").join("info").join("exclude"));
+    for file in excludes.into_iter().chain(gitconfig_excludes_path()) {
+        if !file.is_file() {
+            continue;
+        }
+        let mut builder = GitignoreBuilder::new(base);
+        if let Some(e) = builder.add(&file) {
+            warn!(file = %file.display(), error = %e, "invalid ignore file");
+        }
+        match builder.build() {
+            Ok(matcher) => matchers.push(matcher),
+            Err(e) => warn!(file = %file.display(), error = %e, "invalid ignore file"),
+        }
+    }
+    matchers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_content_filter_drafts() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("wip")).unwrap();
+        fs::write(root.join("done.typ"), "= Done").unwrap();
+        fs::write(root.join("wip/next.typ"), "= Next").unwrap();
+        fs::write(
+            root.join("marked.typ"),
+            "#metadata((draft: true)) <rheo>\n= Marked",
+        )
+        .unwrap();
+
+        let config = RheoConfig {
+            drafts: vec!["wip/**".to_string()],
+            ..RheoConfig::default()
+        };
+
+        let filter = ContentFilter::new(root, &config, false).unwrap();
+        assert!(filter.includes(&root.join("done.typ")));
+        assert!(!filter.includes(&root.join("wip/next.typ")));
+        assert!(!filter.includes(&root.join("marked.typ")));
+
+        let filter = ContentFilter::new(root, &config, true).unwrap();
+        assert!(filter.is_draft(&root.join("marked.typ")));
+        assert!(filter.includes(&root.join("wip/next.typ")));
+        assert!(filter.includes(&root.join("marked.typ")));
+    }
+
+    #[test]
+    fn test_discover_respects_ignore_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for dir in ["node_modules/pkg", ".direnv", "posts", "vendor"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join(".gitignore"), "node_modules/\n*.gen.typ\n").unwrap();
+        fs::write(root.join("vendor/.ignore"), "*.typ\n!keep.typ\n").unwrap();
+        for file in [
+            "index.typ",
+            "posts/intro.typ",
+            "posts/table.gen.typ",
+            "node_modules/pkg/lib.typ",
+            ".direnv/env.typ",
+            "vendor/skip.typ",
+            "vendor/keep.typ",
+        ] {
+            fs::write(root.join(file), "= Page").unwrap();
+        }
+
+        let discover = |config: &RheoConfig| {
+            let filter = ContentFilter::new(root, config, false).unwrap();
+            let mut files: Vec<PathBuf> = filter
+                .discover_typ_files()
+                .into_iter()
+                .map(|path| path.strip_prefix(root).unwrap().to_path_buf())
+                .collect();
+            files.sort();
+            (filter, files)
+        };
+
+        let (filter, files) = discover(&RheoConfig::default());
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("index.typ"),
+                PathBuf::from("posts/intro.typ"),
+                PathBuf::from("vendor/keep.typ"),
+            ]
+        );
+        assert!(filter.is_ignored(&root.join("node_modules/pkg/lib.typ")));
+        assert!(filter.is_ignored(&root.join(".direnv/env.typ")));
+        assert!(filter.is_ignored(&root.join("posts/new.gen.typ")));
+        assert!(!filter.is_ignored(&root.join("posts/new.typ")));
+
+        // The repository's excludes apply too, with the lowest precedence
+        fs::create_dir_all(root.join(".git/info")).unwrap();
+        fs::write(root.join(".git/info/exclude"), "posts/\n*.typ\n").unwrap();
+        let (filter, files) = discover(&RheoConfig::default());
+        assert_eq!(files, vec![PathBuf::from("vendor/keep.typ")]);
+        assert!(filter.is_ignored(&root.join("posts/new.typ")));
+        fs::remove_dir_all(root.join(".git")).unwrap();
+
+        let config = RheoConfig {
+            respect_ignore: false,
+            ..RheoConfig::default()
+        };
+        let (filter, files) = discover(&config);
+        assert_eq!(files.len(), 7);
+        assert!(!filter.is_ignored(&root.join(".direnv/env.typ")));
+    }
+
+    #[test]
+    fn test_ignored_content_dir_is_still_walked() {
+        let temp = TempDir::new().unwrap();
+        let content_dir = temp.path().join("site");
+        fs::create_dir_all(temp.path().join(".git/info")).unwrap();
+        fs::create_dir_all(&content_dir).unwrap();
+        fs::write(temp.path().join(".git/info/exclude"), "site/\n").unwrap();
+        fs::write(content_dir.join("index.typ"), "= Home").unwrap();
+
+        // Rules only apply below the content directory, as in the directory walk
+        let filter = ContentFilter::new(&content_dir, &RheoConfig::default(), false).unwrap();
+        assert!(!filter.is_ignored(&content_dir.join("index.typ")));
+        assert_eq!(filter.discover_typ_files().len(), 1);
+    }
+
+    #[test]
+    fn test_content_filter_exclude() {
+        let root = Path::new("/project/content");
+        let config = RheoConfig {
+            exclude: vec!["lib/**".to_string(), "**/*.tmpl.typ".to_string()],
+            ..RheoConfig::default()
+        };
+        let filter = ContentFilter::new(root, &config, false).unwrap();
+
+        assert!(filter.is_excluded(&root.join("lib/template.typ")));
+        assert!(filter.is_excluded(&root.join("posts/page.tmpl.typ")));
+        assert!(filter.is_excluded(&root.join("_partials/header.typ")));
+        assert!(filter.is_excluded(&root.join("posts/_aside.typ")));
+        assert!(!filter.is_excluded(&root.join("posts/intro.typ")));
+        assert!(!filter.is_excluded(&root.join("library.typ")));
+        // The `_` convention only applies below the content directory
+        let filter = ContentFilter::new(Path::new("/_site"), &config, false).unwrap();
+        assert!(!filter.is_excluded(Path::new("/_site/index.typ")));
+        assert!(!filter.is_excluded(Path::new("/elsewhere/_notes.typ")));
+
+        // The default filter includes everything
+        assert!(!ContentFilter::default().is_excluded(&root.join("_partials/header.typ")));
+    }
+}
@@ -0,0 +1,658 @@
+use std::collections::{BTreeSet, HashMap};
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use typst::foundations::Bytes;
+use typst::text::{Font, FontBook, FontInfo, FontStyle};
+use typst::utils::LazyHash;
+use typst_kit::fonts::Fonts;
+use walkdir::WalkDir;
+
+use crate::{Result, RheoError};
+
+/// File name of the on-disk font index in rheo's cache directory.
+const FONT_INDEX_FILE: &str = "fonts.json";
+
+lazy_static! {
+    /// Fonts shared by all worlds of a build, searched on first use.
+    static ref SHARED: FontCache = FontCache::load(
+        // Respect TYPST_IGNORE_SYSTEM_FONTS for test consistency
+        std::env::var("TYPST_IGNORE_SYSTEM_FONTS").is_err(),
+        // RHEO_NO_FONT_CACHE disables the on-disk font index
+        std::env::var("RHEO_NO_FONT_CACHE")
+            .is_err()
+            .then(default_index_path)
+            .flatten()
+            .as_deref(),
+    );
+
+    /// Fonts of the worlds with project font directories, by directories.
+    static ref WITH_FONT_PATHS: Mutex<HashMap<Vec<PathBuf>, FontCache>> =
+        Mutex::new(HashMap::new());
+}
+
+/// File name of the stylesheet with `@font-face` rules for copied project fonts.
+pub const FONTS_CSS_FILE: &str = "fonts.css";
+
+/// Directory (relative to the stylesheet) that project fonts are copied to.
+pub const FONTS_DIR: &str = "fonts";
+
+/// Discovered fonts, shared between worlds.
+///
+/// Searching the system fonts reads every font file, so it is done once per
+/// build and the result is handed to all worlds. Cloning is cheap, and fonts
+/// are loaded lazily on first use by any of the worlds.
+#[derive(Clone)]
+pub struct FontCache {
+    /// Metadata about the discovered fonts.
+    book: Arc<LazyHash<FontBook>>,
+    /// Fonts from project font directories, which come first in the book.
+    project: Arc<Vec<FontSlot>>,
+    /// Locations of and storage for the lazily loaded fonts.
+    fonts: Arc<Vec<FontSlot>>,
+}
+
+/// A font in a project font directory.
+#[derive(Debug, Clone)]
+pub struct ProjectFont {
+    /// Location of the font file.
+    pub path: PathBuf,
+    /// Index of the font in its collection (zero for single-font files).
+    pub index: u32,
+    /// Metadata of the font.
+    pub info: FontInfo,
+}
+
+/// Location of a font and, once loaded, the font itself.
+struct FontSlot {
+    path: PathBuf,
+    /// Index of the font in its collection (zero for single-font files).
+    index: u32,
+    font: OnceLock<Option<Font>>,
+}
+
+impl FontSlot {
+    fn get(&self) -> Option<Font> {
+        self.font
+            .get_or_init(|| Font::new(Bytes::new(fs::read(&self.path).ok()?), self.index))
+            .clone()
+    }
+}
+
+/// On-disk index of the system fonts.
+///
+/// The index stores the metadata of every font, so a cold start doesn't have
+/// to parse all font files. It lives in rheo's cache directory (e.g.
+/// `~/.cache/rheo/fonts.json`) and is keyed on the system font search roots
+/// (see [`system_font_roots`]): it is valid as long as the roots are the same
+/// and no directory below them was added, removed or modified. Set
+/// `RHEO_NO_FONT_CACHE` to bypass the index and always search the fonts.
+#[derive(Serialize, Deserialize)]
+struct FontIndex {
+    /// Version of rheo that wrote the index.
+    version: String,
+    /// Directories searched for system fonts.
+    roots: Vec<PathBuf>,
+    /// Existing directories in and below the roots, with their modification times.
+    directories: Vec<(PathBuf, SystemTime)>,
+    fonts: Vec<IndexedFont>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexedFont {
+    path: PathBuf,
+    index: u32,
+    info: FontInfo,
+}
+
+impl FontCache {
+    /// The fonts shared by all worlds of this process.
+    pub fn shared() -> Self {
+        SHARED.clone()
+    }
+
+    /// The shared fonts, preceded by the fonts in project font directories.
+    ///
+    /// The directories are searched once per process, so all worlds using the
+    /// same directories share their fonts.
+    ///
+    /// # Arguments
+    /// * `font_paths` - Project font directories
+    pub fn with_font_paths(font_paths: &[PathBuf]) -> Self {
+        if font_paths.is_empty() {
+            return Self::shared();
+        }
+        WITH_FONT_PATHS
+            .lock()
+            .entry(font_paths.to_vec())
+            .or_insert_with(|| Self::shared().with_project_fonts(search_font_paths(font_paths)))
+            .clone()
+    }
+
+    /// Put project fonts in front of this cache's fonts.
+    fn with_project_fonts(&self, project: Vec<ProjectFont>) -> Self {
+        let infos: Vec<FontInfo> = project
+            .iter()
+            .map(|font| font.info.clone())
+            .chain((0..self.fonts.len()).filter_map(|i| self.book.info(i).cloned()))
+            .collect();
+        let project = project
+            .into_iter()
+            .map(|font| FontSlot {
+                path: font.path,
+                index: font.index,
+                font: OnceLock::new(),
+            })
+            .collect();
+        Self {
+            book: Arc::new(LazyHash::new(FontBook::from_infos(infos))),
+            project: Arc::new(project),
+            fonts: Arc::clone(&self.fonts),
+        }
+    }
+
+    /// Search for fonts.
+    ///
+    /// # Arguments
+    /// * `include_system_fonts` - Whether to search the system fonts
+    /// * `index_path` - Location of the on-disk font index (None = don't use an index)
+    pub fn load(include_system_fonts: bool, index_path: Option<&Path>) -> Self {
+        // Only searching system fonts is slow enough to be worth an index
+        let index_path = index_path.filter(|_| include_system_fonts);
+        let roots = system_font_roots();
+
+        if let Some(index) = index_path.and_then(|path| FontIndex::read(path, &roots)) {
+            debug!(fonts = index.fonts.len(), "using font index");
+            return Self::from_index(index);
+        }
+
+        let search = Fonts::searcher()
+            .include_system_fonts(include_system_fonts)
+            .search();
+        // Only fonts with a path can be loaded lazily. Each slot is paired with
+        // its book entry, so the book is rebuilt from the kept fonts and its
+        // indices keep matching `fonts` when a font without a path is dropped.
+        let (infos, fonts): (Vec<FontInfo>, Vec<FontSlot>) = search
+            .fonts
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| {
+                let slot = FontSlot {
+                    path: slot.path()?.to_path_buf(),
+                    index: slot.index(),
+                    font: OnceLock::new(),
+                };
+                Some((search.book.info(i)?.clone(), slot))
+            })
+            .unzip();
+
+        if let Some(path) = index_path {
+            let index = FontIndex::new(&infos, &fonts, roots);
+            if let Err(e) = index.write(path) {
+                debug!(path = %path.display(), error = %e, "failed to write font index");
+            }
+        }
+
+        Self {
+            book: Arc::new(LazyHash::new(FontBook::from_infos(infos))),
+            project: Arc::default(),
+            fonts: Arc::new(fonts),
+        }
+    }
+
+    fn from_index(index: FontIndex) -> Self {
+        let book = FontBook::from_infos(index.fonts.iter().map(|font| font.info.clone()));
+        let fonts = index
+            .fonts
+            .into_iter()
+            .map(|font| FontSlot {
+                path: font.path,
+                index: font.index,
+                font: OnceLock::new(),
+            })
+            .collect();
+        Self {
+            book: Arc::new(LazyHash::new(book)),
+            project: Arc::default(),
+            fonts: Arc::new(fonts),
+        }
+    }
+
+    /// The fonts from project font directories.
+    pub fn project_fonts(&self) -> Vec<ProjectFont> {
+        self.project
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| {
+                Some(ProjectFont {
+                    path: slot.path.clone(),
+                    index: slot.index,
+                    info: self.book.info(i)?.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Metadata about the fonts.
+    pub fn book(&self) -> &LazyHash<FontBook> {
+        &self.book
+    }
+
+    /// Get the font with the given index, loading it on first access.
+    pub fn font(&self, index: usize) -> Option<Font> {
+        match index.checked_sub(self.project.len()) {
+            Some(index) => self.fonts.get(index)?.get(),
+            None => self.project[index].get(),
+        }
+    }
+}
+
+impl FontIndex {
+    fn new(infos: &[FontInfo], fonts: &[FontSlot], roots: Vec<PathBuf>) -> Self {
+        let fonts: Vec<IndexedFont> = infos
+            .iter()
+            .zip(fonts)
+            .map(|(info, slot)| IndexedFont {
+                path: slot.path.clone(),
+                index: slot.index,
+                info: info.clone(),
+            })
+            .collect();
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            directories: font_directories(&roots),
+            roots,
+            fonts,
+        }
+    }
+
+    /// Read the index at `path`, if it exists and is up to date.
+    ///
+    /// # Arguments
+    /// * `path` - Location of the index
+    /// * `roots` - Directories currently searched for system fonts
+    fn read(path: &Path, roots: &[PathBuf]) -> Option<Self> {
+        let index: Self = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+        let valid = index.version == env!("CARGO_PKG_VERSION")
+            && !index.fonts.is_empty()
+            && index.roots == roots
+            && index.directories == font_directories(roots);
+        valid.then_some(index)
+    }
+
+    fn write(&self, path: &Path) -> std::io::Result<()> {
+        if self.fonts.is_empty() {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first, so concurrent builds never read a partial index
+        let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&tmp, serde_json::to_vec(self)?)?;
+        fs::rename(&tmp, path)
+    }
+}
+
+/// Search project font directories (recursively) for fonts.
+///
+/// # Arguments
+/// * `font_paths` - Project font directories
+///
+/// # Returns
+/// The fonts, in the order of the directories.
+pub fn search_font_paths(font_paths: &[PathBuf]) -> Vec<ProjectFont> {
+    let search = Fonts::searcher()
+        .include_system_fonts(false)
+        .search_with(font_paths);
+    let fonts: Vec<ProjectFont> = search
+        .fonts
+        .iter()
+        .enumerate()
+        .filter_map(|(i, slot)| {
+            Some(ProjectFont {
+                path: slot.path()?.to_path_buf(),
+                index: slot.index(),
+                info: search.book.info(i)?.clone(),
+            })
+        })
+        .collect();
+    debug!(fonts = fonts.len(), dirs = ?font_paths, "found project fonts");
+    fonts
+}
+
+/// Project fonts that can be copied to HTML or EPUB output, with their location
+/// relative to the stylesheet.
+///
+/// Font collections can't be referenced from CSS, so only single-font files
+/// are included. Fonts are copied flat into `fonts/`, so of several files with
+/// the same name only the first is used.
+///
+/// # Arguments
+/// * `fonts` - Project fonts
+pub fn web_fonts(fonts: &[ProjectFont]) -> Vec<(String, &ProjectFont)> {
+    let mut hrefs = BTreeSet::new();
+    fonts
+        .iter()
+        .filter(|font| {
+            let extension = font
+                .path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(str::to_ascii_lowercase);
+            font.index == 0 && matches!(extension.as_deref(), Some("ttf" | "otf"))
+        })
+        .filter_map(|font| {
+            let href = format!("{}/{}", FONTS_DIR, font.path.file_name()?.to_string_lossy());
+            if !hrefs.insert(href.clone()) {
+                warn!(path = %font.path.display(), "skipping font with duplicate file name");
+                return None;
+            }
+            Some((href, font))
+        })
+        .collect()
+}
+
+/// Generate `@font-face` rules for web fonts.
+///
+/// # Arguments
+/// * `fonts` - Fonts and their location relative to the stylesheet (see [`web_fonts`])
+pub fn font_face_css(fonts: &[(String, &ProjectFont)]) -> String {
+    let mut css = String::new();
+    for (href, font) in fonts {
+        let style = match font.info.variant.style {
+            FontStyle::Normal => "normal",
+            FontStyle::Italic => "italic",
+            FontStyle::Oblique => "oblique",
+        };
+        let _ = writeln!(
+            css,
+            "@font-face {{\n  font-family: \"{}\";\n  src: url(\"{}\");\n  font-style: {};\n  font-weight: {};\n}}",
+            font.info.family,
+            href,
+            style,
+            font.info.variant.weight.to_number(),
+        );
+    }
+    css
+}
+
+/// Copy web fonts to `dir/fonts/` and write `dir/fonts.css` with their `@font-face` rules.
+///
+/// # Arguments
+/// * `fonts` - Project fonts
+/// * `dir` - Output directory
+///
+/// # Returns
+/// The number of copied fonts
+///
+/// # Errors
+/// Returns an error if a font or the stylesheet can't be written
+pub fn copy_web_fonts(fonts: &[ProjectFont], dir: &Path) -> Result<usize> {
+    let fonts = web_fonts(fonts);
+    let fonts_dir = dir.join(FONTS_DIR);
+    fs::create_dir_all(&fonts_dir)
+        .map_err(|e| RheoError::io(e, format!("creating font directory {:?}", fonts_dir)))?;
+    for (href, font) in &fonts {
+        let dest = dir.join(href);
+        fs::copy(&font.path, &dest).map_err(|e| {
+            RheoError::io(
+                e,
+                format!("copying font from {:?} to {:?}", font.path, dest),
+            )
+        })?;
+    }
+    let css_path = dir.join(FONTS_CSS_FILE);
+    fs::write(&css_path, font_face_css(&fonts))
+        .map_err(|e| RheoError::io(e, format!("writing {:?}", css_path)))?;
+    debug!(fonts = fonts.len(), dest = %dir.display(), "copied project fonts");
+    Ok(fonts.len())
+}
+
+/// Default location of the font index, in the user's cache directory.
+fn default_index_path() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("rheo").join(FONT_INDEX_FILE))
+}
+
+/// The directories searched for system fonts, as fontdb searches them.
+///
+/// On Linux these are the `<dir>` entries of the fontconfig configuration
+/// (falling back to the usual font directories without one), so changing the
+/// configuration changes the roots.
+fn system_font_roots() -> Vec<PathBuf> {
+    #[allow(unused_mut)]
+    let mut roots: Vec<PathBuf> = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    {
+        let system_root = env::var_os("SYSTEMROOT").map(PathBuf::from);
+        roots.push(system_root.map_or_else(
+            || PathBuf::from("C:\\Windows\\Fonts\\"),
+            |root| root.join("Fonts"),
+        ));
+        if let Some(home) = env::var_os("USERPROFILE").map(PathBuf::from) {
+            roots.push(home.join("AppData\\Local\\Microsoft\\Windows\\Fonts"));
+            roots.push(home.join("AppData\\Roaming\\Microsoft\\Windows\\Fonts"));
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        roots.push(PathBuf::from("/Library/Fonts"));
+        roots.push(PathBuf::from("/System/Library/Fonts"));
+        // Downloadable fonts are searched in all of the asset directories
+        roots.push(PathBuf::from("/System/Library/AssetsV2"));
+        roots.push(PathBuf::from("/Network/Library/Fonts"));
+        if let Some(home) = env::var_os("HOME").map(PathBuf::from) {
+            roots.push(home.join("Library/Fonts"));
+        }
+    }
+
+    #[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+    {
+        roots.extend(fontconfig_roots());
+        if roots.is_empty() {
+            roots.push(PathBuf::from("/usr/share/fonts/"));
+            roots.push(PathBuf::from("/usr/local/share/fonts/"));
+            if let Some(home) = dirs::home_dir() {
+                roots.push(home.join(".fonts"));
+                roots.push(home.join(".local/share/fonts"));
+            }
+        }
+    }
+
+    roots
+}
+
+/// The font directories of the fontconfig configuration.
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+fn fontconfig_roots() -> Vec<PathBuf> {
+    let mut config = fontconfig_parser::FontConfig::default();
+    let home = dirs::home_dir();
+
+    if let Some(file) = std::env::var_os("FONTCONFIG_FILE") {
+        let _ = config.merge_config(Path::new(&file));
+    } else {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| Some(home.as_ref()?.join(".config")));
+        let read_global = match config_home {
+            Some(dir) => config
+                .merge_config(&dir.join("fontconfig/fonts.conf"))
+                .is_err(),
+            None => true,
+        };
+        if read_global {
+            let _ = config.merge_config(Path::new("/etc/fonts/local.conf"));
+        }
+        let _ = config.merge_config(Path::new("/etc/fonts/fonts.conf"));
+    }
+
+    config
+        .dirs
+        .into_iter()
+        .filter_map(|dir| match dir.path.strip_prefix("~") {
+            Ok(relative) => Some(home.as_ref()?.join(relative)),
+            Err(_) => Some(dir.path),
+        })
+        .collect()
+}
+
+/// Existing directories in and below the font search roots, with their
+/// modification times.
+///
+/// Adding or removing a font changes the modification time of its directory,
+/// and a new directory shows up in the walk, so this changes whenever the
+/// fonts found in the roots could have changed.
+fn font_directories(roots: &[PathBuf]) -> Vec<(PathBuf, SystemTime)> {
+    let directories: BTreeSet<(PathBuf, SystemTime)> = roots
+        .iter()
+        .flat_map(|root| WalkDir::new(root).follow_links(true))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_dir())
+        .filter_map(|entry| {
+            let mtime = entry.metadata().ok()?.modified().ok()?;
+            Some((entry.into_path(), mtime))
+        })
+        .collect();
+    directories.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use typst::text::{FontVariant, FontWeight};
+
+    fn test_info(family: &str, variant: FontVariant) -> FontInfo {
+        FontInfo {
+            family: family.into(),
+            variant,
+            flags: typst::text::FontFlags::empty(),
+            coverage: typst::text::Coverage::from_vec(vec![]),
+        }
+    }
+
+    fn test_index(dir: &Path) -> FontIndex {
+        let font_dir = dir.join("fonts");
+        fs::create_dir_all(&font_dir).unwrap();
+        let info = test_info("Test", FontVariant::default());
+        let roots = vec![font_dir.clone()];
+        FontIndex {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            directories: font_directories(&roots),
+            roots,
+            fonts: vec![IndexedFont {
+                path: font_dir.join("test.ttf"),
+                index: 0,
+                info,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_font_index_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("cache").join(FONT_INDEX_FILE);
+        test_index(temp.path()).write(&path).unwrap();
+
+        let roots = [temp.path().join("fonts")];
+        let cache = FontCache::from_index(FontIndex::read(&path, &roots).unwrap());
+        assert_eq!(cache.fonts.len(), 1);
+        assert!(cache.book().select_family("test").next().is_some());
+    }
+
+    #[test]
+    fn test_font_index_invalidated_by_directory_change() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(FONT_INDEX_FILE);
+        let mut index = test_index(temp.path());
+        index.directories[0].1 = SystemTime::UNIX_EPOCH;
+        index.write(&path).unwrap();
+        assert!(FontIndex::read(&path, &index.roots).is_none());
+    }
+
+    #[test]
+    fn test_font_index_keyed_on_roots() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(FONT_INDEX_FILE);
+        let index = test_index(temp.path());
+        index.write(&path).unwrap();
+        let roots = index.roots.clone();
+        assert!(FontIndex::read(&path, &roots).is_some());
+
+        // Other search roots, e.g. from a changed fontconfig configuration
+        assert!(FontIndex::read(&path, &[temp.path().join("other")]).is_none());
+
+        // A new directory anywhere below a root, even one without fonts yet
+        fs::create_dir_all(roots[0].join("new").join("nested")).unwrap();
+        assert!(FontIndex::read(&path, &roots).is_none());
+    }
+
+    #[test]
+    fn test_project_fonts_take_precedence() {
+        let temp = TempDir::new().unwrap();
+        let system = FontCache::from_index(test_index(temp.path()));
+        let brand = ProjectFont {
+            path: temp.path().join("brand").join("test.ttf"),
+            index: 0,
+            info: test_info("Test", FontVariant::default()),
+        };
+        let cache = system.with_project_fonts(vec![brand.clone()]);
+
+        assert_eq!(cache.book().select_family("test").next(), Some(0));
+        let project = cache.project_fonts();
+        assert_eq!(project.len(), 1);
+        assert_eq!(project[0].path, brand.path);
+    }
+
+    #[test]
+    fn test_loaded_book_matches_fonts() {
+        let cache = FontCache::load(true, None);
+        for i in 0..cache.fonts.len().min(20) {
+            let font = cache.font(i).unwrap();
+            assert_eq!(Some(font.info()), cache.book().info(i));
+        }
+    }
+
+    #[test]
+    fn test_font_face_css() {
+        let bold = FontVariant::new(FontStyle::Italic, FontWeight::BOLD, Default::default());
+        let fonts = [
+            ProjectFont {
+                path: PathBuf::from("fonts/Brand-BoldItalic.ttf"),
+                index: 0,
+                info: test_info("Brand", bold),
+            },
+            // Collections can't be loaded from CSS
+            ProjectFont {
+                path: PathBuf::from("fonts/Brand.ttc"),
+                index: 1,
+                info: test_info("Brand", FontVariant::default()),
+            },
+            // Only the first of several files with the same name is copied
+            ProjectFont {
+                path: PathBuf::from("other/Brand-BoldItalic.ttf"),
+                index: 0,
+                info: test_info("Other", FontVariant::default()),
+            },
+        ];
+        let web_fonts = web_fonts(&fonts);
+        assert_eq!(web_fonts.len(), 1);
+        assert_eq!(web_fonts[0].0, "fonts/Brand-BoldItalic.ttf");
+        assert_eq!(
+            font_face_css(&web_fonts),
+            "@font-face {\n  font-family: \"Brand\";\n  src: url(\"fonts/Brand-BoldItalic.ttf\");\n  font-style: italic;\n  font-weight: 700;\n}\n"
+        );
+    }
+}
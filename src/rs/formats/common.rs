@@ -3,9 +3,13 @@ use crate::world::RheoWorld;
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use codespan_reporting::term;
 use ecow::{EcoVec, eco_format};
+use std::num::NonZeroUsize;
 use tracing::{error, warn};
 use typst::WorldExt;
-use typst::diag::{Severity, SourceDiagnostic, SourceResult, Warned};
+use typst::diag::{EcoString, Severity, SourceDiagnostic, SourceResult, Warned};
+use typst::foundations::{NativeElement, StyleChain};
+use typst::model::HeadingElem;
+use typst_html::HtmlDocument;
 
 /// Print diagnostic messages to stderr using codespan-reporting.
 ///
@@ -29,8 +33,9 @@ pub fn print_diagnostics(
         ..Default::default()
     };
 
-    // Use stderr for diagnostic output
-    let mut stderr = term::termcolor::StandardStream::stderr(term::termcolor::ColorChoice::Auto);
+    // Render into a buffer for stderr, which is captured while running parallel jobs
+    let writer = term::termcolor::BufferWriter::stderr(term::termcolor::ColorChoice::Auto);
+    let mut stderr = writer.buffer();
 
     for diagnostic in warnings.iter().chain(errors.iter()) {
         let diag = match diagnostic.severity {
@@ -60,6 +65,7 @@ pub fn print_diagnostics(
         }
     }
 
+    crate::logging::write_stderr(stderr.as_slice());
     Ok(())
 }
 
@@ -221,6 +227,60 @@ pub fn handle_export_errors(
     }
 }
 
+/// A heading of a compiled document, as found by the introspector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentHeading {
+    /// Heading level (1 for `=`).
+    pub level: NonZeroUsize,
+    /// Plain text of the heading body.
+    pub text: EcoString,
+    /// Anchor id: the heading's label if it has one, otherwise derived from the text.
+    pub id: EcoString,
+    /// Heading numbering (e.g. "1.2"), if numbering is enabled.
+    pub numbers: Option<EcoString>,
+}
+
+/// Query all headings of a compiled document in document order.
+///
+/// Shared by the EPUB outline and the HTML search index.
+pub fn query_headings(doc: &HtmlDocument) -> Vec<DocumentHeading> {
+    doc.introspector
+        .query(&HeadingElem::ELEM.select())
+        .iter()
+        .map(|elem| {
+            let heading = elem
+                .to_packed::<HeadingElem>()
+                .expect("must be heading b/c queried for headings");
+            let text = heading.body.plain_text();
+            let id = match heading.label() {
+                Some(label) => label.resolve().to_string().into(),
+                None => text_to_id(&text),
+            };
+            DocumentHeading {
+                level: heading.resolve_level(StyleChain::default()),
+                text,
+                id,
+                numbers: heading.numbers.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Derive an anchor id from heading text.
+pub fn text_to_id(s: &str) -> EcoString {
+    // TODO: handle all the cases described here:
+    // https://developer.mozilla.org/en-US/docs/Web/CSS/Reference/Values/ident#syntax
+    s.chars()
+        .map(|char| {
+            if char.is_whitespace() {
+                '-'
+            } else {
+                char.to_ascii_lowercase()
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,33 +1,39 @@
 pub mod package;
+pub mod resources;
 mod xhtml;
 
 use package::{Item, ItemRef, Package};
+use resources::{DataUrlRewriter, EpubResource};
 use xhtml::HtmlInfo;
 
 use crate::compile::RheoCompileOptions;
 use crate::config::{EpubConfig, EpubOptions};
+use crate::discovery::ContentFilter;
+use crate::formats::common::{DocumentHeading, query_headings};
 use crate::reticulate::spine::RheoSpine;
-use crate::{OutputFormat, Result, RheoError};
+use crate::world::{RheoWorld, WorldSettings};
+use crate::{OutputFormat, PageMeta, Result, RheoError};
 use anyhow::Result as AnyhowResult;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Timelike, Utc};
 use iref::{IriRef, IriRefBuf, iri::Fragment};
 use itertools::Itertools;
+use rayon::prelude::*;
 use std::{
+    collections::{BTreeMap, HashMap},
     fmt::Write as _,
     fs::File,
     io::{BufWriter, Write},
     num::NonZero,
     path::{Path, PathBuf},
 };
-use tracing::info;
+use tracing::{debug, info};
 use typst::{
     diag::{EcoString, eco_format},
     ecow::eco_vec,
-    foundations::{NativeElement, StyleChain},
-    model::{HeadingElem, OutlineNode},
+    model::OutlineNode,
+    utils::hash128,
 };
 use typst_html::HtmlDocument;
-use uuid::Uuid;
 use zip::{result::ZipError, write::SimpleFileOptions};
 
 const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -111,23 +117,84 @@ fn date_format(dt: &DateTime<Utc>) -> EcoString {
     dt.format("%Y-%m-%dT%H:%M:%SZ").to_string().into()
 }
 
+/// Returns the resources loaded by all items and the given extra resources,
+/// deduplicated by href and sorted.
+///
+/// Chapters frequently share resources (e.g. a logo), but each file must only be
+/// listed once in the manifest and written once to the container.
+pub fn unique_resources<'a>(
+    items: &'a [EpubItem],
+    extra: &'a [EpubResource],
+) -> Vec<&'a EpubResource> {
+    items
+        .iter()
+        .flat_map(|item| item.resources.iter())
+        .chain(extra)
+        .map(|resource| (resource.href.as_str(), resource))
+        .collect::<BTreeMap<_, _>>()
+        .into_values()
+        .collect()
+}
+
+/// Title of the EPUB: the combined spine title, or the first chapter's title.
+fn epub_title(items: &[EpubItem], config: &EpubConfig) -> EcoString {
+    match &config.spine {
+        None => items[0].title(),
+        Some(combined) => combined.title.as_ref().unwrap().into(),
+    }
+}
+
+/// Stable identifier for an EPUB without a configured identifier.
+///
+/// Derived from the EPUB's title and its spine files relative to the content
+/// directory, so rebuilds keep the same identifier wherever (and under whichever
+/// directory name) the project is checked out, while other books get different ones.
+///
+/// # Arguments
+/// * `content_dir` - Directory containing the spine files
+/// * `spine` - Spine files, in spine order
+/// * `title` - Title of the EPUB
+fn derived_identifier(content_dir: &Path, spine: &[PathBuf], title: &str) -> EcoString {
+    let spine: Vec<_> = spine
+        .iter()
+        .map(|path| {
+            path.strip_prefix(content_dir)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect();
+    let hash = hash128(&(spine, title));
+    let uuid = uuid::Builder::from_custom_bytes(hash.to_be_bytes()).into_uuid();
+    eco_format!("urn:uuid:{}", uuid)
+}
+
 /// Generates the package.opf XML string from the generated EPUB items.
 ///
 /// See: EPUB 3.3 Package document <https://www.w3.org/TR/epub-33/#sec-package-doc>
-pub fn generate_package(items: &[EpubItem], config: &EpubConfig) -> AnyhowResult<String> {
+///
+/// # Arguments
+/// * `items` - Compiled chapters, in spine order
+/// * `resources` - Resources to list in the manifest
+/// * `config` - EPUB configuration
+/// * `identifier` - Unique identifier, used when `config.identifier` is not set
+/// * `modified` - Time of the last modification (`dcterms:modified`)
+pub fn generate_package(
+    items: &[EpubItem],
+    resources: &[&EpubResource],
+    config: &EpubConfig,
+    identifier: EcoString,
+    modified: DateTime<Utc>,
+) -> AnyhowResult<String> {
     let info = &items[0].document.info;
     let language = info.locale.unwrap_or_default().rfc_3066();
-    let title = match &config.spine {
-        None => items[0].title(),
-        Some(combined) => combined.title.as_ref().unwrap().into(),
-    };
+    let title = epub_title(items, config);
 
     const INTERNAL_UNIQUE_ID: &str = "uid";
 
-    // If the user did not provide a unique ID, we generate a UUID for them.
     let identifier_content = match &config.identifier {
         Some(id) => id.into(),
-        None => eco_format!("urn:uuid:{}", Uuid::new_v4()),
+        None => identifier,
     };
 
     // Start building the package
@@ -142,14 +209,16 @@ pub fn generate_package(items: &[EpubItem], config: &EpubConfig) -> AnyhowResult
         builder = builder.creator(info.author.join(", "));
     }
 
-    // Set date if provided
+    // Set date if provided, falling back to the first chapter's front matter date
     if let Some(ref date) = config.date {
         builder = builder.date(date_format(date));
+    } else if let Some(date) = items[0].meta.date {
+        builder = builder.date(date.format("%Y-%m-%d").to_string());
     }
 
     // Add metadata elements
     builder = builder
-        .add_meta("dcterms:modified", date_format(&chrono::Utc::now()))
+        .add_meta("dcterms:modified", date_format(&modified))
         .add_meta("ppub:valid", ".");
 
     // Add navigation item to manifest
@@ -186,6 +255,16 @@ pub fn generate_package(items: &[EpubItem], config: &EpubConfig) -> AnyhowResult
             });
     }
 
+    // Add bundled resources (images, fonts, ...) to manifest
+    for resource in resources {
+        builder = builder.add_item(Item {
+            id: resource.id(),
+            href: resource.href.clone(),
+            media_type: resource.media_type.clone(),
+            properties: None,
+        });
+    }
+
     // Build and validate the package
     let package = builder
         .build()
@@ -196,18 +275,23 @@ pub fn generate_package(items: &[EpubItem], config: &EpubConfig) -> AnyhowResult
 
 /// Combines all EPUB components into the final .epub i.e. zip file.
 ///
+/// Entries are timestamped with the fixed build date if given, otherwise with
+/// the earliest date zip supports (1980-01-01), never with the current time.
+///
 /// See: EPUB 3.3 Open Container Format <https://www.w3.org/TR/epub-33/#sec-ocf>
 pub fn zip_epub(
     epub_path: &Path,
     package_string: String,
     nav_xhtml: String,
     items: &[EpubItem],
+    resources: &[&EpubResource],
+    build_date: Option<DateTime<Utc>>,
 ) -> AnyhowResult<()> {
     let file = File::create(epub_path).map_err(ZipError::Io)?;
     let file = BufWriter::new(file);
     let mut zip = zip::ZipWriter::new(file);
 
-    let opts = SimpleFileOptions::default();
+    let opts = SimpleFileOptions::default().last_modified_time(zip_date(build_date));
 
     // The mimetype file must (a) be first in the archive and (b) be stored without compression.
     zip.start_file(
@@ -237,15 +321,55 @@ pub fn zip_epub(
         zip.write_all(item.xhtml.as_bytes())?;
     }
 
+    for resource in resources {
+        let filename = format!("EPUB/{}", resource.href);
+        zip.start_file(&filename, opts)?;
+        zip.write_all(resource.data.as_slice())?;
+    }
+
     zip.finish()?;
 
     Ok(())
 }
 
+/// Timestamp of zip entries: the build date, or 1980-01-01 if there is none or
+/// it can't be represented.
+fn zip_date(build_date: Option<DateTime<Utc>>) -> zip::DateTime {
+    build_date
+        .and_then(|date| {
+            zip::DateTime::from_date_and_time(
+                date.year().try_into().ok()?,
+                date.month().try_into().ok()?,
+                date.day().try_into().ok()?,
+                date.hour().try_into().ok()?,
+                date.minute().try_into().ok()?,
+                date.second().try_into().ok()?,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
 /// Generates a spine from the EPUB configuration using RheoSpine for AST-based
 /// link transformation (.typ → .xhtml), compiles each file to XHTML,
 /// generates navigation, and packages everything into a .epub (zip) file.
-fn compile_epub_impl(config: &EpubConfig, epub_path: &Path, root: &Path) -> Result<()> {
+///
+/// With a world and cache (watch mode), spine files are compiled in the given
+/// world and unchanged spine files are reused from the cache.
+fn compile_epub_impl(
+    options: &EpubOptions,
+    epub_path: &Path,
+    root: &Path,
+    filter: &ContentFilter,
+    settings: &WorldSettings,
+    incremental: Option<(&mut RheoWorld, &mut EpubCache)>,
+) -> Result<()> {
+    // Incremental compilation uses the settings of the existing world
+    let build_date = match &incremental {
+        Some((world, _)) => world.build_date(),
+        None => settings.build_date,
+    };
+    let config = &options.config;
     let inner = || -> AnyhowResult<()> {
         // Convert spine config to trait object for generic spine handling
         let spine_config = config
@@ -254,23 +378,66 @@ fn compile_epub_impl(config: &EpubConfig, epub_path: &Path, root: &Path) -> Resu
             .map(|s| s as &dyn crate::config::SpineConfig);
 
         // Build RheoSpine with AST-transformed sources (.typ links → .xhtml)
-        let rheo_spine = RheoSpine::build(root, spine_config, crate::OutputFormat::Epub)?;
+        let rheo_spine = RheoSpine::build(root, spine_config, crate::OutputFormat::Epub, filter)?;
 
         // Get the spine file paths
-        let spine = crate::reticulate::spine::generate_spine(root, spine_config, false)?;
+        let spine = crate::reticulate::spine::generate_spine(root, spine_config, false, filter)?;
 
-        // Create EpubItems from transformed sources
-        let mut items = spine
+        // Create EpubItems from transformed sources, compiling spine files in parallel
+        let chapters: Vec<(&PathBuf, &str)> = spine
             .iter()
-            .zip(rheo_spine.source.iter())
-            .map(|(path, transformed_source)| {
-                EpubItem::create_from_source(path.clone(), transformed_source, root)
-            })
-            .collect::<AnyhowResult<Vec<_>>>()?;
+            .zip(rheo_spine.source.iter().map(String::as_str))
+            .collect();
+        let mut items = match incremental {
+            Some((world, cache)) => {
+                let items = cache.items(&chapters, world)?;
+                cache.retain(&spine);
+                items
+            }
+            None => chapters
+                .par_iter()
+                .map(|(path, transformed_source)| {
+                    crate::logging::capture(|| {
+                        EpubItem::create_from_source(
+                            path.to_path_buf(),
+                            transformed_source,
+                            root,
+                            settings,
+                        )
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|(item, output)| {
+                    // Output is replayed in spine order
+                    output.replay();
+                    item
+                })
+                .collect::<AnyhowResult<Vec<_>>>()?,
+        };
+
+        // Load embedded project fonts in every chapter
+        let font_resources = resources::font_resources(&options.fonts)?;
+        if !font_resources.is_empty() {
+            for item in &mut items {
+                item.xhtml = xhtml::link_stylesheet(&item.xhtml, crate::fonts::FONTS_CSS_FILE);
+            }
+        }
 
         let nav_xhtml = generate_nav_xhtml(&mut items)?;
-        let package_string = generate_package(&items, config)?;
-        zip_epub(epub_path, package_string, nav_xhtml, &items)
+        let resources = unique_resources(&items, &font_resources);
+        // If the user did not provide a unique ID, we derive a UUID for them.
+        let identifier = derived_identifier(root, &spine, &epub_title(&items, config));
+        let modified = build_date.unwrap_or_else(Utc::now);
+        let package_string = generate_package(&items, &resources, config, identifier, modified)?;
+        zip_epub(
+            epub_path,
+            package_string,
+            nav_xhtml,
+            &items,
+            &resources,
+            build_date,
+        )
     };
 
     inner().map_err(|e| RheoError::EpubGeneration {
@@ -284,19 +451,145 @@ fn compile_epub_impl(config: &EpubConfig, epub_path: &Path, root: &Path) -> Resu
 
 /// Compile Typst documents to EPUB (unified API).
 ///
-/// Currently routes to the implementation function. EPUB compilation does not
-/// yet support incremental compilation (only fresh compilation is available).
+/// Routes to the implementation function. Incremental compilation needs both
+/// `options.world` and a chapter cache; otherwise every spine file is compiled
+/// in a fresh world.
 ///
 /// # Arguments
 /// * `options` - Compilation options (input, output, root, repo_root, world)
 /// * `epub_options` - EPUB-specific options (wraps EpubConfig)
+/// * `cache` - Spine files compiled by previous builds (watch mode)
 ///
 /// # Returns
 /// * `Result<()>` - Success or compilation error
-pub fn compile_epub_new(options: RheoCompileOptions, epub_options: EpubOptions) -> Result<()> {
-    // Note: EPUB doesn't support incremental compilation yet, so we ignore options.world
-    // and always do fresh compilation
-    compile_epub_impl(&epub_options.config, &options.output, &options.root)
+pub fn compile_epub_new(
+    options: RheoCompileOptions,
+    epub_options: EpubOptions,
+    cache: Option<&mut EpubCache>,
+) -> Result<()> {
+    compile_epub_impl(
+        &epub_options,
+        &options.output,
+        &options.root,
+        &options.filter,
+        &options.settings,
+        options.world.zip(cache),
+    )
+}
+
+/// Spine files compiled by previous EPUB builds in watch mode.
+///
+/// A spine file is reused as long as its transformed source and the contents
+/// of every file it read are unchanged, so only changed chapters are
+/// recompiled before the EPUB is zipped again.
+#[derive(Default)]
+pub struct EpubCache {
+    chapters: HashMap<PathBuf, CachedChapter>,
+}
+
+/// A compiled spine file with the content hashes of its dependencies.
+struct CachedChapter {
+    source: String,
+    dependencies: Vec<(PathBuf, u128)>,
+    item: EpubItem,
+}
+
+impl EpubCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the EPUB items of spine files (path and transformed source).
+    ///
+    /// Spine files without an up-to-date cached item are compiled in parallel,
+    /// each in a fork of `world`.
+    fn items(
+        &mut self,
+        chapters: &[(&PathBuf, &str)],
+        world: &RheoWorld,
+    ) -> AnyhowResult<Vec<EpubItem>> {
+        let compiled: Vec<_> = chapters
+            .par_iter()
+            .map(|(path, transformed_source)| {
+                if self.cached(path, transformed_source).is_some() {
+                    return None;
+                }
+                Some(crate::logging::capture(|| {
+                    let mut world = world.fork();
+                    let item = EpubItem::create_in_world(
+                        path.to_path_buf(),
+                        transformed_source,
+                        &mut world,
+                    )?;
+                    Ok::<_, anyhow::Error>((item, world.dependencies()))
+                }))
+            })
+            .collect();
+
+        let mut items = Vec::with_capacity(chapters.len());
+        for ((path, transformed_source), compiled) in chapters.iter().zip(compiled) {
+            let Some((result, output)) = compiled else {
+                debug!(file = %path.display(), "reusing unchanged spine file");
+                items.push(self.chapters[*path].item.clone());
+                continue;
+            };
+            // Output is replayed in spine order
+            output.replay();
+            self.chapters.remove(*path);
+            let (item, dependencies) = result?;
+            // The virtual main file isn't a dependency; the transformed source stands in for it
+            let dependencies = dependencies
+                .into_iter()
+                .filter_map(|dep| Some((file_hash(&dep)?, dep)))
+                .map(|(hash, dep)| (dep, hash))
+                .collect();
+            self.chapters.insert(
+                path.to_path_buf(),
+                CachedChapter {
+                    source: transformed_source.to_string(),
+                    dependencies,
+                    item: item.clone(),
+                },
+            );
+            items.push(item);
+        }
+        Ok(items)
+    }
+
+    /// The cached item of a spine file, if its transformed source and
+    /// dependencies are unchanged.
+    fn cached(&self, path: &Path, transformed_source: &str) -> Option<&EpubItem> {
+        let cached = self.chapters.get(path)?;
+        let fresh = cached.source == transformed_source
+            && cached
+                .dependencies
+                .iter()
+                .all(|(dep, hash)| file_hash(dep) == Some(*hash));
+        fresh.then_some(&cached.item)
+    }
+
+    /// Drop cached spine files that are no longer part of the spine.
+    fn retain(&mut self, spine: &[PathBuf]) {
+        self.chapters.retain(|path, _| spine.contains(path));
+    }
+
+    /// Spine files of the last build and the files they read, which are the
+    /// dependencies of the EPUB.
+    pub fn dependencies(&self) -> Vec<PathBuf> {
+        self.chapters
+            .iter()
+            .flat_map(|(path, chapter)| {
+                std::iter::once(path).chain(chapter.dependencies.iter().map(|(dep, _)| dep))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Hash of a file's contents, or None if it can't be read.
+fn file_hash(path: &Path) -> Option<u128> {
+    std::fs::read(path).ok().map(|data| hash128(&data))
 }
 
 // ============================================================================
@@ -306,44 +599,67 @@ pub fn compile_epub_new(options: RheoCompileOptions, epub_options: EpubOptions)
 pub struct EpubItem {
     href: IriRefBuf,
     document: HtmlDocument,
+    meta: PageMeta,
     xhtml: String,
     info: HtmlInfo,
     outline: Option<Vec<OutlineNode<EcoString>>>,
+    resources: Vec<EpubResource>,
 }
 
-fn text_to_id(s: &str) -> EcoString {
-    // TODO: handle all the cases described here:
-    // https://developer.mozilla.org/en-US/docs/Web/CSS/Reference/Values/ident#syntax
-    s.chars()
-        .map(|char| {
-            if char.is_whitespace() {
-                '-'
-            } else {
-                char.to_ascii_lowercase()
-            }
-        })
-        .collect()
+impl Clone for EpubItem {
+    fn clone(&self) -> Self {
+        // OutlineNode doesn't implement Clone
+        fn clone_outline(nodes: &[OutlineNode<EcoString>]) -> Vec<OutlineNode<EcoString>> {
+            nodes
+                .iter()
+                .map(|node| OutlineNode {
+                    entry: node.entry.clone(),
+                    level: node.level,
+                    children: clone_outline(&node.children),
+                })
+                .collect()
+        }
+
+        Self {
+            href: self.href.clone(),
+            document: self.document.clone(),
+            meta: self.meta.clone(),
+            xhtml: self.xhtml.clone(),
+            info: self.info.clone(),
+            outline: self.outline.as_deref().map(clone_outline),
+            resources: self.resources.clone(),
+        }
+    }
 }
 
 impl EpubItem {
     pub fn create(path: PathBuf, root: &Path) -> AnyhowResult<Self> {
         info!(file = %path.display(), "compiling spine file");
-        let document =
-            crate::formats::html::compile_html_to_document(&path, root, OutputFormat::Epub)?;
+        let world = RheoWorld::new(root, &path, Some(OutputFormat::Epub))?;
+        let document = crate::formats::html::compile_world_to_document(&world)?;
+        let resources = resources::collect_resources(&world);
         let parent = path.parent().unwrap();
         let bare_file = path.strip_prefix(parent).unwrap();
         let href = IriRefBuf::new(bare_file.with_extension("xhtml").display().to_string())?;
         let (heading_ids, outline) = Self::outline(&document, &href);
         // Export to HTML (links already transformed by RheoWorld)
         let html_string = crate::formats::html::compile_document_to_string(&document)?;
-        let (xhtml, info) = xhtml::html_to_portable_xhtml(&html_string, &heading_ids);
+        let (xhtml, info) = xhtml::html_to_portable_xhtml(
+            &html_string,
+            &heading_ids,
+            &DataUrlRewriter::new(&resources),
+        );
+
+        let meta = PageMeta::query(&document.introspector)?;
 
         Ok(EpubItem {
             href,
             document,
+            meta,
             xhtml,
             info,
             outline: Some(outline),
+            resources,
         })
     }
 
@@ -352,21 +668,48 @@ impl EpubItem {
         path: PathBuf,
         transformed_source: &str,
         root: &Path,
+        settings: &WorldSettings,
     ) -> AnyhowResult<Self> {
-        use std::io::Write;
+        info!(file = %path.display(), "compiling spine file with transformed source");
 
+        // Compile to HTML document, keeping the world to collect loaded resources
+        let world = RheoWorld::from_source(
+            root,
+            &Self::main_name(&path),
+            transformed_source,
+            Some(OutputFormat::Epub),
+        )?
+        .with_settings(settings);
+        Self::from_world(path, &world)
+    }
+
+    /// Create EpubItem from RheoSpine-transformed source, compiled in an existing
+    /// (EPUB) world as in watch mode.
+    fn create_in_world(
+        path: PathBuf,
+        transformed_source: &str,
+        world: &mut RheoWorld,
+    ) -> AnyhowResult<Self> {
         info!(file = %path.display(), "compiling spine file with transformed source");
 
-        // Write transformed source to temporary file
-        let mut temp_file = tempfile::NamedTempFile::new_in(root)?;
-        temp_file.write_all(transformed_source.as_bytes())?;
-        temp_file.flush()?;
+        world.set_main_source(&Self::main_name(&path), transformed_source);
+        // Only the files read by this spine file are collected as resources
+        world.reset();
+        Self::from_world(path, world)
+    }
 
-        let temp_path = temp_file.path();
+    /// Name of the virtual main file holding the transformed source of a spine file.
+    ///
+    /// It lives in the root, where the transformed links resolve.
+    fn main_name(path: &Path) -> String {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        format!(".rheo-{file_name}")
+    }
 
-        // Compile to HTML document
-        let document =
-            crate::formats::html::compile_html_to_document(temp_path, root, OutputFormat::Epub)?;
+    /// Compile the main file of a world to an EpubItem for the spine file `path`.
+    fn from_world(path: PathBuf, world: &RheoWorld) -> AnyhowResult<Self> {
+        let document = crate::formats::html::compile_world_to_document(world)?;
+        let resources = resources::collect_resources(world);
 
         let parent = path.parent().unwrap();
         let bare_file = path.strip_prefix(parent).unwrap();
@@ -375,33 +718,37 @@ impl EpubItem {
 
         // Export to HTML (links already .typ → .xhtml from RheoSpine)
         let html_string = crate::formats::html::compile_document_to_string(&document)?;
-        let (xhtml, info) = xhtml::html_to_portable_xhtml(&html_string, &heading_ids);
+        let (xhtml, info) = xhtml::html_to_portable_xhtml(
+            &html_string,
+            &heading_ids,
+            &DataUrlRewriter::new(&resources),
+        );
+
+        let meta = PageMeta::query(&document.introspector)?;
 
         Ok(EpubItem {
             href,
             document,
+            meta,
             xhtml,
             info,
             outline: Some(outline),
+            resources,
         })
     }
 
     fn outline(doc: &HtmlDocument, href: &IriRef) -> (Vec<EcoString>, Vec<OutlineNode<EcoString>>) {
         // Adapted from https://github.com/typst/typst/blob/02cd1c13de50363010b41b95148233dc952042c2/crates/typst-pdf/src/outline.rs#L7
-        let elems = doc.introspector.query(&HeadingElem::ELEM.select());
-        let (nodes, heading_ids): (Vec<_>, Vec<_>) = elems
-            .iter()
-            .map(|elem| {
-                let heading = elem
-                    .to_packed::<HeadingElem>()
-                    .expect("must be heading b/c queried for headings");
-                let level = heading.resolve_level(StyleChain::default());
-                let text = heading.body.plain_text();
-                let id = match heading.label() {
-                    Some(label) => label.resolve().to_string().into(),
-                    None => text_to_id(&text),
-                };
-                let entry = match &heading.numbers {
+        let (nodes, heading_ids): (Vec<_>, Vec<_>) = query_headings(doc)
+            .into_iter()
+            .map(|heading| {
+                let DocumentHeading {
+                    level,
+                    text,
+                    id,
+                    numbers,
+                } = heading;
+                let entry = match numbers {
                     Some(num) => eco_format!("{num} {text}"),
                     None => text,
                 };
@@ -443,3 +790,139 @@ impl EpubItem {
             .into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_epub_cache_reuses_unchanged_chapters() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        let a = root.join("a.typ");
+        let b = root.join("b.typ");
+        std::fs::write(root.join("shared.typ"), "#let name = \"one\"\n").unwrap();
+        std::fs::write(&a, "= A\n").unwrap();
+        std::fs::write(&b, "#import \"shared.typ\": name\n= B #name\n").unwrap();
+        let source_a = std::fs::read_to_string(&a).unwrap();
+        let source_b = std::fs::read_to_string(&b).unwrap();
+
+        let world = RheoWorld::new(&root, &a, Some(OutputFormat::Epub)).unwrap();
+        let mut cache = EpubCache::new();
+        let items = cache
+            .items(&[(&a, &source_a), (&b, &source_b)], &world)
+            .unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(cache.cached(&a, &source_a).is_some());
+        assert!(cache.cached(&b, &source_b).is_some());
+
+        // A changed source or dependency invalidates only the affected chapter
+        assert!(cache.cached(&a, "= A changed\n").is_none());
+        std::fs::write(root.join("shared.typ"), "#let name = \"two\"\n").unwrap();
+        assert!(cache.cached(&a, &source_a).is_some());
+        assert!(cache.cached(&b, &source_b).is_none());
+
+        // Chapters removed from the spine are dropped
+        cache.retain(std::slice::from_ref(&b));
+        assert!(cache.cached(&a, &source_a).is_none());
+    }
+
+    #[test]
+    fn test_spine_source_compiles_in_memory() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::write(root.join("lib.typ"), "#let greeting = \"hello\"\n").unwrap();
+        std::fs::write(root.join("a.typ"), "= A\n").unwrap();
+
+        let source = "#import \"lib.typ\": greeting\n= A #greeting\n";
+        let item = EpubItem::create_from_source(
+            root.join("a.typ"),
+            source,
+            &root,
+            &WorldSettings::default(),
+        )
+        .unwrap();
+        assert!(item.xhtml.contains("hello"));
+
+        // Nothing is written next to the spine files
+        let mut entries: Vec<_> = std::fs::read_dir(&root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        entries.sort();
+        assert_eq!(entries, ["a.typ", "lib.typ"]);
+    }
+
+    #[test]
+    fn test_epub_is_reproducible_with_build_date() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::write(root.join("a.typ"), "#set document(title: [Book])\n= A\n").unwrap();
+        let settings = WorldSettings {
+            build_date: DateTime::from_timestamp(1_704_067_200, 0),
+            ..WorldSettings::default()
+        };
+        let filter = ContentFilter::new(&root, &Default::default(), false).unwrap();
+        let build = |name: &str| {
+            let path = root.join(name);
+            let options = EpubOptions::from(&EpubConfig::default());
+            compile_epub_impl(&options, &path, &root, &filter, &settings, None).unwrap();
+            std::fs::read(path).unwrap()
+        };
+
+        let first = build("book.epub");
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        assert_eq!(first, build("book.epub"));
+
+        let package = read_package(&first);
+        assert!(package.contains("2024-01-01T00:00:00Z"), "{}", package);
+        let expected = derived_identifier(&root, &[root.join("a.typ")], "Book");
+        assert!(package.contains(expected.as_str()));
+
+        // The identifier depends on the spine and title
+        assert_ne!(
+            expected,
+            derived_identifier(&root, &[root.join("b.typ")], "Book")
+        );
+        assert_ne!(
+            expected,
+            derived_identifier(&root, &[root.join("a.typ")], "Other")
+        );
+    }
+
+    #[test]
+    fn test_derived_identifier_is_independent_of_checkout() {
+        let identifier = |dir_name: &str| {
+            let temp = TempDir::new().unwrap();
+            let root = temp.path().canonicalize().unwrap().join(dir_name);
+            std::fs::create_dir_all(root.join("chapters")).unwrap();
+            std::fs::write(
+                root.join("chapters/a.typ"),
+                "#set document(title: [Book])\n= A\n",
+            )
+            .unwrap();
+            let content_dir = root.join("chapters");
+            let filter = ContentFilter::new(&content_dir, &Default::default(), false).unwrap();
+            let path = root.join(format!("{}.epub", dir_name));
+            let options = EpubOptions::from(&EpubConfig::default());
+            let settings = WorldSettings::default();
+            compile_epub_impl(&options, &path, &content_dir, &filter, &settings, None).unwrap();
+            let package = read_package(&std::fs::read(path).unwrap());
+            let start = package.find("urn:uuid:").unwrap();
+            package[start..start + "urn:uuid:".len() + 36].to_string()
+        };
+        assert_eq!(identifier("rheo"), identifier("rheo-main"));
+    }
+
+    fn read_package(epub: &[u8]) -> String {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(epub)).unwrap();
+        let mut package = String::new();
+        std::io::Read::read_to_string(
+            &mut archive.by_name("EPUB/package.opf").unwrap(),
+            &mut package,
+        )
+        .unwrap();
+        package
+    }
+}
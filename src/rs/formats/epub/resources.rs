@@ -0,0 +1,230 @@
+//! Collects the binary resources (images, fonts, ...) that EPUB chapters load.
+//!
+//! Typst's HTML export inlines images as `data:` URLs. EPUB reading systems handle
+//! large inline payloads poorly, so we bundle every resource a chapter read through
+//! [`RheoWorld`] into the container and point the XHTML at the bundled copy instead.
+
+use crate::fonts::{self, ProjectFont};
+use crate::world::RheoWorld;
+use base64::Engine;
+use iref::IriRefBuf;
+use std::collections::HashMap;
+use std::path::Path;
+use typst::diag::{EcoString, eco_format};
+use typst::foundations::Bytes;
+use typst::syntax::FileId;
+use typst::utils::hash128;
+
+/// Directory (relative to the EPUB package root) holding resources loaded from packages.
+const PACKAGE_RESOURCE_DIR: &str = "packages";
+
+/// A publication resource bundled into the EPUB container.
+///
+/// See: EPUB 3.3, Publication resources <https://www.w3.org/TR/epub-33/#sec-publication-resources>
+#[derive(Debug, Clone)]
+pub struct EpubResource {
+    /// Location of the resource relative to the package document.
+    pub href: IriRefBuf,
+    /// Media type declared in the manifest.
+    pub media_type: EcoString,
+    /// Raw file contents.
+    pub data: Bytes,
+}
+
+impl EpubResource {
+    /// Manifest ID for this resource, e.g. `img/foo.jpg` becomes `res-img-foo-jpg-1a2b3c4d`.
+    ///
+    /// Sanitizing the href is lossy (`img/a-b.png` and `img/a_b.png` both become
+    /// `res-img-a-b-png`), so a short hash of the href keeps the IDs unique.
+    pub fn id(&self) -> EcoString {
+        let href = self.href.as_str();
+        let sanitized: String = href
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        eco_format!("res-{sanitized}-{:08x}", hash128(&href) as u32)
+    }
+}
+
+/// Returns the EPUB core media type for a file, or None if it is not a resource
+/// that reading systems can render (e.g. `.bib` or `.json` data files).
+///
+/// See: EPUB 3.3, Core media types <https://www.w3.org/TR/epub-33/#sec-core-media-types>
+pub fn media_type_for(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    let media_type = match ext.as_str() {
+        "gif" => "image/gif",
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "m4a" | "mp4" => "audio/mp4",
+        "css" => "text/css",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        _ => return None,
+    };
+    Some(media_type)
+}
+
+/// Stable location of a loaded file inside the EPUB package.
+///
+/// Project files keep their path relative to the compilation root, and package
+/// files are namespaced under `packages/{namespace}/{name}/{version}/`.
+fn resource_href(id: FileId) -> String {
+    let path = id
+        .vpath()
+        .as_rootless_path()
+        .to_string_lossy()
+        .replace('\\', "/");
+    match id.package() {
+        Some(spec) => format!(
+            "{PACKAGE_RESOURCE_DIR}/{}/{}/{}/{path}",
+            spec.namespace, spec.name, spec.version
+        ),
+        None => path,
+    }
+}
+
+/// Collect every EPUB-compatible resource that the world loaded during compilation.
+pub fn collect_resources(world: &RheoWorld) -> Vec<EpubResource> {
+    world
+        .loaded_files()
+        .into_iter()
+        .filter_map(|(id, data)| {
+            let media_type = media_type_for(id.vpath().as_rootless_path())?;
+            let href = IriRefBuf::new(resource_href(id)).ok()?;
+            Some(EpubResource {
+                href,
+                media_type: media_type.into(),
+                data,
+            })
+        })
+        .collect()
+}
+
+/// Bundle project fonts (when `[epub] copy_fonts = true`) with the stylesheet
+/// loading them.
+///
+/// # Arguments
+/// * `fonts` - Project fonts
+///
+/// # Errors
+/// Returns an error if a font file can't be read
+pub fn font_resources(fonts: &[ProjectFont]) -> std::io::Result<Vec<EpubResource>> {
+    let fonts = fonts::web_fonts(fonts);
+    if fonts.is_empty() {
+        return Ok(Vec::new());
+    }
+    let stylesheet = EpubResource {
+        href: IriRefBuf::new(fonts::FONTS_CSS_FILE.to_string()).expect("valid href"),
+        media_type: "text/css".into(),
+        data: Bytes::new(fonts::font_face_css(&fonts).into_bytes()),
+    };
+    let mut resources = vec![stylesheet];
+    for (href, font) in &fonts {
+        let (Ok(href), Some(media_type)) =
+            (IriRefBuf::new(href.clone()), media_type_for(&font.path))
+        else {
+            continue;
+        };
+        resources.push(EpubResource {
+            href,
+            media_type: media_type.into(),
+            data: Bytes::new(std::fs::read(&font.path)?),
+        });
+    }
+    Ok(resources)
+}
+
+/// Maps the base64 payload of a `data:` URL to the href of the bundled resource
+/// with identical contents.
+pub struct DataUrlRewriter {
+    hrefs: HashMap<String, EcoString>,
+}
+
+impl DataUrlRewriter {
+    pub fn new(resources: &[EpubResource]) -> Self {
+        let hrefs = resources
+            .iter()
+            .map(|resource| {
+                let encoded = base64::engine::general_purpose::STANDARD.encode(&resource.data);
+                (encoded, resource.href.as_str().into())
+            })
+            .collect();
+        Self { hrefs }
+    }
+
+    /// Returns the bundled resource href for a `data:` URL, or None if the URL does
+    /// not correspond to any bundled resource.
+    pub fn rewrite(&self, url: &str) -> Option<&str> {
+        let (_, payload) = url.strip_prefix("data:")?.split_once(";base64,")?;
+        self.hrefs.get(payload).map(|href| href.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use typst::syntax::VirtualPath;
+
+    fn resource(href: &str, data: &[u8]) -> EpubResource {
+        EpubResource {
+            href: IriRefBuf::new(href.to_string()).unwrap(),
+            media_type: media_type_for(Path::new(href)).unwrap().into(),
+            data: Bytes::new(data.to_vec()),
+        }
+    }
+
+    #[test]
+    fn test_media_type_for() {
+        assert_eq!(media_type_for(Path::new("img/a.JPG")), Some("image/jpeg"));
+        assert_eq!(
+            media_type_for(Path::new("img/a.svg")),
+            Some("image/svg+xml")
+        );
+        assert_eq!(
+            media_type_for(Path::new("fonts/a.woff2")),
+            Some("font/woff2")
+        );
+        assert_eq!(media_type_for(Path::new("references.bib")), None);
+        assert_eq!(media_type_for(Path::new("noext")), None);
+    }
+
+    #[test]
+    fn test_resource_href_local() {
+        let id = FileId::new(None, VirtualPath::new("img/header.svg"));
+        assert_eq!(resource_href(id), "img/header.svg");
+    }
+
+    #[test]
+    fn test_resource_id() {
+        let res = resource("img/severance-s1e1.png", b"png");
+        assert!(res.id().starts_with("res-img-severance-s1e1-png-"));
+        assert_eq!(res.id(), resource("img/severance-s1e1.png", b"other").id());
+    }
+
+    #[test]
+    fn test_resource_ids_are_unique() {
+        let ids: std::collections::HashSet<_> = ["img/a-b.png", "img/a_b.png", "img/a.b.png"]
+            .into_iter()
+            .map(|href| resource(href, b"png").id())
+            .collect();
+        assert_eq!(ids.len(), 3);
+    }
+
+    #[test]
+    fn test_data_url_rewriter() {
+        let res = resource("img/a.png", b"hello");
+        let rewriter = DataUrlRewriter::new(&[res]);
+        assert_eq!(
+            rewriter.rewrite("data:image/png;base64,aGVsbG8="),
+            Some("img/a.png")
+        );
+        assert_eq!(rewriter.rewrite("data:image/png;base64,b3RoZXI="), None);
+        assert_eq!(rewriter.rewrite("img/a.png"), None);
+    }
+}
@@ -1,5 +1,6 @@
 //! Converts Typst HTML output into portable XHTML.
 
+use super::resources::DataUrlRewriter;
 use html5ever::{ParseOpts, tendril::TendrilSink};
 use markup5ever_rcdom::{Handle, NodeData, RcDom};
 use std::{fmt::Write, slice};
@@ -28,6 +29,7 @@ fn is_void_element(tag_name: &str) -> bool {
 }
 
 /// Metadata about features used by the HTML generated by Typst.
+#[derive(Clone)]
 pub struct HtmlInfo {
     /// True if the document uses Javascript in any way.
     pub scripted: bool,
@@ -42,6 +44,7 @@ pub struct HtmlInfo {
 /// * `html_string` should be generated by Typst.
 /// * `heading_ids` is a list of CSS identifiers for all heading elements h2-h6, in the order
 ///   they appear in the document.
+/// * `resources` rewrites inline `data:` URLs in `src` attributes to bundled resource hrefs.
 ///
 /// # Notes
 /// - HTML parsing uses the [`html5ever`] crate from Servo. It should be generally accurate, although
@@ -49,7 +52,11 @@ pub struct HtmlInfo {
 ///   See: https://github.com/servo/html5ever/issues?q=is%3Aopen+is%3Aissue+label%3Aweb-compat
 /// - Eventually the XHTML functionality should be removed once it is implemented in Typst.
 ///   See: https://github.com/typst/typst/issues/6446
-pub fn html_to_portable_xhtml(html_string: &str, heading_ids: &[EcoString]) -> (String, HtmlInfo) {
+pub fn html_to_portable_xhtml(
+    html_string: &str,
+    heading_ids: &[EcoString],
+    resources: &DataUrlRewriter,
+) -> (String, HtmlInfo) {
     // TODO: should factor the XHTML-izing and portabl-izing code into separate functions.
 
     let dom = html5ever::parse_document(RcDom::default(), ParseOpts::default())
@@ -60,6 +67,7 @@ pub fn html_to_portable_xhtml(html_string: &str, heading_ids: &[EcoString]) -> (
     struct Walker<'a> {
         buf: String,
         heading_ids: slice::Iter<'a, EcoString>,
+        resources: &'a DataUrlRewriter,
         info: HtmlInfo,
     }
 
@@ -103,9 +111,14 @@ pub fn html_to_portable_xhtml(html_string: &str, heading_ids: &[EcoString]) -> (
                     }
 
                     for attr in attrs.borrow().iter() {
+                        // Point inline images at their bundled copy in the container
+                        let value = match self.resources.rewrite(&attr.value) {
+                            Some(href) if &attr.name.local == "src" => href,
+                            _ => &attr.value,
+                        };
+
                         // Escape attribute values properly for XHTML
-                        let escaped_value = attr
-                            .value
+                        let escaped_value = value
                             .replace("&", "&amp;")
                             .replace("\"", "&quot;")
                             .replace("<", "&lt;")
@@ -157,6 +170,7 @@ pub fn html_to_portable_xhtml(html_string: &str, heading_ids: &[EcoString]) -> (
     let mut walker = Walker {
         buf: String::new(),
         heading_ids: heading_ids.iter(),
+        resources,
         info: HtmlInfo {
             scripted: false,
             mathml: false,
@@ -167,6 +181,21 @@ pub fn html_to_portable_xhtml(html_string: &str, heading_ids: &[EcoString]) -> (
     (walker.buf, walker.info)
 }
 
+/// Adds a stylesheet link to the end of the `<head>` of a portable XHTML document.
+///
+/// Documents without a `<head>` are returned unchanged.
+pub fn link_stylesheet(xhtml: &str, href: &str) -> String {
+    match xhtml.find("</head>") {
+        Some(end) => format!(
+            r#"{}<link rel="stylesheet" href="{}"/>{}"#,
+            &xhtml[..end],
+            href,
+            &xhtml[end..]
+        ),
+        None => xhtml.to_string(),
+    }
+}
+
 #[test]
 fn test_html_to_xhtml() {
     let input = r#"<!DOCTYPE html>
@@ -193,6 +222,33 @@ fn test_html_to_xhtml() {
     
 </p></article></body></html>"#;
 
-    let (actual, _) = html_to_portable_xhtml(input, &["test".into()]);
+    let (actual, _) = html_to_portable_xhtml(input, &["test".into()], &DataUrlRewriter::new(&[]));
     assert_eq!(expected, actual);
 }
+
+#[test]
+fn test_html_to_xhtml_rewrites_data_urls() {
+    use super::resources::EpubResource;
+    use iref::IriRefBuf;
+    use typst::foundations::Bytes;
+
+    let input = r#"<html><body><img src="data:image/png;base64,aGVsbG8=" alt="a"></body></html>"#;
+    let resources = [EpubResource {
+        href: IriRefBuf::new("img/a.png".into()).unwrap(),
+        media_type: "image/png".into(),
+        data: Bytes::new(b"hello".to_vec()),
+    }];
+
+    let (actual, _) = html_to_portable_xhtml(input, &[], &DataUrlRewriter::new(&resources));
+    assert!(actual.contains(r#"<img src="img/a.png" alt="a"/>"#));
+}
+
+#[test]
+fn test_link_stylesheet() {
+    let xhtml = r#"<html xmlns="http://www.w3.org/1999/xhtml"><head><meta charset="utf-8"/></head><body></body></html>"#;
+    assert_eq!(
+        link_stylesheet(xhtml, "fonts.css"),
+        r#"<html xmlns="http://www.w3.org/1999/xhtml"><head><meta charset="utf-8"/><link rel="stylesheet" href="fonts.css"/></head><body></body></html>"#
+    );
+    assert_eq!(link_stylesheet("<p/>", "fonts.css"), "<p/>");
+}
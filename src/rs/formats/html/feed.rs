@@ -0,0 +1,467 @@
+//! Atom feed (`feed.xml`) generation for HTML builds.
+//!
+//! See: The Atom Syndication Format <https://www.rfc-editor.org/rfc/rfc4287>
+
+use crate::formats::html::element_text;
+use crate::formats::html::sitemap::{encode_href, page_url, source_modified};
+use crate::formats::pdf::DocumentTitle;
+use crate::{PageMeta, Result, RheoError};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::info;
+use typst::foundations::{Datetime, Smart};
+use typst_html::{HtmlDocument, HtmlElement, HtmlNode, HtmlTag};
+
+/// https://www.rfc-editor.org/rfc/rfc4287#section-4.1.1
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename = "feed")]
+pub struct Feed {
+    pub title: String,
+    pub id: String,
+    pub updated: String,
+    /// Required unless every entry has an author, see
+    /// https://www.rfc-editor.org/rfc/rfc4287#section-4.1.1
+    pub author: Person,
+    #[serde(rename = "link")]
+    pub links: Vec<Link>,
+    #[serde(rename = "entry")]
+    pub entries: Vec<Entry>,
+}
+
+/// https://www.rfc-editor.org/rfc/rfc4287#section-4.1.2
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub title: String,
+    pub link: Link,
+    pub id: String,
+    pub updated: String,
+    #[serde(rename = "author", skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<Person>,
+    #[serde(rename = "category", skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<Category>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// https://www.rfc-editor.org/rfc/rfc4287#section-4.2.7
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    #[serde(rename = "@href")]
+    pub href: String,
+    #[serde(rename = "@rel", skip_serializing_if = "Option::is_none")]
+    pub rel: Option<String>,
+}
+
+/// https://www.rfc-editor.org/rfc/rfc4287#section-4.2.2
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Category {
+    #[serde(rename = "@term")]
+    pub term: String,
+}
+
+/// https://www.rfc-editor.org/rfc/rfc4287#section-3.2
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Person {
+    pub name: String,
+}
+
+impl Feed {
+    pub fn to_xml(&self) -> std::result::Result<String, serde_xml_rs::Error> {
+        let config = serde_xml_rs::SerdeXml::new().default_namespace("http://www.w3.org/2005/Atom");
+        config.to_string(self)
+    }
+}
+
+/// A compiled HTML page to list in the feed, extracted from its document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedItem {
+    /// Source file of the page.
+    pub source: PathBuf,
+    /// Output path relative to the HTML output directory (e.g. "posts/intro.html").
+    pub href: String,
+    /// Document title, falling back to a readable version of the file name.
+    pub title: String,
+    /// Document authors.
+    pub authors: Vec<String>,
+    /// Document date, from `#set document(date: ...)` or the `<rheo>` front matter.
+    pub date: Option<DateTime<Utc>>,
+    /// Tags from the `<rheo>` front matter.
+    pub tags: Vec<String>,
+    /// Front matter description, or the text of the document's first paragraph.
+    pub summary: Option<String>,
+}
+
+impl FeedItem {
+    /// Extract feed metadata from a compiled HTML document.
+    ///
+    /// # Arguments
+    /// * `document` - The compiled document
+    /// * `meta` - Front matter of the document
+    /// * `source` - Source .typ file of the document
+    /// * `href` - Output path relative to the HTML output directory
+    pub fn from_document(
+        document: &HtmlDocument,
+        meta: &PageMeta,
+        source: &Path,
+        href: impl Into<String>,
+    ) -> Self {
+        let info = &document.info;
+        let title = match &info.title {
+            Some(title) => title.to_string(),
+            None => {
+                let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+                DocumentTitle::to_readable_name(stem)
+            }
+        };
+        let date = match &info.date {
+            Smart::Custom(Some(date)) => to_chrono(date),
+            _ => None,
+        }
+        .or_else(|| {
+            meta.date
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|d| d.and_utc())
+        });
+
+        Self {
+            source: source.to_path_buf(),
+            href: href.into(),
+            title,
+            authors: info.author.iter().map(|a| a.to_string()).collect(),
+            date,
+            tags: meta.tags.clone(),
+            summary: meta
+                .description
+                .clone()
+                .or_else(|| first_paragraph(&document.root)),
+        }
+    }
+
+    /// Time the entry was last updated: the document date if known, otherwise the
+    /// source file's modification time (clamped to the build date), otherwise the
+    /// build date or the current time.
+    fn updated(&self, build_date: Option<DateTime<Utc>>) -> DateTime<Utc> {
+        self.date
+            .or_else(|| source_modified(&self.source, build_date))
+            .unwrap_or_else(|| build_date.unwrap_or_else(Utc::now))
+    }
+}
+
+/// Convert a Typst datetime to UTC, treating times as UTC and missing times as midnight.
+fn to_chrono(date: &Datetime) -> Option<DateTime<Utc>> {
+    let date = NaiveDate::from_ymd_opt(date.year()?, date.month()?.into(), date.day()?.into())?
+        .and_hms_opt(
+            date.hour().unwrap_or(0).into(),
+            date.minute().unwrap_or(0).into(),
+            date.second().unwrap_or(0).into(),
+        )?;
+    Some(date.and_utc())
+}
+
+/// Plain text of the first `<p>` element, with whitespace collapsed.
+fn first_paragraph(element: &HtmlElement) -> Option<String> {
+    if element.tag == HtmlTag::constant("p") {
+        let text = element_text(element);
+        return (!text.is_empty()).then_some(text);
+    }
+    element.children.iter().find_map(|child| match child {
+        HtmlNode::Element(child) => first_paragraph(child),
+        _ => None,
+    })
+}
+
+fn date_format(dt: &DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// Generate the feed.xml content for the given items.
+///
+/// Items are sorted newest first and truncated to `limit`.
+///
+/// # Arguments
+/// * `title` - Feed title
+/// * `author` - Feed author, which entries without authors inherit
+/// * `base_url` - Absolute URL the HTML output directory is published at
+/// * `items` - Feed items in any order
+/// * `limit` - Maximum number of entries
+/// * `build_date` - Fixed build date that source modification times are clamped to
+pub fn generate_feed(
+    title: &str,
+    author: &str,
+    base_url: &str,
+    mut items: Vec<FeedItem>,
+    limit: usize,
+    build_date: Option<DateTime<Utc>>,
+) -> Result<String> {
+    items.sort_by_key(|item| std::cmp::Reverse(item.updated(build_date)));
+    items.truncate(limit);
+
+    let feed_url = page_url(base_url, "feed.xml");
+    let updated = items
+        .iter()
+        .map(|item| item.updated(build_date))
+        .max()
+        .unwrap_or_else(|| build_date.unwrap_or_else(Utc::now));
+
+    let feed = Feed {
+        title: title.to_string(),
+        id: feed_url.clone(),
+        updated: date_format(&updated),
+        author: Person {
+            name: author.to_string(),
+        },
+        links: vec![
+            Link {
+                href: feed_url,
+                rel: Some("self".to_string()),
+            },
+            Link {
+                href: page_url(base_url, ""),
+                rel: None,
+            },
+        ],
+        entries: items
+            .iter()
+            .map(|item| {
+                let url = page_url(base_url, &encode_href(&item.href));
+                Entry {
+                    title: item.title.clone(),
+                    link: Link {
+                        href: url.clone(),
+                        rel: None,
+                    },
+                    id: url,
+                    updated: date_format(&item.updated(build_date)),
+                    authors: item
+                        .authors
+                        .iter()
+                        .map(|name| Person { name: name.clone() })
+                        .collect(),
+                    categories: item
+                        .tags
+                        .iter()
+                        .map(|term| Category { term: term.clone() })
+                        .collect(),
+                    summary: item.summary.clone(),
+                }
+            })
+            .collect(),
+    };
+
+    feed.to_xml().map_err(|e| RheoError::HtmlGeneration {
+        count: 1,
+        errors: format!("failed to serialize feed.xml: {}", e),
+    })
+}
+
+/// Write feed.xml to the HTML output directory.
+///
+/// # Arguments
+/// * `html_dir` - HTML output directory
+/// * `title` - Feed title
+/// * `author` - Feed author, which entries without authors inherit
+/// * `base_url` - Site base URL from `[html] base_url`
+/// * `items` - Feed items in any order
+/// * `limit` - Maximum number of entries
+/// * `build_date` - Fixed build date that source modification times are clamped to
+pub fn write_feed(
+    html_dir: &Path,
+    title: &str,
+    author: &str,
+    base_url: &str,
+    items: Vec<FeedItem>,
+    limit: usize,
+    build_date: Option<DateTime<Utc>>,
+) -> Result<()> {
+    let count = items.len().min(limit);
+    let feed = generate_feed(title, author, base_url, items, limit, build_date)?;
+    let path = html_dir.join("feed.xml");
+    std::fs::write(&path, feed)
+        .map_err(|e| RheoError::io(e, format!("writing feed to {:?}", path)))?;
+    info!(output = %path.display(), entries = count, "generated feed");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(href: &str, title: &str, date: Option<(i32, u32, u32)>) -> FeedItem {
+        FeedItem {
+            source: PathBuf::from("/nonexistent/page.typ"),
+            href: href.to_string(),
+            title: title.to_string(),
+            authors: vec!["Jane Doe".to_string()],
+            tags: vec!["news".to_string()],
+            date: date.map(|(y, m, d)| {
+                NaiveDate::from_ymd_opt(y, m, d)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .and_utc()
+            }),
+            summary: Some("First paragraph.".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_generate_feed() {
+        let items = vec![
+            item("posts/old.html", "Old", Some((2024, 1, 1))),
+            item("posts/new.html", "New", Some((2025, 6, 1))),
+        ];
+        let xml = generate_feed(
+            "My Blog",
+            "Blog Team",
+            "https://example.com/",
+            items,
+            20,
+            None,
+        )
+        .unwrap();
+
+        assert!(xml.contains(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#));
+        assert!(xml.contains("<title>My Blog</title>"));
+        assert!(xml.contains("<updated>2025-06-01T00:00:00Z</updated>"));
+        assert!(xml.contains(r#"<link href="https://example.com/feed.xml" rel="self""#));
+        assert!(xml.contains("<id>https://example.com/posts/new.html</id>"));
+        assert!(xml.contains("<author><name>Jane Doe</name></author>"));
+        assert!(
+            xml.contains(r#"<category term="news" />"#)
+                || xml.contains(r#"<category term="news"/>"#)
+        );
+        assert!(xml.contains("<summary>First paragraph.</summary>"));
+
+        // Newest entry first
+        let new_pos = xml.find("<title>New</title>").unwrap();
+        let old_pos = xml.find("<title>Old</title>").unwrap();
+        assert!(new_pos < old_pos);
+    }
+
+    #[test]
+    fn test_feed_author_for_entries_without_authors() {
+        let mut anonymous = item("a.html", "A", Some((2024, 1, 1)));
+        anonymous.authors.clear();
+        let xml = generate_feed(
+            "Blog",
+            "Blog Team",
+            "https://example.com",
+            vec![anonymous],
+            20,
+            None,
+        )
+        .unwrap();
+
+        // The entry has no author of its own, so the feed must have one
+        assert_eq!(xml.matches("<author>").count(), 1);
+        let author = xml.find("<author><name>Blog Team</name></author>").unwrap();
+        assert!(author < xml.find("<entry>").unwrap());
+    }
+
+    #[test]
+    fn test_feed_encodes_paths() {
+        let items = vec![
+            item("my posts/café.html", "Café", Some((2024, 1, 1))),
+            item("100%.html", "Percent", Some((2024, 2, 1))),
+        ];
+        let xml =
+            generate_feed("Blog", "Blog Team", "https://example.com", items, 20, None).unwrap();
+        assert!(xml.contains(r#"<link href="https://example.com/my%20posts/caf%C3%A9.html""#));
+        assert!(xml.contains("<id>https://example.com/my%20posts/caf%C3%A9.html</id>"));
+        assert!(xml.contains("<id>https://example.com/100%25.html</id>"));
+    }
+
+    #[test]
+    fn test_generate_feed_limit() {
+        let items = vec![
+            item("a.html", "A", Some((2024, 1, 1))),
+            item("b.html", "B", Some((2024, 2, 1))),
+            item("c.html", "C", Some((2024, 3, 1))),
+        ];
+        let xml =
+            generate_feed("Blog", "Blog Team", "https://example.com", items, 2, None).unwrap();
+        assert!(xml.contains("<title>C</title>"));
+        assert!(xml.contains("<title>B</title>"));
+        assert!(!xml.contains("<title>A</title>"));
+    }
+
+    #[test]
+    fn test_generate_feed_updated_fallback() {
+        let build_date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc();
+
+        // Entries without a date or readable source use the build date
+        let items = vec![item("a.html", "A", None)];
+        let xml = generate_feed(
+            "Blog",
+            "Blog Team",
+            "https://example.com",
+            items,
+            20,
+            Some(build_date),
+        )
+        .unwrap();
+        assert_eq!(
+            xml.matches("<updated>2025-06-01T12:00:00Z</updated>")
+                .count(),
+            2
+        );
+
+        // An empty feed is updated at the build date, or now, not in 1970
+        let xml = generate_feed(
+            "Blog",
+            "Blog Team",
+            "https://example.com",
+            vec![],
+            20,
+            Some(build_date),
+        )
+        .unwrap();
+        assert!(xml.contains("<updated>2025-06-01T12:00:00Z</updated>"));
+        let xml =
+            generate_feed("Blog", "Blog Team", "https://example.com", vec![], 20, None).unwrap();
+        assert!(!xml.contains("<updated>1970"));
+    }
+
+    #[test]
+    fn test_feed_item_from_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("post.typ");
+        std::fs::write(
+            &input,
+            "#set document(title: \"Hello\", author: \"Jane\", date: datetime(year: 2025, month: 3, day: 14))\n\n= Heading\n\nThe *first*\nparagraph.\n\nThe second.",
+        )
+        .unwrap();
+        let document = crate::formats::html::compile_html_to_document(
+            &input,
+            dir.path(),
+            crate::OutputFormat::Html,
+        )
+        .unwrap();
+
+        let meta = PageMeta::query(&document.introspector).unwrap();
+        let item = FeedItem::from_document(&document, &meta, &input, "post.html");
+        assert_eq!(item.title, "Hello");
+        assert_eq!(item.authors, vec!["Jane"]);
+        assert_eq!(
+            item.date.map(|d| date_format(&d)),
+            Some("2025-03-14T00:00:00Z".to_string())
+        );
+        assert_eq!(item.summary.as_deref(), Some("The first paragraph."));
+    }
+
+    #[test]
+    fn test_to_chrono() {
+        let date = Datetime::from_ymd(2025, 3, 14).unwrap();
+        assert_eq!(
+            to_chrono(&date).map(|d| date_format(&d)),
+            Some("2025-03-14T00:00:00Z".to_string())
+        );
+        let time = Datetime::from_hms(12, 0, 0).unwrap();
+        assert_eq!(to_chrono(&time), None);
+    }
+}
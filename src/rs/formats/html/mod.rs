@@ -1,12 +1,16 @@
+pub mod feed;
+pub mod search;
+pub mod sitemap;
+
 use crate::compile::RheoCompileOptions;
-use crate::config::HtmlOptions;
+use crate::config::{HtmlOptions, OutputLayout};
 use crate::formats::common::{ExportErrorType, handle_export_errors, unwrap_compilation_result};
 use crate::postprocess;
-use crate::world::RheoWorld;
-use crate::{OutputFormat, Result, RheoError};
-use std::path::Path;
+use crate::world::{RheoWorld, WorldSettings};
+use crate::{OutputFormat, PageMeta, Result, RheoError};
+use std::path::{Component, Path, PathBuf};
 use tracing::{debug, info};
-use typst_html::HtmlDocument;
+use typst_html::{HtmlDocument, HtmlElement, HtmlNode};
 
 pub fn compile_html_to_document(
     input: &Path,
@@ -18,7 +22,15 @@ pub fn compile_html_to_document(
 
     // Compile the document to HtmlDocument
     info!(input = %input.display(), "compiling to HTML");
-    let result = typst::compile::<HtmlDocument>(&world);
+    compile_world_to_document(&world)
+}
+
+/// Compile the main file of an existing world to an HtmlDocument.
+///
+/// Useful when the caller needs to inspect the world after compilation
+/// (e.g. to collect the resources the document loaded).
+pub fn compile_world_to_document(world: &RheoWorld) -> Result<HtmlDocument> {
+    let result = typst::compile::<HtmlDocument>(world);
 
     // Filter out HTML development warning
     let html_filter = |w: &typst::diag::SourceDiagnostic| {
@@ -26,7 +38,7 @@ pub fn compile_html_to_document(
             .contains("html export is under active development and incomplete")
     };
 
-    unwrap_compilation_result(Some(&world), result, Some(html_filter))
+    unwrap_compilation_result(Some(world), result, Some(html_filter))
 }
 
 pub fn compile_document_to_string(document: &HtmlDocument) -> Result<String> {
@@ -43,33 +55,36 @@ pub fn compile_document_to_string(document: &HtmlDocument) -> Result<String> {
 /// Uses format-aware RheoWorld for automatic link transformation (.typ → .html).
 /// Transformations happen on-demand during Typst compilation (including imports).
 ///
-/// Pipeline: Compile (with transformations) → Export → Inject Head → Write
+/// Pipeline: Compile (with transformations) → Export → Apply Layout → Inject Head → Inject Nav → Write
 fn compile_html_impl_fresh(
     input: &Path,
     output: &Path,
     root: &Path,
+    settings: &WorldSettings,
     html_options: &HtmlOptions,
-) -> Result<()> {
+) -> Result<HtmlDocument> {
     // Compile to HTML document (transformations happen in RheoWorld)
-    let doc = compile_html_to_document(input, root, OutputFormat::Html)?;
+    let mut world = RheoWorld::new(root, input, Some(OutputFormat::Html))?.with_settings(settings);
+    world.set_layout(html_options.layout);
+    world.set_site_nav(html_options.nav.as_ref());
+    info!(input = %input.display(), "compiling to HTML");
+    let doc = compile_world_to_document(&world)?;
     let html_string = compile_document_to_string(&doc)?;
 
-    // Inject CSS and font links into <head>
-    let stylesheets: Vec<&str> = html_options
-        .stylesheets
-        .iter()
-        .map(|s| s.as_str())
-        .collect();
-    let fonts: Vec<&str> = html_options.fonts.iter().map(|s| s.as_str()).collect();
-    let html_string = postprocess::inject_head_links(&html_string, &stylesheets, &fonts)?;
+    // Read front matter, then apply layout, inject head tags and site navigation
+    let meta = PageMeta::query(&doc.introspector)?;
+    let html_string = postprocess_page(&html_string, input, html_options, &meta)?;
 
     // Write to file
     debug!(size = html_string.len(), "writing HTML file");
     std::fs::write(output, &html_string)
         .map_err(|e| RheoError::io(e, format!("writing HTML file to {:?}", output)))?;
 
+    // Copy local files the page links to (images, downloads, favicons)
+    copy_referenced_assets(&html_string, input, output, root, html_options.layout)?;
+
     info!(output = %output.display(), "successfully compiled to HTML");
-    Ok(())
+    Ok(doc)
 }
 
 /// Implementation: Compile a Typst document to HTML (incremental compilation)
@@ -78,7 +93,7 @@ fn compile_html_impl_fresh(
 /// Reuses existing RheoWorld instance for compilation (enabling incremental compilation
 /// through Typst's comemo caching system).
 ///
-/// Pipeline: Update World → Compile (with transformations) → Export → Inject Head → Write
+/// Pipeline: Update World → Compile (with transformations) → Export → Apply Layout → Inject Head → Inject Nav → Write
 ///
 /// # Arguments
 /// * `world` - Existing RheoWorld instance (will be updated with new main file)
@@ -86,13 +101,13 @@ fn compile_html_impl_fresh(
 /// * `output` - Path where the HTML should be written
 /// * `root` - Project root path (unused, for API consistency)
 /// * `repo_root` - Repository root path (unused, for API consistency)
-/// * `html_options` - HTML-specific options (stylesheets, fonts)
+/// * `html_options` - HTML-specific options (stylesheets, fonts, site navigation)
 fn compile_html_impl(
     world: &RheoWorld,
     input: &Path,
     output: &Path,
     html_options: &HtmlOptions,
-) -> Result<()> {
+) -> Result<HtmlDocument> {
     // Compile to HTML document (transformations happen in RheoWorld)
     info!(input = %input.display(), "compiling to HTML");
     let result = typst::compile::<HtmlDocument>(world);
@@ -110,22 +125,26 @@ fn compile_html_impl(
     let html_string =
         typst_html::html(&document).map_err(|e| handle_export_errors(e, ExportErrorType::Html))?;
 
-    // Inject CSS and font links into <head>
-    let stylesheets: Vec<&str> = html_options
-        .stylesheets
-        .iter()
-        .map(|s| s.as_str())
-        .collect();
-    let fonts: Vec<&str> = html_options.fonts.iter().map(|s| s.as_str()).collect();
-    let html_string = postprocess::inject_head_links(&html_string, &stylesheets, &fonts)?;
+    // Read front matter, then apply layout, inject head tags and site navigation
+    let meta = PageMeta::query(&document.introspector)?;
+    let html_string = postprocess_page(&html_string, input, html_options, &meta)?;
 
     // Write to file
     debug!(size = html_string.len(), "writing HTML file");
     std::fs::write(output, &html_string)
         .map_err(|e| RheoError::io(e, format!("writing HTML file to {:?}", output)))?;
 
+    // Copy local files the page links to (images, downloads, favicons)
+    copy_referenced_assets(
+        &html_string,
+        input,
+        output,
+        world.root(),
+        html_options.layout,
+    )?;
+
     info!(output = %output.display(), "successfully compiled to HTML");
-    Ok(())
+    Ok(document)
 }
 
 // ============================================================================
@@ -143,16 +162,24 @@ fn compile_html_impl(
 /// * `html_options` - HTML-specific options (stylesheets, fonts for head injection)
 ///
 /// # Returns
-/// * `Result<()>` - Success or compilation error
-pub fn compile_html_new(options: RheoCompileOptions, html_options: HtmlOptions) -> Result<()> {
+/// * `Result<HtmlDocument>` - The compiled document (e.g. for feed metadata) or compilation error
+pub fn compile_html_new(
+    options: RheoCompileOptions,
+    html_options: HtmlOptions,
+) -> Result<HtmlDocument> {
     match options.world {
         // Incremental compilation (reuse existing world)
-        Some(world) => compile_html_impl(world, &options.input, &options.output, &html_options),
+        Some(world) => {
+            world.set_layout(html_options.layout);
+            world.set_site_nav(html_options.nav.as_ref());
+            compile_html_impl(world, &options.input, &options.output, &html_options)
+        }
         // Fresh compilation (create new world)
         None => compile_html_impl_fresh(
             &options.input,
             &options.output,
             &options.root,
+            &options.settings,
             &html_options,
         ),
     }
@@ -161,3 +188,295 @@ pub fn compile_html_new(options: RheoCompileOptions, html_options: HtmlOptions)
 // ============================================================================
 // Helper functions
 // ============================================================================
+
+/// Plain text of an element and its descendants, with whitespace collapsed.
+pub fn element_text(element: &HtmlElement) -> String {
+    const INLINE_TAGS: &[&str] = &[
+        "a", "abbr", "b", "cite", "code", "em", "i", "kbd", "mark", "q", "s", "small", "span",
+        "strong", "sub", "sup", "u",
+    ];
+
+    fn collect_text(element: &HtmlElement, buf: &mut String) {
+        for child in &element.children {
+            match child {
+                HtmlNode::Text(text, _) => {
+                    buf.push_str(text);
+                }
+                HtmlNode::Element(child) => {
+                    // Keep words of adjacent block elements apart
+                    if !INLINE_TAGS.contains(&child.tag.resolve().as_str()) {
+                        buf.push(' ');
+                    }
+                    collect_text(child, buf);
+                }
+                HtmlNode::Tag(_) | HtmlNode::Frame(_) => {}
+            }
+        }
+    }
+
+    let mut text = String::new();
+    collect_text(element, &mut text);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Post-process an exported page: wrap it in its layout, then inject CSS/font
+/// links, the search widget and site navigation.
+///
+/// Navigation is rendered into the layout's `{{nav}}` placeholder if it has one,
+/// and prepended to `<body>` otherwise.
+///
+/// # Arguments
+/// * `html` - The exported HTML
+/// * `input` - Path to the source .typ file (identifies the current page in the navigation)
+/// * `html_options` - HTML-specific options (stylesheets, fonts, site navigation, layout, search)
+/// * `meta` - Front matter of the page (description and tags become `<meta>` tags)
+fn postprocess_page(
+    html: &str,
+    input: &Path,
+    html_options: &HtmlOptions,
+    meta: &PageMeta,
+) -> Result<String> {
+    let nav = html_options
+        .nav
+        .as_ref()
+        .map(|nav| (nav, nav.position(input)));
+
+    // Wrap the page body in its layout
+    let (html, nav_placed) = match &html_options.page_layout {
+        Some(layout) => {
+            let nav_html = match nav {
+                Some((nav, current)) if layout.places_nav() => {
+                    postprocess::render_site_nav(nav, current)?
+                }
+                _ => String::new(),
+            };
+            (layout.apply(html, &nav_html)?, layout.places_nav())
+        }
+        None => (html.to_string(), false),
+    };
+
+    // Inject CSS and font links into <head>
+    let stylesheets: Vec<&str> = html_options
+        .stylesheets
+        .iter()
+        .map(|s| s.as_str())
+        .collect();
+    let fonts: Vec<&str> = html_options.fonts.iter().map(|s| s.as_str()).collect();
+    let html = postprocess::inject_head_links(&html, &stylesheets, &fonts)?;
+
+    // Describe the page with its front matter
+    let keywords = meta.tags.join(", ");
+    let mut tags = Vec::new();
+    if let Some(description) = &meta.description {
+        tags.push(("description", description.as_str()));
+    }
+    if !keywords.is_empty() {
+        tags.push(("keywords", keywords.as_str()));
+    }
+    let html = postprocess::inject_head_meta(&html, &tags)?;
+
+    // Load the search widget
+    let html = match &html_options.search_script {
+        Some(src) => postprocess::inject_head_script(&html, src)?,
+        None => html,
+    };
+
+    // Inject site navigation when an HTML spine is configured
+    match nav {
+        Some((nav, current)) if !nav_placed => postprocess::inject_site_nav(&html, nav, current),
+        _ => Ok(html),
+    }
+}
+
+/// Copy local files referenced from `src`/`href` attributes next to the HTML output.
+///
+/// References are resolved relative to the source file and written to the same
+/// relative location from the output file, so the emitted URLs resolve unchanged.
+/// External URLs, fragments, other pages and files outside `root` are skipped.
+///
+/// # Arguments
+/// * `html` - The exported HTML
+/// * `input` - Path to the source .typ file
+/// * `output` - Path where the HTML was written
+/// * `root` - Compilation root; referenced files must live inside it
+/// * `layout` - Output layout, used to keep copies inside the HTML output directory
+fn copy_referenced_assets(
+    html: &str,
+    input: &Path,
+    output: &Path,
+    root: &Path,
+    layout: OutputLayout,
+) -> Result<()> {
+    let (Ok(root), Ok(input)) = (root.canonicalize(), input.canonicalize()) else {
+        return Ok(());
+    };
+    let (Some(input_dir), Some(output_dir)) = (input.parent(), output.parent()) else {
+        return Ok(());
+    };
+
+    // Directory of the output file relative to the HTML output root
+    let base = match layout {
+        OutputLayout::Nested => input_dir.strip_prefix(&root).unwrap_or(Path::new("")),
+        OutputLayout::Flat => Path::new(""),
+    };
+
+    let dom = postprocess::dom::HtmlDom::parse(html)?;
+    for url in dom.attribute_values(&["src", "href"]) {
+        if local_asset_path(&url, base).is_none() {
+            continue;
+        }
+        let path = url.split(['#', '?']).next().unwrap_or_default();
+        let source = input_dir.join(path);
+        let in_root = source
+            .canonicalize()
+            .is_ok_and(|path| path.starts_with(&root) && path.is_file());
+        if in_root {
+            crate::output::copy_asset(&source, &output_dir.join(path))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolve a URL to a path relative to the HTML output root if it points to a local asset.
+///
+/// Returns None for external URLs (`https:`, `mailto:`, `data:`), absolute paths,
+/// fragment-only links, HTML/Typst pages and paths escaping the output root.
+///
+/// # Arguments
+/// * `url` - The referenced URL
+/// * `base` - Directory of the referencing page relative to the output root
+fn local_asset_path(url: &str, base: &Path) -> Option<PathBuf> {
+    let path = url.split(['#', '?']).next().unwrap_or_default();
+    if path.is_empty() || path.starts_with('/') || path.contains(':') {
+        return None;
+    }
+
+    let mut normalized = PathBuf::new();
+    for component in base.join(path).components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir if normalized.pop() => {}
+            _ => return None,
+        }
+    }
+
+    let is_page = normalized
+        .extension()
+        .is_some_and(|ext| ext == "html" || ext == "typ");
+    if normalized.as_os_str().is_empty() || is_page {
+        return None;
+    }
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_asset_path() {
+        let asset = |url| local_asset_path(url, Path::new(""));
+        assert_eq!(asset("img/a.png"), Some(PathBuf::from("img/a.png")));
+        assert_eq!(
+            asset("./notes.pdf#page=2"),
+            Some(PathBuf::from("notes.pdf"))
+        );
+        assert_eq!(
+            asset("img/../favicon.ico?v=1"),
+            Some(PathBuf::from("favicon.ico"))
+        );
+        assert_eq!(asset("https://example.com/a.png"), None);
+        assert_eq!(asset("data:image/png;base64,AAAA"), None);
+        assert_eq!(asset("mailto:me@example.com"), None);
+        assert_eq!(asset("/absolute.png"), None);
+        assert_eq!(asset("#section"), None);
+        assert_eq!(asset("other.html#intro"), None);
+        assert_eq!(asset("../outside.png"), None);
+    }
+
+    #[test]
+    fn test_postprocess_page_with_layout() {
+        use crate::postprocess::PageLayout;
+        use crate::postprocess::site_nav::{NavEntry, SiteNav};
+
+        let nav = SiteNav {
+            title: None,
+            entries: vec![NavEntry {
+                source: PathBuf::from("index.typ"),
+                href: "index.html".to_string(),
+                title: "Home".to_string(),
+                label: "index".to_string(),
+            }],
+        };
+        let page = "<html><head><title>Home</title></head><body><p>Hello</p></body></html>";
+        let layout = "<html><head><title>{{title}}</title></head><body><header>{{nav}}</header><main>{{content}}</main></body></html>";
+        let meta = PageMeta {
+            description: Some("A greeting".to_string()),
+            tags: vec!["a".to_string(), "b".to_string()],
+            ..PageMeta::default()
+        };
+        let options = HtmlOptions {
+            nav: Some(nav),
+            page_layout: Some(PageLayout::new(layout)),
+            ..HtmlOptions::default()
+        };
+
+        let html = postprocess_page(page, Path::new("index.typ"), &options, &meta).unwrap();
+        assert!(html.contains(r#"<link rel="stylesheet" href="style.css">"#));
+        assert!(html.contains(r#"<header><nav class="rheo-nav" aria-label="Site">"#));
+        assert!(html.contains("<main><p>Hello</p></main>"));
+        assert!(html.contains(r#"<meta name="description" content="A greeting">"#));
+        assert!(html.contains(r#"<meta name="keywords" content="a, b">"#));
+        // Navigation placed by the layout is not injected a second time
+        assert_eq!(html.matches("rheo-nav").count(), 1);
+
+        // Layouts without {{nav}} get the navigation prepended to <body>
+        let options = HtmlOptions {
+            page_layout: Some(PageLayout::new("<body><main>{{content}}</main></body>")),
+            ..options
+        };
+        let html = postprocess_page(page, Path::new("index.typ"), &options, &meta).unwrap();
+        assert!(html.contains(r#"<body><nav class="rheo-nav""#));
+    }
+
+    #[test]
+    fn test_local_asset_path_nested_page() {
+        let base = Path::new("posts");
+        assert_eq!(
+            local_asset_path("../img/a.png", base),
+            Some(PathBuf::from("img/a.png"))
+        );
+        assert_eq!(local_asset_path("../../outside.png", base), None);
+    }
+
+    #[test]
+    fn test_copy_referenced_assets() {
+        use tempfile::TempDir;
+
+        let content = TempDir::new().unwrap();
+        let build = TempDir::new().unwrap();
+        std::fs::create_dir_all(content.path().join("img")).unwrap();
+        std::fs::create_dir_all(content.path().join("posts")).unwrap();
+        std::fs::write(content.path().join("img/a.png"), b"png").unwrap();
+        std::fs::write(content.path().join("notes.pdf"), b"pdf").unwrap();
+        std::fs::write(content.path().join("posts/intro.typ"), b"= Intro").unwrap();
+
+        let html = r#"<html><body><img src="../img/a.png"><a href="../notes.pdf">n</a><a href="../missing.pdf">m</a><a href="page.html">p</a></body></html>"#;
+        let input = content.path().join("posts/intro.typ");
+        let output = build.path().join("posts/intro.html");
+        copy_referenced_assets(html, &input, &output, content.path(), OutputLayout::Nested)
+            .unwrap();
+
+        assert!(build.path().join("img/a.png").exists());
+        assert!(build.path().join("notes.pdf").exists());
+        assert!(!build.path().join("missing.pdf").exists());
+
+        // Flat layout: references escaping the output directory are not copied
+        let flat = TempDir::new().unwrap();
+        let output = flat.path().join("intro.html");
+        copy_referenced_assets(html, &input, &output, content.path(), OutputLayout::Flat).unwrap();
+        assert!(!flat.path().join("img/a.png").exists());
+    }
+}
@@ -0,0 +1,151 @@
+//! Client-side search for HTML builds: `search-index.json` and an optional widget.
+//!
+//! The index lists every compiled page with its title, headings and plain text.
+//! The bundled widget (`rheo-search.js`) fetches it and searches in the browser.
+
+use crate::formats::common::query_headings;
+use crate::formats::html::element_text;
+use crate::{Result, RheoError};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::{debug, info};
+use typst_html::{HtmlDocument, HtmlElement, HtmlNode, HtmlTag};
+
+/// File name of the search index in the HTML output directory.
+pub const SEARCH_INDEX_FILE: &str = "search-index.json";
+
+/// File name of the search widget script in the HTML output directory.
+pub const SEARCH_WIDGET_FILE: &str = "rheo-search.js";
+
+const SEARCH_WIDGET_JS: &str = include_str!("../../../templates/html/rheo-search.js");
+
+/// A page in the search index.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SearchEntry {
+    /// Output path relative to the HTML output directory (e.g. "posts/intro.html").
+    pub url: String,
+    /// Page title.
+    pub title: String,
+    /// Plain text of the page's headings, in document order.
+    pub headings: Vec<String>,
+    /// Plain text of the page body.
+    pub text: String,
+}
+
+impl SearchEntry {
+    /// Build a search entry from a compiled HTML document.
+    ///
+    /// # Arguments
+    /// * `document` - The compiled document
+    /// * `url` - Output path relative to the HTML output directory
+    /// * `title` - Page title
+    pub fn from_document(
+        document: &HtmlDocument,
+        url: impl Into<String>,
+        title: impl Into<String>,
+    ) -> Self {
+        let body = find_body(&document.root).unwrap_or(&document.root);
+        Self {
+            url: url.into(),
+            title: title.into(),
+            headings: query_headings(document)
+                .into_iter()
+                .map(|heading| heading.text.to_string())
+                .collect(),
+            text: element_text(body),
+        }
+    }
+}
+
+/// Find the `<body>` element below the document root.
+fn find_body(element: &HtmlElement) -> Option<&HtmlElement> {
+    if element.tag == HtmlTag::constant("body") {
+        return Some(element);
+    }
+    element.children.iter().find_map(|child| match child {
+        HtmlNode::Element(child) => find_body(child),
+        _ => None,
+    })
+}
+
+/// Generate the search-index.json content.
+pub fn generate_search_index(entries: &[SearchEntry]) -> Result<String> {
+    serde_json::to_string(entries).map_err(|e| RheoError::HtmlGeneration {
+        count: 1,
+        errors: format!("failed to serialize {}: {}", SEARCH_INDEX_FILE, e),
+    })
+}
+
+/// Write search-index.json and, if enabled, the search widget script.
+///
+/// # Arguments
+/// * `html_dir` - HTML output directory
+/// * `entries` - Compiled pages
+/// * `widget` - Whether to write the bundled search widget
+pub fn write_search_files(html_dir: &Path, entries: &[SearchEntry], widget: bool) -> Result<()> {
+    let path = html_dir.join(SEARCH_INDEX_FILE);
+    std::fs::write(&path, generate_search_index(entries)?)
+        .map_err(|e| RheoError::io(e, format!("writing search index to {:?}", path)))?;
+    info!(output = %path.display(), pages = entries.len(), "generated search index");
+
+    if widget {
+        let path = html_dir.join(SEARCH_WIDGET_FILE);
+        std::fs::write(&path, SEARCH_WIDGET_JS)
+            .map_err(|e| RheoError::io(e, format!("writing search widget to {:?}", path)))?;
+        debug!(output = %path.display(), "wrote search widget");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_entry_from_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("guide.typ");
+        std::fs::write(
+            &input,
+            "#set document(title: \"Guide\")\n\n= Install\n\nRun the *installer*.\n\n== On Linux\n\nUse your package manager.",
+        )
+        .unwrap();
+        let document = crate::formats::html::compile_html_to_document(
+            &input,
+            dir.path(),
+            crate::OutputFormat::Html,
+        )
+        .unwrap();
+
+        let entry = SearchEntry::from_document(&document, "guide.html", "Guide");
+        assert_eq!(entry.url, "guide.html");
+        assert_eq!(entry.headings, vec!["Install", "On Linux"]);
+        assert_eq!(
+            entry.text,
+            "Install Run the installer. On Linux Use your package manager."
+        );
+    }
+
+    #[test]
+    fn test_write_search_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let entries = vec![SearchEntry {
+            url: "posts/a.html".to_string(),
+            title: "A \"quoted\" title".to_string(),
+            headings: vec!["Intro".to_string()],
+            text: "Body".to_string(),
+        }];
+
+        write_search_files(dir.path(), &entries, false).unwrap();
+        let index = std::fs::read_to_string(dir.path().join(SEARCH_INDEX_FILE)).unwrap();
+        assert_eq!(
+            index,
+            r#"[{"url":"posts/a.html","title":"A \"quoted\" title","headings":["Intro"],"text":"Body"}]"#
+        );
+        assert!(!dir.path().join(SEARCH_WIDGET_FILE).exists());
+
+        write_search_files(dir.path(), &entries, true).unwrap();
+        assert!(dir.path().join(SEARCH_WIDGET_FILE).exists());
+    }
+}
@@ -0,0 +1,243 @@
+//! Search-engine discovery files (`sitemap.xml`, `robots.txt`) for HTML builds.
+//!
+//! See: Sitemaps XML format <https://www.sitemaps.org/protocol.html>
+
+use crate::{Result, RheoError};
+use chrono::{DateTime, NaiveDate, Utc};
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// https://www.sitemaps.org/protocol.html#urlsetdef
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename = "urlset")]
+pub struct UrlSet {
+    #[serde(rename = "url")]
+    pub urls: Vec<Url>,
+}
+
+/// https://www.sitemaps.org/protocol.html#urldef
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Url {
+    /// Absolute URL of the page.
+    pub loc: String,
+    /// Last modification date in W3C Datetime format (YYYY-MM-DD).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastmod: Option<String>,
+}
+
+impl UrlSet {
+    pub fn to_xml(&self) -> std::result::Result<String, serde_xml_rs::Error> {
+        let config = serde_xml_rs::SerdeXml::new()
+            .default_namespace("http://www.sitemaps.org/schemas/sitemap/0.9");
+        config.to_string(self)
+    }
+}
+
+/// A compiled HTML page to list in the sitemap.
+#[derive(Debug, Clone)]
+pub struct SitemapPage {
+    /// Source file of the page.
+    pub source: PathBuf,
+    /// Output path relative to the HTML output directory (e.g. "posts/intro.html").
+    pub href: String,
+    /// Document date, preferred over the source modification time for `lastmod`.
+    pub date: Option<NaiveDate>,
+}
+
+/// Join the site base URL and a page path relative to the HTML output directory.
+pub fn page_url(base_url: &str, href: &str) -> String {
+    format!("{}/{}", base_url.trim_end_matches('/'), href)
+}
+
+/// Characters that must be percent-encoded in a URL path segment.
+///
+/// See: URL Standard, path percent-encode set <https://url.spec.whatwg.org/#path-percent-encode-set>
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Percent-encode each segment of a page path relative to the HTML output directory,
+/// as sitemaps and feeds require URLs to be escaped (e.g. "my posts/é.html" →
+/// "my%20posts/%C3%A9.html").
+pub(crate) fn encode_href(href: &str) -> String {
+    href.split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Modification time of a source file, clamped to the fixed build date (if any)
+/// so that files touched after it don't change the output.
+pub fn source_modified(source: &Path, build_date: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    let modified = DateTime::<Utc>::from(std::fs::metadata(source).ok()?.modified().ok()?);
+    Some(build_date.map_or(modified, |date| modified.min(date)))
+}
+
+/// Last modification date of a page: the document date if known, otherwise the
+/// source file's modification time.
+fn lastmod(page: &SitemapPage, build_date: Option<DateTime<Utc>>) -> Option<String> {
+    let date = page.date.or_else(|| {
+        source_modified(&page.source, build_date).map(|modified| modified.date_naive())
+    })?;
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
+/// Generate the sitemap.xml content for the given pages.
+///
+/// # Arguments
+/// * `base_url` - Absolute URL the HTML output directory is published at
+/// * `pages` - Compiled pages, listed in the given order
+/// * `build_date` - Fixed build date that source modification times are clamped to
+pub fn generate_sitemap(
+    base_url: &str,
+    pages: &[SitemapPage],
+    build_date: Option<DateTime<Utc>>,
+) -> Result<String> {
+    let urlset = UrlSet {
+        urls: pages
+            .iter()
+            .map(|page| Url {
+                loc: page_url(base_url, &encode_href(&page.href)),
+                lastmod: lastmod(page, build_date),
+            })
+            .collect(),
+    };
+    urlset.to_xml().map_err(|e| RheoError::HtmlGeneration {
+        count: 1,
+        errors: format!("failed to serialize sitemap.xml: {}", e),
+    })
+}
+
+/// Generate robots.txt content allowing all crawlers, pointing to the sitemap if known.
+pub fn generate_robots_txt(base_url: Option<&str>) -> String {
+    let mut robots = String::from("User-agent: *\nAllow: /\n");
+    if let Some(base_url) = base_url {
+        robots.push_str(&format!(
+            "\nSitemap: {}\n",
+            page_url(base_url, "sitemap.xml")
+        ));
+    }
+    robots
+}
+
+/// Write sitemap.xml (if a base URL is configured) and robots.txt (if enabled).
+///
+/// # Arguments
+/// * `html_dir` - HTML output directory
+/// * `base_url` - Optional site base URL from `[html] base_url`
+/// * `robots` - Whether to write robots.txt
+/// * `pages` - Compiled pages to list in the sitemap
+/// * `build_date` - Fixed build date that source modification times are clamped to
+pub fn write_site_files(
+    html_dir: &Path,
+    base_url: Option<&str>,
+    robots: bool,
+    pages: &[SitemapPage],
+    build_date: Option<DateTime<Utc>>,
+) -> Result<()> {
+    if let Some(base_url) = base_url {
+        let sitemap = generate_sitemap(base_url, pages, build_date)?;
+        let path = html_dir.join("sitemap.xml");
+        std::fs::write(&path, sitemap)
+            .map_err(|e| RheoError::io(e, format!("writing sitemap to {:?}", path)))?;
+        info!(output = %path.display(), pages = pages.len(), "generated sitemap");
+    }
+
+    if robots {
+        let path = html_dir.join("robots.txt");
+        std::fs::write(&path, generate_robots_txt(base_url))
+            .map_err(|e| RheoError::io(e, format!("writing robots.txt to {:?}", path)))?;
+        debug!(output = %path.display(), "generated robots.txt");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(href: &str, date: Option<NaiveDate>) -> SitemapPage {
+        SitemapPage {
+            source: PathBuf::from("/nonexistent/page.typ"),
+            href: href.to_string(),
+            date,
+        }
+    }
+
+    #[test]
+    fn test_page_url() {
+        assert_eq!(
+            page_url("https://example.com/", "posts/a.html"),
+            "https://example.com/posts/a.html"
+        );
+        assert_eq!(
+            page_url("https://example.com/blog", "index.html"),
+            "https://example.com/blog/index.html"
+        );
+    }
+
+    #[test]
+    fn test_generate_sitemap() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 14);
+        let pages = vec![page("index.html", date), page("about.html", None)];
+        let xml = generate_sitemap("https://example.com", &pages, None).unwrap();
+
+        assert!(xml.contains(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#));
+        assert!(xml.contains(
+            "<url><loc>https://example.com/index.html</loc><lastmod>2025-03-14</lastmod></url>"
+        ));
+        // No date and no readable source: lastmod is omitted
+        assert!(xml.contains("<url><loc>https://example.com/about.html</loc></url>"));
+    }
+
+    #[test]
+    fn test_sitemap_lastmod_from_mtime() {
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        let page = SitemapPage {
+            source: temp.path().to_path_buf(),
+            href: "index.html".to_string(),
+            date: None,
+        };
+        let today = Utc::now().date_naive().format("%Y-%m-%d").to_string();
+        assert_eq!(lastmod(&page, None), Some(today));
+
+        // Files modified after the build date are clamped to it
+        let build_date = DateTime::from_timestamp(1_704_067_200, 0);
+        assert_eq!(lastmod(&page, build_date), Some("2024-01-01".to_string()));
+    }
+
+    #[test]
+    fn test_sitemap_escapes_urls() {
+        let xml = generate_sitemap("https://example.com", &[page("a&b.html", None)], None).unwrap();
+        assert!(xml.contains("https://example.com/a&amp;b.html"));
+    }
+
+    #[test]
+    fn test_sitemap_encodes_paths() {
+        let pages = [page("my posts/café.html", None), page("100%.html", None)];
+        let xml = generate_sitemap("https://example.com", &pages, None).unwrap();
+        assert!(xml.contains("<loc>https://example.com/my%20posts/caf%C3%A9.html</loc>"));
+        assert!(xml.contains("<loc>https://example.com/100%25.html</loc>"));
+    }
+
+    #[test]
+    fn test_generate_robots_txt() {
+        assert_eq!(generate_robots_txt(None), "User-agent: *\nAllow: /\n");
+        assert_eq!(
+            generate_robots_txt(Some("https://example.com/")),
+            "User-agent: *\nAllow: /\n\nSitemap: https://example.com/sitemap.xml\n"
+        );
+    }
+}
@@ -1,16 +1,17 @@
 use crate::compile::RheoCompileOptions;
 use crate::config::PdfConfig;
 use crate::constants::TYPST_LABEL_PATTERN;
+use crate::discovery::ContentFilter;
 use crate::formats::common::{ExportErrorType, handle_export_errors, unwrap_compilation_result};
 use crate::reticulate::spine::RheoSpine;
-use crate::world::RheoWorld;
-use crate::{OutputFormat, Result, RheoError};
-use std::io::Write;
+use crate::world::{RheoWorld, WorldSettings};
+use crate::{OutputFormat, PageMeta, Result, RheoError};
+use chrono::{Datelike, Timelike};
 use std::path::Path;
-use tempfile::NamedTempFile;
 use tracing::{debug, info};
+use typst::foundations::{Datetime, Smart};
 use typst::layout::PagedDocument;
-use typst_pdf::PdfOptions;
+use typst_pdf::{PdfOptions, Timestamp};
 
 // ============================================================================
 // Single-file PDF compilation (implementation functions)
@@ -22,9 +23,14 @@ use typst_pdf::PdfOptions;
 /// Transformations happen on-demand during Typst compilation (including imports).
 ///
 /// Pipeline: Compile (with transformations) → Export → Write
-fn compile_pdf_single_impl_fresh(input: &Path, output: &Path, root: &Path) -> Result<()> {
+fn compile_pdf_single_impl_fresh(
+    input: &Path,
+    output: &Path,
+    root: &Path,
+    settings: &WorldSettings,
+) -> Result<()> {
     // Create format-aware world (handles link removal on import)
-    let world = RheoWorld::new(root, input, Some(OutputFormat::Pdf))?;
+    let world = RheoWorld::new(root, input, Some(OutputFormat::Pdf))?.with_settings(settings);
 
     // Compile the document
     info!(input = %input.display(), "compiling to PDF");
@@ -33,7 +39,7 @@ fn compile_pdf_single_impl_fresh(input: &Path, output: &Path, root: &Path) -> Re
 
     // Export to PDF
     debug!(output = %output.display(), "exporting to PDF");
-    let pdf_bytes = typst_pdf::pdf(&document, &PdfOptions::default())
+    let pdf_bytes = typst_pdf::pdf(&document, &pdf_options(&document, &world, output)?)
         .map_err(|e| handle_export_errors(e, ExportErrorType::Pdf))?;
 
     // Write to file
@@ -54,7 +60,7 @@ fn compile_pdf_single_impl(world: &RheoWorld, output: &Path) -> Result<()> {
 
     // Export to PDF
     debug!(output = %output.display(), "exporting to PDF");
-    let pdf_bytes = typst_pdf::pdf(&document, &PdfOptions::default())
+    let pdf_bytes = typst_pdf::pdf(&document, &pdf_options(&document, world, output)?)
         .map_err(|e| handle_export_errors(e, ExportErrorType::Pdf))?;
 
     // Write to file
@@ -66,6 +72,39 @@ fn compile_pdf_single_impl(world: &RheoWorld, output: &Path) -> Result<()> {
     Ok(())
 }
 
+/// PDF export options for a compiled document.
+///
+/// The `<rheo>` front matter date, or else the fixed build date, is used as the
+/// creation timestamp when the document does not `set document(date: ..)`
+/// itself. The PDF identifier is derived from the output file name, so it is
+/// stable across builds.
+fn pdf_options<'a>(
+    document: &PagedDocument,
+    world: &RheoWorld,
+    output: &'a Path,
+) -> Result<PdfOptions<'a>> {
+    let meta = PageMeta::query(&document.introspector)?;
+    let timestamp = meta.datetime().or_else(|| {
+        let date = world.build_date()?;
+        Datetime::from_ymd_hms(
+            date.year(),
+            date.month().try_into().ok()?,
+            date.day().try_into().ok()?,
+            date.hour().try_into().ok()?,
+            date.minute().try_into().ok()?,
+            date.second().try_into().ok()?,
+        )
+    });
+    Ok(PdfOptions {
+        ident: output
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(Smart::Auto, Smart::Custom),
+        timestamp: timestamp.map(Timestamp::new_utc),
+        ..PdfOptions::default()
+    })
+}
+
 // ============================================================================
 // Helper functions for merged PDF compilation
 // ============================================================================
@@ -109,7 +148,8 @@ impl DocumentTitle {
 
     /// Extract the document title.
     ///
-    /// Searches for `#set document(title: [...])` in the source and extracts the content.
+    /// Searches for `#set document(title: [...])` or `#set document(title: "...")` in the
+    /// source and extracts the content.
     /// Falls back to the filename converted to title case if no title is found.
     pub fn extract(&self) -> String {
         // Find the start of the title parameter
@@ -118,6 +158,16 @@ impl DocumentTitle {
             if let Some(title_pos) = after_doc.find("title:") {
                 let after_title = &after_doc[title_pos + 6..]; // Skip "title:"
 
+                // String titles: #set document(title: "My Title")
+                if let Some(string_content) = after_title.trim_start().strip_prefix('"')
+                    && let Some(end_pos) = string_content.find('"')
+                {
+                    let title = string_content[..end_pos].trim();
+                    if !title.is_empty() {
+                        return title.to_string();
+                    }
+                }
+
                 // Find the opening bracket for the title
                 // PDF metadata uses bracket-delimited format: /Title [(title text)]
                 if let Some(bracket_start) = after_title.find('[') {
@@ -205,6 +255,9 @@ fn strip_typst_markup(text: &str) -> String {
 // Merged PDF compilation (implementation functions)
 // ============================================================================
 
+/// Name of the virtual main file holding the concatenated spine sources.
+const MERGED_MAIN: &str = ".rheo-merged.typ";
+
 /// Implementation: Compile multiple Typst files into a single merged PDF (fresh compilation)
 ///
 /// Generates a spine from the PDF spine configuration, concatenates all sources
@@ -213,6 +266,8 @@ fn compile_pdf_merged_impl_fresh(
     config: &PdfConfig,
     output_path: &Path,
     root: &Path,
+    filter: &ContentFilter,
+    settings: &WorldSettings,
 ) -> Result<()> {
     let merge = config.spine.as_ref().ok_or_else(|| {
         RheoError::project_config("PDF spine configuration required for merged compilation")
@@ -220,7 +275,7 @@ fn compile_pdf_merged_impl_fresh(
 
     // Build RheoSpine with AST-transformed sources (links → labels, metadata headings injected)
     let spine_config: &dyn crate::config::SpineConfig = merge;
-    let rheo_spine = RheoSpine::build(root, Some(spine_config), crate::OutputFormat::Pdf)?;
+    let rheo_spine = RheoSpine::build(root, Some(spine_config), crate::OutputFormat::Pdf, filter)?;
 
     debug!(file_count = rheo_spine.source.len(), "built PDF spine");
 
@@ -231,23 +286,11 @@ fn compile_pdf_merged_impl_fresh(
         "concatenated sources"
     );
 
-    // Create temporary file with concatenated source in the root directory
-    // (Typst compiler requires main file to be within root for imports)
-    let mut temp_file = NamedTempFile::new_in(root)
-        .map_err(|e| RheoError::io(e, "creating temporary file for merged PDF"))?;
-    temp_file
-        .write_all(concatenated_source.as_bytes())
-        .map_err(|e| RheoError::io(e, "writing concatenated source to temporary file"))?;
-    temp_file
-        .flush()
-        .map_err(|e| RheoError::io(e, "flushing temporary file"))?;
-
-    let temp_path = temp_file.path();
-    debug!(temp_path = %temp_path.display(), "created temporary file");
-
-    // Create RheoWorld with temp file as main
+    // Create RheoWorld with the concatenated source as virtual main file in the root
+    // (imports resolve relative to the root, nothing is written to disk)
     // output_format=None because links already transformed to labels by RheoSpine
-    let world = RheoWorld::new(root, temp_path, None)?;
+    let world = RheoWorld::from_source(root, MERGED_MAIN, concatenated_source.as_str(), None)?
+        .with_settings(settings);
 
     // Compile to PagedDocument
     info!(output = %output_path.display(), "compiling merged PDF");
@@ -257,7 +300,7 @@ fn compile_pdf_merged_impl_fresh(
     // Export PDF bytes
     // Note: PDF title is set via document metadata in Typst source, not PdfOptions
     debug!(output = %output_path.display(), "exporting to PDF");
-    let pdf_bytes = typst_pdf::pdf(&document, &PdfOptions::default())
+    let pdf_bytes = typst_pdf::pdf(&document, &pdf_options(&document, &world, output_path)?)
         .map_err(|e| handle_export_errors(e, ExportErrorType::Pdf))?;
 
     // Write to output file
@@ -278,6 +321,7 @@ fn compile_pdf_merged_impl(
     config: &PdfConfig,
     output_path: &Path,
     root: &Path,
+    filter: &ContentFilter,
 ) -> Result<()> {
     let merge = config.spine.as_ref().ok_or_else(|| {
         RheoError::project_config("PDF spine configuration required for merged compilation")
@@ -285,7 +329,7 @@ fn compile_pdf_merged_impl(
 
     // Build RheoSpine with AST-transformed sources (links → labels, metadata headings injected)
     let spine_config: &dyn crate::config::SpineConfig = merge;
-    let rheo_spine = RheoSpine::build(root, Some(spine_config), crate::OutputFormat::Pdf)?;
+    let rheo_spine = RheoSpine::build(root, Some(spine_config), crate::OutputFormat::Pdf, filter)?;
 
     debug!(file_count = rheo_spine.source.len(), "built PDF spine");
 
@@ -296,22 +340,8 @@ fn compile_pdf_merged_impl(
         "concatenated sources"
     );
 
-    // Create temporary file with concatenated source in the root directory
-    // (Typst compiler requires main file to be within root)
-    let mut temp_file = NamedTempFile::new_in(root)
-        .map_err(|e| RheoError::io(e, "creating temporary file for merged PDF"))?;
-    temp_file
-        .write_all(concatenated_source.as_bytes())
-        .map_err(|e| RheoError::io(e, "writing concatenated source to temporary file"))?;
-    temp_file
-        .flush()
-        .map_err(|e| RheoError::io(e, "flushing temporary file"))?;
-
-    let temp_path = temp_file.path();
-    debug!(temp_path = %temp_path.display(), "created temporary file");
-
-    // Set main file in existing world
-    world.set_main(temp_path)?;
+    // Set the concatenated source as virtual main file in the existing world
+    world.set_main_source(MERGED_MAIN, concatenated_source.as_str());
 
     // Compile to PagedDocument
     info!("compiling merged PDF");
@@ -321,7 +351,7 @@ fn compile_pdf_merged_impl(
     // Export PDF bytes
     // Note: PDF title is set via document metadata in Typst source, not PdfOptions
     debug!(output = %output_path.display(), "exporting to PDF");
-    let pdf_bytes = typst_pdf::pdf(&document, &PdfOptions::default())
+    let pdf_bytes = typst_pdf::pdf(&document, &pdf_options(&document, world, output_path)?)
         .map_err(|e| handle_export_errors(e, ExportErrorType::Pdf))?;
 
     // Write to output file
@@ -364,20 +394,35 @@ pub fn compile_pdf_new(options: RheoCompileOptions, pdf_config: Option<&PdfConfi
             let config = pdf_config.ok_or_else(|| {
                 RheoError::project_config("PDF config required for merged compilation")
             })?;
-            compile_pdf_merged_impl(world, config, &options.output, &options.root)
+            compile_pdf_merged_impl(
+                world,
+                config,
+                &options.output,
+                &options.root,
+                &options.filter,
+            )
         }
         // Merged PDF, fresh
         (true, None) => {
             let config = pdf_config.ok_or_else(|| {
                 RheoError::project_config("PDF config required for merged compilation")
             })?;
-            compile_pdf_merged_impl_fresh(config, &options.output, &options.root)
+            compile_pdf_merged_impl_fresh(
+                config,
+                &options.output,
+                &options.root,
+                &options.filter,
+                &options.settings,
+            )
         }
         // Single file, incremental
         (false, Some(world)) => compile_pdf_single_impl(world, &options.output),
         // Single file, fresh
-        (false, None) => {
-            compile_pdf_single_impl_fresh(&options.input, &options.output, &options.root)
-        }
+        (false, None) => compile_pdf_single_impl_fresh(
+            &options.input,
+            &options.output,
+            &options.root,
+            &options.settings,
+        ),
     }
 }
@@ -0,0 +1,191 @@
+//! State for dependency-aware incremental rebuilds in watch mode.
+//!
+//! After each successful compilation, the files the compilation read through
+//! [`RheoWorld`](crate::world::RheoWorld) are recorded for its output. On the
+//! next change, only outputs whose dependencies include a changed path are
+//! recompiled. Outputs without recorded dependencies (new files, earlier
+//! failures) are always recompiled.
+//!
+//! The state (except compiled EPUB chapters) is also persisted between
+//! `rheo compile` runs by the [build cache](crate::build_cache).
+
+use crate::OutputFormat;
+use crate::formats::epub::EpubCache;
+use crate::formats::html::feed::FeedItem;
+use crate::formats::html::search::SearchEntry;
+use crate::postprocess::SiteNav;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+/// An output whose dependencies are tracked.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum OutputKey {
+    /// Per-file output of a source file (HTML page or individual PDF)
+    Page(OutputFormat, PathBuf),
+    /// Merged output of the whole spine (merged PDF or EPUB)
+    Merged(OutputFormat),
+}
+
+/// Data collected from a compiled HTML page, reused when the page is not recompiled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageRecord {
+    /// Feed entry (also provides the page title and date)
+    pub feed_item: FeedItem,
+    /// Search index entry, if search is enabled
+    pub search_entry: Option<SearchEntry>,
+}
+
+/// Dependencies and page data carried between watch-mode rebuilds.
+///
+/// Ordered collections keep the saved build cache identical between builds.
+#[derive(Default, Serialize, Deserialize)]
+pub struct IncrementalState {
+    /// Files read by the last successful compilation of each output
+    #[serde(with = "entries")]
+    dependencies: BTreeMap<OutputKey, BTreeSet<PathBuf>>,
+    /// Data of the last successful compilation of each HTML page
+    pages: BTreeMap<PathBuf, PageRecord>,
+    /// Site navigation of the last build, which is part of every HTML page
+    nav: Option<SiteNav>,
+    /// EPUB chapters compiled by previous builds
+    #[serde(skip)]
+    epub: EpubCache,
+}
+
+/// Serialize a map as a list of entries, as JSON only supports string keys.
+mod entries {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+
+    pub fn serialize<K, V, S>(map: &BTreeMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map)
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<BTreeMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Ord,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<(K, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+impl IncrementalState {
+    /// Create an empty state, which rebuilds every output.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether an output has to be recompiled.
+    ///
+    /// # Arguments
+    /// * `key` - The output
+    /// * `changed` - Changed paths, or None if everything has to be rebuilt
+    pub fn needs_rebuild(&self, key: &OutputKey, changed: Option<&[PathBuf]>) -> bool {
+        let Some(changed) = changed else {
+            return true;
+        };
+        match self.dependencies.get(key) {
+            Some(dependencies) => changed.iter().any(|path| dependencies.contains(path)),
+            None => true,
+        }
+    }
+
+    /// Record the files read by a successful compilation of an output.
+    pub fn record(&mut self, key: OutputKey, dependencies: impl IntoIterator<Item = PathBuf>) {
+        self.dependencies
+            .insert(key, dependencies.into_iter().collect());
+    }
+
+    /// Forget the dependencies of an output (e.g. after a failed compilation),
+    /// so it is recompiled on the next change.
+    pub fn forget(&mut self, key: &OutputKey) {
+        self.dependencies.remove(key);
+    }
+
+    /// All files that recorded outputs depend on.
+    pub fn dependency_paths(&self) -> HashSet<&Path> {
+        self.dependencies
+            .values()
+            .flatten()
+            .map(PathBuf::as_path)
+            .collect()
+    }
+
+    /// Record the data of a compiled HTML page.
+    pub fn record_page(&mut self, source: &Path, record: PageRecord) {
+        self.pages.insert(source.to_path_buf(), record);
+    }
+
+    /// Data of the last successful compilation of an HTML page.
+    pub fn page(&self, source: &Path) -> Option<&PageRecord> {
+        self.pages.get(source)
+    }
+
+    /// EPUB chapters compiled by previous builds, reused while unchanged.
+    pub fn epub_cache(&mut self) -> &mut EpubCache {
+        &mut self.epub
+    }
+
+    /// Remember the site navigation of this build.
+    ///
+    /// # Returns
+    /// true if the navigation differs from the previous build, in which case all
+    /// HTML pages have to be recompiled
+    pub fn update_nav(&mut self, nav: Option<&SiteNav>) -> bool {
+        if self.nav.as_ref() == nav {
+            return false;
+        }
+        self.nav = nav.cloned();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_rebuild() {
+        let mut state = IncrementalState::new();
+        let page = OutputKey::Page(OutputFormat::Html, PathBuf::from("/p/a.typ"));
+        let template = PathBuf::from("/p/lib/template.typ");
+
+        // Unknown outputs and full rebuilds always compile
+        assert!(state.needs_rebuild(&page, Some(std::slice::from_ref(&template))));
+        state.record(page.clone(), [PathBuf::from("/p/a.typ"), template.clone()]);
+        assert!(state.needs_rebuild(&page, None));
+
+        assert!(state.needs_rebuild(&page, Some(std::slice::from_ref(&template))));
+        assert!(!state.needs_rebuild(&page, Some(&[PathBuf::from("/p/b.typ")])));
+        // Other formats of the same file are tracked separately
+        let pdf = OutputKey::Page(OutputFormat::Pdf, PathBuf::from("/p/a.typ"));
+        assert!(state.needs_rebuild(&pdf, Some(&[PathBuf::from("/p/b.typ")])));
+
+        state.forget(&page);
+        assert!(state.needs_rebuild(&page, Some(&[PathBuf::from("/p/b.typ")])));
+    }
+
+    #[test]
+    fn test_update_nav() {
+        let mut state = IncrementalState::new();
+        assert!(!state.update_nav(None));
+
+        let nav = SiteNav {
+            title: Some("Site".to_string()),
+            entries: Vec::new(),
+        };
+        assert!(state.update_nav(Some(&nav)));
+        assert!(!state.update_nav(Some(&nav)));
+        assert!(state.update_nav(None));
+    }
+}
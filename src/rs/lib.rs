@@ -1,13 +1,18 @@
+pub mod build_cache;
 pub mod cli;
 pub mod compile;
 pub mod config;
 pub mod constants;
+pub mod discovery;
 pub mod error;
+pub mod fonts;
 pub mod formats;
+pub mod incremental;
 pub mod init;
 pub mod logging;
 pub mod manifest_version;
 pub mod output;
+pub mod page_meta;
 pub mod path_utils;
 pub mod postprocess;
 pub mod project;
@@ -15,6 +20,7 @@ pub mod results;
 pub mod reticulate;
 pub mod server;
 pub mod validation;
+pub mod vendor;
 pub mod watch;
 pub mod world;
 
@@ -24,6 +30,7 @@ pub use constants::*;
 pub use error::RheoError;
 pub use globset::{Glob, GlobSet, GlobSetBuilder};
 pub use manifest_version::ManifestVersion;
+pub use page_meta::PageMeta;
 pub use path_utils::PathExt;
 pub use results::{CompilationResults, FormatResult};
 use std::fmt;
@@ -34,7 +41,7 @@ use walkdir::WalkDir;
 /// Result type alias using RheoError
 pub type Result<T> = std::result::Result<T, RheoError>;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
 pub enum OutputFormat {
     Html,
     Epub,
@@ -105,7 +112,6 @@ pub fn open_all_files_in_folder(folder: PathBuf, fmt: OutputFormat) -> Result<()
     };
 
     for entry in WalkDir::new(&folder)
-        .max_depth(1)
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some(ext))
@@ -1,4 +1,6 @@
 use crate::{Result, RheoError};
+use std::cell::RefCell;
+use std::io::{self, Write};
 use tracing::Level;
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -45,7 +47,8 @@ pub fn init(verbosity: Verbosity) -> Result<()> {
         .with_level(true) // Show log level
         .with_ansi(is_tty) // Only use colors if outputting to a TTY
         .without_time() // Don't show timestamps for cleaner output
-        .compact(); // Use compact format similar to cargo
+        .compact() // Use compact format similar to cargo
+        .with_writer(|| LogWriter); // Captured while running parallel jobs
 
     // Initialize the subscriber
     tracing_subscriber::registry()
@@ -58,3 +61,105 @@ pub fn init(verbosity: Verbosity) -> Result<()> {
 
     Ok(())
 }
+
+thread_local! {
+    /// Output captured on this thread, innermost capture last
+    static CAPTURED: RefCell<Vec<CapturedOutput>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Log and diagnostic output captured while running a job.
+///
+/// Jobs of a parallel build capture their output, which is then replayed in
+/// job order, so the output doesn't interleave and doesn't depend on scheduling.
+#[derive(Debug, Default)]
+pub struct CapturedOutput {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl CapturedOutput {
+    /// Print the captured output (into the enclosing capture, if any).
+    pub fn replay(self) {
+        write_stdout(&self.stdout);
+        write_stderr(&self.stderr);
+    }
+}
+
+/// Run a function, capturing the log and diagnostic output it produces on this thread.
+///
+/// # Returns
+/// The function's result and its captured output
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, CapturedOutput) {
+    /// Ends the capture even if the function panics
+    struct Guard;
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            CAPTURED.with_borrow_mut(|captured| captured.pop());
+        }
+    }
+
+    CAPTURED.with_borrow_mut(|captured| captured.push(CapturedOutput::default()));
+    let guard = Guard;
+    let result = f();
+    let output = CAPTURED
+        .with_borrow_mut(|captured| captured.last_mut().map(std::mem::take))
+        .unwrap_or_default();
+    drop(guard);
+    (result, output)
+}
+
+/// Write log output to stdout, or to the current capture.
+fn write_stdout(bytes: &[u8]) {
+    let captured = CAPTURED.with_borrow_mut(|captured| {
+        captured
+            .last_mut()
+            .map(|output| output.stdout.extend_from_slice(bytes))
+    });
+    if captured.is_none() {
+        let _ = io::stdout().write_all(bytes);
+    }
+}
+
+/// Write diagnostic output to stderr, or to the current capture.
+pub fn write_stderr(bytes: &[u8]) {
+    let captured = CAPTURED.with_borrow_mut(|captured| {
+        captured
+            .last_mut()
+            .map(|output| output.stderr.extend_from_slice(bytes))
+    });
+    if captured.is_none() {
+        let _ = io::stderr().write_all(bytes);
+    }
+}
+
+/// Writer for the log output, which goes through [`write_stdout`].
+struct LogWriter;
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        write_stdout(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_nests() {
+        let ((), outer) = capture(|| {
+            write_stderr(b"outer ");
+            let ((), inner) = capture(|| write_stderr(b"inner"));
+            assert_eq!(inner.stderr, b"inner");
+            inner.replay();
+        });
+        assert_eq!(outer.stderr, b"outer inner");
+        assert!(outer.stdout.is_empty());
+    }
+}
@@ -1,11 +1,16 @@
+use crate::config::build_glob_set;
 use crate::{Result, RheoError};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::debug;
+use walkdir::WalkDir;
 
 /// Output directory configuration for a project
 #[derive(Debug)]
 pub struct OutputConfig {
+    /// Build directory containing the format directories
+    pub build_dir: PathBuf,
+
     /// PDF output directory
     pub pdf_dir: PathBuf,
 
@@ -30,6 +35,7 @@ impl OutputConfig {
             pdf_dir: base.join("pdf"),
             html_dir: base.join("html"),
             epub_dir: base.join("epub"),
+            build_dir: base,
         }
     }
 
@@ -51,21 +57,51 @@ impl OutputConfig {
 
     /// Clean this project's build artifacts
     pub fn clean(&self) -> Result<()> {
-        // Determine the project's root build directory (parent of pdf/html/epub dirs)
-        let project_build_dir = self
-            .pdf_dir
-            .parent()
-            .ok_or_else(|| RheoError::path(&self.pdf_dir, "no parent directory"))?;
-
-        if project_build_dir.exists() {
-            fs::remove_dir_all(project_build_dir).map_err(|e| {
-                RheoError::io(e, format!("removing directory {:?}", project_build_dir))
+        if self.build_dir.exists() {
+            fs::remove_dir_all(&self.build_dir).map_err(|e| {
+                RheoError::io(e, format!("removing directory {:?}", self.build_dir))
             })?;
         }
 
         Ok(())
     }
 
+    /// Remove the per-file outputs (PDF and HTML) of a removed source file
+    ///
+    /// Directories left empty are removed as well, up to the format directory.
+    ///
+    /// # Arguments
+    /// * `output_rel` - Output path relative to the format directories, as returned
+    ///   for the source file by the output layout (e.g. `posts/intro.typ`)
+    ///
+    /// # Returns
+    /// * `Ok(usize)` with the number of removed files
+    /// * `Err` if removing a file failed
+    pub fn remove_outputs(&self, output_rel: &Path) -> Result<usize> {
+        let mut removed = 0;
+        for (dir, ext) in [(&self.pdf_dir, "pdf"), (&self.html_dir, "html")] {
+            let path = dir.join(output_rel).with_extension(ext);
+            if !path.is_file() {
+                continue;
+            }
+            fs::remove_file(&path)
+                .map_err(|e| RheoError::io(e, format!("removing stale output {:?}", path)))?;
+            debug!(output = %path.display(), "removed stale output");
+            removed += 1;
+
+            // Prune directories that are now empty (remove_dir fails on non-empty ones)
+            for parent in path.ancestors().skip(1) {
+                if parent == dir.as_path() || !parent.starts_with(dir) {
+                    break;
+                }
+                if fs::remove_dir(parent).is_err() {
+                    break;
+                }
+            }
+        }
+        Ok(removed)
+    }
+
     /// Copy style.css to HTML output directory
     ///
     /// Priority:
@@ -106,6 +142,105 @@ impl OutputConfig {
 
         Ok(())
     }
+
+    /// Copy static assets matching glob patterns into the HTML output directory
+    ///
+    /// Matched files keep their path relative to the content directory, so
+    /// `img/logo.png` is written to `{html_dir}/img/logo.png`. Typst sources and
+    /// the build directory itself are never copied. Copies that are up to date
+    /// are kept, so rebuilds (e.g. in watch mode) only copy new and changed assets.
+    ///
+    /// # Arguments
+    /// * `content_dir` - Directory the patterns are evaluated against
+    /// * `patterns` - Glob patterns from `[html] assets`
+    ///
+    /// # Returns
+    /// * `Ok(usize)` with the number of copied (new or changed) files
+    /// * `Err` if a pattern is invalid or copying failed
+    pub fn copy_static_assets(&self, content_dir: &Path, patterns: &[String]) -> Result<usize> {
+        if patterns.is_empty() {
+            return Ok(0);
+        }
+
+        let globs = build_glob_set("html.assets", patterns)?;
+
+        // Skip the build directory in case it lives inside the content directory
+        let build_dir = self
+            .html_dir
+            .parent()
+            .and_then(|dir| dir.canonicalize().ok());
+
+        let mut copied = 0;
+        for entry in WalkDir::new(content_dir)
+            .into_iter()
+            .filter_entry(|e| match (&build_dir, e.path().canonicalize()) {
+                (Some(build_dir), Ok(path)) => !path.starts_with(build_dir),
+                _ => true,
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "typ") {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(content_dir) else {
+                continue;
+            };
+            let dest = self.html_dir.join(relative);
+            if globs.is_match(relative) && !is_up_to_date(path, &dest) {
+                copy_asset(path, &dest)?;
+                copied += 1;
+            }
+        }
+
+        debug!(count = copied, "copied static assets");
+        Ok(copied)
+    }
+}
+
+/// Create the parent directory of an output file if it does not exist yet
+///
+/// Needed for nested output layouts, where outputs mirror the content directory.
+pub fn ensure_parent_dir(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| RheoError::io(e, format!("creating directory {:?}", parent)))?;
+    }
+    Ok(())
+}
+
+/// Check whether a copy of a file has the same size and is at least as new.
+fn is_up_to_date(source: &Path, dest: &Path) -> bool {
+    let (Ok(source), Ok(dest)) = (fs::metadata(source), fs::metadata(dest)) else {
+        return false;
+    };
+    match (source.modified(), dest.modified()) {
+        (Ok(source_time), Ok(dest_time)) => source.len() == dest.len() && dest_time >= source_time,
+        _ => false,
+    }
+}
+
+/// Copy a single asset file, creating parent directories as needed
+///
+/// The file is copied to a temporary file next to the destination and renamed
+/// into place, so pages compiled in parallel that reference the same asset never
+/// expose a partially written copy.
+///
+/// # Arguments
+/// * `source` - Path to the asset in the content directory
+/// * `dest` - Destination path inside an output directory
+pub fn copy_asset(source: &Path, dest: &Path) -> Result<()> {
+    ensure_parent_dir(dest)?;
+    let error = |e: std::io::Error| {
+        RheoError::io(e, format!("copying asset from {:?} to {:?}", source, dest))
+    };
+    let temp =
+        tempfile::NamedTempFile::new_in(dest.parent().unwrap_or(Path::new("."))).map_err(error)?;
+    fs::copy(source, temp.path()).map_err(error)?;
+    temp.persist(dest).map_err(|e| error(e.error))?;
+    debug!(source = %source.display(), dest = %dest.display(), "copied asset");
+    Ok(())
 }
 
 #[cfg(test)]
@@ -122,6 +257,7 @@ mod tests {
 
         // Create a test configuration in the temp directory
         let config = OutputConfig {
+            build_dir: temp_dir.clone(),
             pdf_dir: temp_dir.join("pdf"),
             html_dir: temp_dir.join("html"),
             epub_dir: temp_dir.join("epub"),
@@ -139,6 +275,31 @@ mod tests {
         fs::remove_dir_all(&temp_dir).expect("Failed to clean up test directory");
     }
 
+    #[test]
+    fn test_remove_outputs() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = OutputConfig::new(temp.path(), None);
+        config.create_dirs().unwrap();
+        for path in [
+            config.pdf_dir.join("posts/intro.pdf"),
+            config.html_dir.join("posts/intro.html"),
+            config.html_dir.join("index.html"),
+        ] {
+            ensure_parent_dir(&path).unwrap();
+            fs::write(&path, "").unwrap();
+        }
+
+        let removed = config.remove_outputs(Path::new("posts/intro.typ")).unwrap();
+        assert_eq!(removed, 2);
+        assert!(!config.pdf_dir.join("posts").exists());
+        assert!(!config.html_dir.join("posts").exists());
+        assert!(config.html_dir.join("index.html").exists());
+        assert!(config.pdf_dir.exists());
+
+        // Outputs that were never written are skipped
+        assert_eq!(config.remove_outputs(Path::new("missing.typ")).unwrap(), 0);
+    }
+
     #[test]
     fn test_output_config_new() {
         let project_root = PathBuf::from("/home/user/my-book");
@@ -201,6 +362,89 @@ mod tests {
         assert!(config.clean().is_ok());
     }
 
+    #[test]
+    fn test_copy_static_assets() {
+        use tempfile::TempDir;
+
+        let temp = TempDir::new().unwrap();
+        let content_dir = temp.path();
+        fs::create_dir_all(content_dir.join("img/nested")).unwrap();
+        fs::create_dir_all(content_dir.join("static")).unwrap();
+        fs::write(content_dir.join("img/a.png"), b"png").unwrap();
+        fs::write(content_dir.join("img/nested/b.svg"), b"svg").unwrap();
+        fs::write(content_dir.join("img/figure.typ"), b"= Figure").unwrap();
+        fs::write(content_dir.join("static/notes.pdf"), b"pdf").unwrap();
+        fs::write(content_dir.join("other.txt"), b"txt").unwrap();
+
+        // Build directory inside the content directory must not be copied into itself
+        let config = OutputConfig::new(content_dir, None);
+        config.create_dirs().unwrap();
+        fs::write(config.html_dir.join("index.html"), b"html").unwrap();
+
+        let patterns = vec!["img/**".to_string(), "static/**".to_string()];
+        let copied = config.copy_static_assets(content_dir, &patterns).unwrap();
+
+        assert_eq!(copied, 3);
+        assert!(config.html_dir.join("img/a.png").exists());
+        assert!(config.html_dir.join("img/nested/b.svg").exists());
+        assert!(config.html_dir.join("static/notes.pdf").exists());
+        assert!(!config.html_dir.join("img/figure.typ").exists());
+        assert!(!config.html_dir.join("other.txt").exists());
+
+        // Catch-all patterns skip the build directory, and up-to-date copies are kept
+        let copied = config
+            .copy_static_assets(content_dir, &["**".to_string()])
+            .unwrap();
+        assert_eq!(copied, 1);
+        assert!(config.html_dir.join("other.txt").exists());
+        assert!(!config.html_dir.join("build").exists());
+
+        // Changed assets are copied again
+        fs::write(content_dir.join("img/a.png"), b"new png").unwrap();
+        assert_eq!(
+            config.copy_static_assets(content_dir, &patterns).unwrap(),
+            1
+        );
+        assert_eq!(
+            fs::read(config.html_dir.join("img/a.png")).unwrap(),
+            b"new png"
+        );
+    }
+
+    #[test]
+    fn test_copy_asset_concurrently() {
+        use rayon::prelude::*;
+
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("logo.png");
+        let data = vec![7u8; 1 << 20];
+        fs::write(&source, &data).unwrap();
+        let dest = temp.path().join("html/img/logo.png");
+
+        // Pages compiled in parallel may copy the same asset at the same time
+        (0..16)
+            .into_par_iter()
+            .try_for_each(|_| copy_asset(&source, &dest))
+            .unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), data);
+        // No temporary files are left behind
+        assert_eq!(
+            fs::read_dir(temp.path().join("html/img")).unwrap().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_copy_static_assets_no_patterns() {
+        let config = OutputConfig::new(Path::new("/nonexistent/rheo"), None);
+        assert_eq!(
+            config
+                .copy_static_assets(Path::new("/nonexistent"), &[])
+                .unwrap(),
+            0
+        );
+    }
+
     #[test]
     fn test_output_config_custom_build_dir() {
         let project_root = PathBuf::from("/home/user/my-book");
@@ -0,0 +1,300 @@
+//! Per-document front matter declared with Typst metadata.
+//!
+//! A document describes itself to rheo by placing a dictionary in a `metadata`
+//! element labelled `<rheo>`:
+//!
+//! ```typ
+//! #metadata((
+//!   date: datetime(year: 2025, month: 3, day: 14),
+//!   tags: ("typst", "publishing"),
+//!   description: "How rheo turns Typst into websites",
+//!   draft: false,
+//! )) <rheo>
+//! ```
+//!
+//! All keys are optional and unknown keys are ignored. `date` may also be an
+//! ISO 8601 string ("2025-03-14"), and `tags` a single string. If a document
+//! (or its template) contains several `<rheo>` metadata elements, they are merged
+//! in document order, with later keys taking precedence.
+//!
+//! The metadata is read from the compiled document's introspector, so it works
+//! identically for HTML, EPUB and PDF output.
+
+use crate::{Result, RheoError};
+use chrono::{Datelike, NaiveDate};
+use typst::foundations::{Datetime, Dict, Label, Selector, Value};
+use typst::introspection::{Introspector, MetadataElem};
+use typst::syntax::{SyntaxNode, ast};
+use typst::utils::PicoStr;
+
+/// Label identifying rheo front matter (`#metadata(..) <rheo>`).
+pub const PAGE_META_LABEL: &str = "rheo";
+
+/// Typed front matter of a document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageMeta {
+    /// Publication date.
+    pub date: Option<NaiveDate>,
+    /// Tags (categories) of the document.
+    pub tags: Vec<String>,
+    /// Short description, used for summaries and `<meta name="description">`.
+    pub description: Option<String>,
+    /// Whether the document is a draft.
+    pub draft: bool,
+}
+
+impl PageMeta {
+    /// Query the front matter of a compiled document.
+    ///
+    /// # Arguments
+    /// * `introspector` - Introspector of a compiled document (HTML or paged)
+    ///
+    /// # Returns
+    /// The merged front matter, or the default if the document declares none
+    ///
+    /// # Errors
+    /// Returns `RheoError::InvalidData` if the metadata is not a dictionary or a
+    /// known key has the wrong type.
+    pub fn query(introspector: &Introspector) -> Result<Self> {
+        let label = Label::new(PicoStr::intern(PAGE_META_LABEL)).expect("label is not empty");
+        let mut merged = Dict::new();
+        for elem in introspector.query(&Selector::Label(label)).iter() {
+            let Some(metadata) = elem.to_packed::<MetadataElem>() else {
+                continue;
+            };
+            match &metadata.value {
+                Value::Dict(dict) => {
+                    for (key, value) in dict.iter() {
+                        merged.insert(key.clone(), value.clone());
+                    }
+                }
+                other => {
+                    return Err(invalid(format!(
+                        "expected a dictionary, found {}",
+                        other.ty()
+                    )));
+                }
+            }
+        }
+        Self::from_dict(&merged)
+    }
+
+    /// Build front matter from a metadata dictionary.
+    pub fn from_dict(dict: &Dict) -> Result<Self> {
+        let mut meta = Self::default();
+        for (key, value) in dict.iter() {
+            match key.as_str() {
+                "date" => meta.date = Some(to_date(value)?),
+                "tags" => meta.tags = to_tags(value)?,
+                "description" => meta.description = Some(to_text("description", value)?),
+                "draft" => match value {
+                    Value::Bool(draft) => meta.draft = *draft,
+                    other => return Err(wrong_type("draft", "a boolean", other)),
+                },
+                _ => {}
+            }
+        }
+        Ok(meta)
+    }
+
+    /// Check whether a source file marks itself as a draft without compiling it.
+    ///
+    /// Only a literal `draft: true` in a `#metadata((..)) <rheo>` element written in
+    /// the file itself is recognized, since discovery happens before compilation.
+    pub fn declares_draft(source: &str) -> bool {
+        declares_draft_in(&typst::syntax::parse(source))
+    }
+
+    /// The date as a Typst datetime (midnight), e.g. for PDF timestamps.
+    pub fn datetime(&self) -> Option<Datetime> {
+        let date = self.date?;
+        Datetime::from_ymd_hms(date.year(), date.month() as u8, date.day() as u8, 0, 0, 0)
+    }
+}
+
+fn declares_draft_in(node: &SyntaxNode) -> bool {
+    let children: Vec<&SyntaxNode> = node.children().collect();
+    for (i, child) in children.iter().enumerate() {
+        // `#metadata(..) <rheo>`: the label follows the call in the same markup
+        let labelled = children[i + 1..]
+            .iter()
+            .find(|next| !next.kind().is_trivia())
+            .and_then(|next| next.cast::<ast::Label>())
+            .is_some_and(|label| label.get() == PAGE_META_LABEL);
+        if labelled
+            && let Some(call) = child.cast::<ast::FuncCall>()
+            && is_draft_metadata(call)
+        {
+            return true;
+        }
+        if declares_draft_in(child) {
+            return true;
+        }
+    }
+    false
+}
+
+fn is_draft_metadata(call: ast::FuncCall) -> bool {
+    let ast::Expr::Ident(callee) = call.callee() else {
+        return false;
+    };
+    if callee.get() != "metadata" {
+        return false;
+    }
+    call.args().items().any(|arg| match arg {
+        ast::Arg::Pos(ast::Expr::Dict(dict)) => dict.items().any(|item| match item {
+            ast::DictItem::Named(named) => {
+                named.name().get() == "draft"
+                    && matches!(named.expr(), ast::Expr::Bool(draft) if draft.get())
+            }
+            _ => false,
+        }),
+        _ => false,
+    })
+}
+
+fn invalid(message: String) -> RheoError {
+    RheoError::invalid_data(format!(
+        "invalid <{}> metadata: {}",
+        PAGE_META_LABEL, message
+    ))
+}
+
+fn wrong_type(key: &str, expected: &str, found: &Value) -> RheoError {
+    invalid(format!(
+        "`{}` must be {}, found {}",
+        key,
+        expected,
+        found.ty()
+    ))
+}
+
+fn to_date(value: &Value) -> Result<NaiveDate> {
+    match value {
+        Value::Datetime(datetime) => {
+            let date = datetime.year().zip(datetime.month()).zip(datetime.day());
+            date.and_then(|((year, month), day)| {
+                NaiveDate::from_ymd_opt(year, month.into(), day.into())
+            })
+            .ok_or_else(|| invalid("`date` must include a year, month and day".to_string()))
+        }
+        Value::Str(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| {
+            invalid(format!(
+                "`date` must be in YYYY-MM-DD format, got '{}': {}",
+                date.as_str(),
+                e
+            ))
+        }),
+        other => Err(wrong_type("date", "a datetime or string", other)),
+    }
+}
+
+fn to_tags(value: &Value) -> Result<Vec<String>> {
+    match value {
+        Value::Str(tag) => Ok(vec![tag.to_string()]),
+        Value::Array(tags) => tags
+            .iter()
+            .map(|tag| to_text("tags", tag))
+            .collect::<Result<_>>(),
+        other => Err(wrong_type("tags", "an array of strings", other)),
+    }
+}
+
+fn to_text(key: &str, value: &Value) -> Result<String> {
+    match value {
+        Value::Str(text) => Ok(text.to_string()),
+        Value::Content(content) => Ok(content.plain_text().to_string()),
+        other => Err(wrong_type(key, "a string", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OutputFormat;
+
+    fn compile_meta(source: &str) -> Result<PageMeta> {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("page.typ");
+        std::fs::write(&input, source).unwrap();
+        let document =
+            crate::formats::html::compile_html_to_document(&input, dir.path(), OutputFormat::Html)
+                .unwrap();
+        PageMeta::query(&document.introspector)
+    }
+
+    #[test]
+    fn test_query_page_meta() {
+        let meta = compile_meta(
+            r#"#metadata((
+  date: datetime(year: 2025, month: 3, day: 14),
+  tags: ("typst", "web"),
+  description: [A *short* summary],
+  draft: true,
+  custom: 42,
+)) <rheo>
+
+= Hello"#,
+        )
+        .unwrap();
+
+        assert_eq!(meta.date, NaiveDate::from_ymd_opt(2025, 3, 14));
+        assert_eq!(meta.tags, vec!["typst", "web"]);
+        assert_eq!(meta.description.as_deref(), Some("A short summary"));
+        assert!(meta.draft);
+        assert_eq!(
+            meta.datetime(),
+            Datetime::from_ymd_hms(2025, 3, 14, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_query_page_meta_absent() {
+        let meta = compile_meta("= Hello").unwrap();
+        assert_eq!(meta, PageMeta::default());
+    }
+
+    #[test]
+    fn test_query_page_meta_merges_in_order() {
+        let meta = compile_meta(
+            r#"#metadata((date: "2024-01-01", tags: "a")) <rheo>
+#metadata((date: "2024-02-02")) <rheo>"#,
+        )
+        .unwrap();
+        assert_eq!(meta.date, NaiveDate::from_ymd_opt(2024, 2, 2));
+        assert_eq!(meta.tags, vec!["a"]);
+    }
+
+    #[test]
+    fn test_declares_draft() {
+        assert!(PageMeta::declares_draft(
+            "#metadata((title: \"WIP\", draft: true)) <rheo>\n= Hello"
+        ));
+        assert!(PageMeta::declares_draft(
+            "#set page(width: auto)\n#metadata((\n  draft: true,\n)) <rheo>"
+        ));
+        assert!(!PageMeta::declares_draft(
+            "#metadata((draft: false)) <rheo>"
+        ));
+        assert!(!PageMeta::declares_draft(
+            "#metadata((draft: true)) <other>"
+        ));
+        assert!(!PageMeta::declares_draft("#metadata((draft: true))"));
+        assert!(!PageMeta::declares_draft("draft: true <rheo>"));
+    }
+
+    #[test]
+    fn test_query_page_meta_invalid() {
+        let err = compile_meta(r#"#metadata((draft: "yes")) <rheo>"#).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("`draft` must be a boolean, found str")
+        );
+
+        let err = compile_meta(r#"#metadata((date: "14/03/2025")) <rheo>"#).unwrap_err();
+        assert!(err.to_string().contains("YYYY-MM-DD"));
+
+        let err = compile_meta(r#"#metadata("2025") <rheo>"#).unwrap_err();
+        assert!(err.to_string().contains("expected a dictionary"));
+    }
+}
@@ -55,6 +55,32 @@ impl HtmlDom {
         find_element_by_tag(&self.dom.document, tag_name).map(|handle| Element { handle })
     }
 
+    /// Collect the values of the given attributes across all elements (document order).
+    ///
+    /// # Arguments
+    /// * `names` - Attribute names to collect (e.g., ["src", "href"])
+    ///
+    /// # Returns
+    /// Attribute values in the order they appear in the document
+    pub fn attribute_values(&self, names: &[&str]) -> Vec<String> {
+        let mut values = Vec::new();
+        collect_attribute_values(&self.dom.document, names, &mut values);
+        values
+    }
+
+    /// Find all elements with one of the given tag names (document order).
+    ///
+    /// # Arguments
+    /// * `tag_names` - Tag names to search for (e.g., ["h2", "h3"])
+    ///
+    /// # Returns
+    /// Matching elements, including nested matches
+    pub fn find_elements(&self, tag_names: &[&str]) -> Vec<Element> {
+        let mut found = Vec::new();
+        collect_elements_by_tag(&self.dom.document, tag_names, &mut found);
+        found.into_iter().map(|handle| Element { handle }).collect()
+    }
+
     /// Get the document root handle.
     ///
     /// # Returns
@@ -107,6 +133,65 @@ impl Element {
         Self { handle }
     }
 
+    /// Create an element with the given tag name and attributes.
+    ///
+    /// # Arguments
+    /// * `tag_name` - The tag name (e.g., "nav", "a")
+    /// * `attributes` - Attribute name/value pairs, in order
+    ///
+    /// # Returns
+    /// New Element without children
+    pub fn create(tag_name: &str, attributes: &[(&str, &str)]) -> Self {
+        use html5ever::tendril::StrTendril;
+        use html5ever::{Attribute, LocalName, QualName, ns};
+        use markup5ever_rcdom::Node;
+        use std::cell::RefCell;
+
+        let attrs = attributes
+            .iter()
+            .map(|(name, value)| Attribute {
+                name: QualName::new(None, ns!(), LocalName::from(*name)),
+                value: StrTendril::from(*value),
+            })
+            .collect();
+
+        let handle = Node::new(NodeData::Element {
+            name: QualName::new(None, ns!(html), LocalName::from(tag_name)),
+            attrs: RefCell::new(attrs),
+            template_contents: RefCell::new(None),
+            mathml_annotation_xml_integration_point: false,
+        });
+
+        Self { handle }
+    }
+
+    /// Create a text node.
+    ///
+    /// # Arguments
+    /// * `text` - The text content (escaped on serialization)
+    ///
+    /// # Returns
+    /// New Element wrapping a text node
+    pub fn create_text(text: &str) -> Self {
+        use html5ever::tendril::StrTendril;
+        use markup5ever_rcdom::Node;
+        use std::cell::RefCell;
+
+        let handle = Node::new(NodeData::Text {
+            contents: RefCell::new(StrTendril::from(text)),
+        });
+
+        Self { handle }
+    }
+
+    /// Append a child element to this element.
+    ///
+    /// # Arguments
+    /// * `child` - The child element to append
+    pub fn append_child(&self, child: Element) {
+        self.handle.children.borrow_mut().push(child.handle);
+    }
+
     /// Prepend a child element to this element.
     ///
     /// # Arguments
@@ -116,6 +201,74 @@ impl Element {
         children.insert(0, child.handle);
     }
 
+    /// Get the value of an attribute.
+    ///
+    /// # Arguments
+    /// * `name` - The attribute name (e.g., "id")
+    ///
+    /// # Returns
+    /// Attribute value, or None if the attribute is not set
+    pub fn attribute(&self, name: &str) -> Option<String> {
+        match &self.handle.data {
+            NodeData::Element { attrs, .. } => attrs
+                .borrow()
+                .iter()
+                .find(|attr| attr.name.local.as_ref() == name)
+                .map(|attr| attr.value.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Set an attribute, replacing any existing value.
+    ///
+    /// # Arguments
+    /// * `name` - The attribute name (e.g., "id")
+    /// * `value` - The attribute value
+    pub fn set_attribute(&self, name: &str, value: &str) {
+        use html5ever::tendril::StrTendril;
+        use html5ever::{Attribute, LocalName, QualName, ns};
+
+        if let NodeData::Element { attrs, .. } = &self.handle.data {
+            let mut attrs = attrs.borrow_mut();
+            match attrs
+                .iter_mut()
+                .find(|attr| attr.name.local.as_ref() == name)
+            {
+                Some(attr) => attr.value = StrTendril::from(value),
+                None => attrs.push(Attribute {
+                    name: QualName::new(None, ns!(), LocalName::from(name)),
+                    value: StrTendril::from(value),
+                }),
+            }
+        }
+    }
+
+    /// Get the concatenated text of this node and its descendants.
+    pub fn text_content(&self) -> String {
+        let mut text = String::new();
+        collect_text(&self.handle, &mut text);
+        text
+    }
+
+    /// Serialize the children of this element to HTML.
+    ///
+    /// # Returns
+    /// HTML of the element's contents, without the element's own tags
+    pub fn inner_html(&self) -> Result<String> {
+        let mut output = String::new();
+        for child in self.handle.children.borrow().iter() {
+            serialize_node(child, &mut output)?;
+        }
+        Ok(output)
+    }
+
+    /// Serialize this element (including its own tags) to HTML.
+    pub fn outer_html(&self) -> Result<String> {
+        let mut output = String::new();
+        serialize_node(&self.handle, &mut output)?;
+        Ok(output)
+    }
+
     /// Get the tag name of this element.
     ///
     /// # Returns
@@ -147,6 +300,45 @@ fn find_element_by_tag(handle: &Handle, tag_name: &str) -> Option<Handle> {
     None
 }
 
+/// Collect elements by tag name in the DOM tree (depth-first search).
+fn collect_elements_by_tag(handle: &Handle, tag_names: &[&str], found: &mut Vec<Handle>) {
+    if let NodeData::Element { name, .. } = &handle.data
+        && tag_names.contains(&name.local.as_ref())
+    {
+        found.push(handle.clone());
+    }
+
+    for child in handle.children.borrow().iter() {
+        collect_elements_by_tag(child, tag_names, found);
+    }
+}
+
+/// Collect text content in the DOM tree (depth-first search).
+fn collect_text(handle: &Handle, text: &mut String) {
+    if let NodeData::Text { contents } = &handle.data {
+        text.push_str(&contents.borrow());
+    }
+
+    for child in handle.children.borrow().iter() {
+        collect_text(child, text);
+    }
+}
+
+/// Collect attribute values in the DOM tree (depth-first search).
+fn collect_attribute_values(handle: &Handle, names: &[&str], values: &mut Vec<String>) {
+    if let NodeData::Element { attrs, .. } = &handle.data {
+        for attr in attrs.borrow().iter() {
+            if names.contains(&attr.name.local.as_ref()) {
+                values.push(attr.value.to_string());
+            }
+        }
+    }
+
+    for child in handle.children.borrow().iter() {
+        collect_attribute_values(child, names, values);
+    }
+}
+
 /// Serialize a single node and its children to HTML.
 fn serialize_node(handle: &Handle, output: &mut String) -> Result<()> {
     match &handle.data {
@@ -266,6 +458,39 @@ mod tests {
         assert!(script.is_none());
     }
 
+    #[test]
+    fn test_attribute_values() {
+        let html = r#"<html><head><link rel="icon" href="favicon.ico"></head><body><img src="img/a.png"><a href="notes.pdf">notes</a></body></html>"#;
+        let dom = HtmlDom::parse(html).unwrap();
+        assert_eq!(
+            dom.attribute_values(&["src", "href"]),
+            vec!["favicon.ico", "img/a.png", "notes.pdf"]
+        );
+        assert!(dom.attribute_values(&["poster"]).is_empty());
+    }
+
+    #[test]
+    fn test_find_elements_and_attributes() {
+        let html = r#"<html><body><h2 id="a">One <em>two</em></h2><section><h3>Three</h3></section></body></html>"#;
+        let dom = HtmlDom::parse(html).unwrap();
+        let headings = dom.find_elements(&["h2", "h3"]);
+        assert_eq!(headings.len(), 2);
+        assert_eq!(headings[0].attribute("id").as_deref(), Some("a"));
+        assert_eq!(headings[0].text_content(), "One two");
+        assert_eq!(headings[1].attribute("id"), None);
+
+        headings[1].set_attribute("id", "three");
+        headings[0].set_attribute("id", "one");
+        assert_eq!(
+            headings[1].outer_html().unwrap(),
+            r#"<h3 id="three">Three</h3>"#
+        );
+        assert_eq!(
+            dom.find_element("body").unwrap().inner_html().unwrap(),
+            r#"<h2 id="one">One <em>two</em></h2><section><h3 id="three">Three</h3></section>"#
+        );
+    }
+
     #[test]
     fn test_create_link_element() {
         let link = Element::create_link("stylesheet", "style.css");
@@ -295,6 +520,22 @@ mod tests {
         assert!(serialized.contains("<title>Test</title>"));
     }
 
+    #[test]
+    fn test_create_and_append_child() {
+        let html = "<html><head></head><body><p>Content</p></body></html>";
+        let dom = HtmlDom::parse(html).unwrap();
+        let body = dom.find_element("body").unwrap();
+
+        let link = Element::create("a", &[("href", "next.html"), ("rel", "next")]);
+        link.append_child(Element::create_text("Next & last"));
+        body.append_child(link);
+
+        let serialized = dom.serialize().unwrap();
+        assert!(serialized.contains(
+            "<p>Content</p><a href=\"next.html\" rel=\"next\">Next &amp; last</a></body>"
+        ));
+    }
+
     #[test]
     fn test_prepend_child() {
         let html = "<html><head><title>Test</title></head></html>";
@@ -1,7 +1,7 @@
 //! HTML head injection utilities for CSS and font links.
 //!
 //! This module provides functionality for injecting stylesheet and font links
-//! into the <head> section of HTML documents.
+//! (and scripts) into the <head> section of HTML documents.
 
 use crate::{Result, RheoError};
 
@@ -57,6 +57,80 @@ pub fn inject_head_links(html: &str, stylesheets: &[&str], fonts: &[&str]) -> Re
     dom.serialize()
 }
 
+/// Inject named `<meta>` tags into the HTML <head> section.
+///
+/// Tags whose name is already present in the document (e.g. a description set
+/// with `#set document(description: ..)`) are skipped.
+///
+/// # Arguments
+/// * `html` - The HTML content to modify
+/// * `tags` - Name/content pairs (e.g., [("description", "...")])
+///
+/// # Returns
+/// HTML with the missing `<meta>` tags appended to <head>
+///
+/// # Errors
+/// Returns error if HTML parsing or serialization fails, or <head> is missing
+pub fn inject_head_meta(html: &str, tags: &[(&str, &str)]) -> Result<String> {
+    if tags.is_empty() {
+        return Ok(html.to_string());
+    }
+    let dom = dom::HtmlDom::parse(html)?;
+
+    let head = dom
+        .find_element("head")
+        .ok_or_else(|| RheoError::HtmlGeneration {
+            count: 1,
+            errors: "HTML document does not contain a <head> element".to_string(),
+        })?;
+    let existing: Vec<String> = dom
+        .find_elements(&["meta"])
+        .iter()
+        .filter_map(|meta| meta.attribute("name"))
+        .collect();
+    for (name, content) in tags {
+        if !existing.iter().any(|existing| existing == name) {
+            head.append_child(dom::Element::create(
+                "meta",
+                &[("name", name), ("content", content)],
+            ));
+        }
+    }
+
+    dom.serialize()
+}
+
+/// Inject a deferred script into the HTML <head> section.
+///
+/// The script is appended after existing head content, so it runs after the
+/// document has been parsed.
+///
+/// # Arguments
+/// * `html` - The HTML content to modify
+/// * `src` - Script URL (e.g., "rheo-search.js")
+///
+/// # Returns
+/// HTML with a `<script defer>` element at the end of <head>
+///
+/// # Errors
+/// Returns error if HTML parsing or serialization fails, or <head> is missing
+pub fn inject_head_script(html: &str, src: &str) -> Result<String> {
+    let dom = dom::HtmlDom::parse(html)?;
+
+    let head = dom
+        .find_element("head")
+        .ok_or_else(|| RheoError::HtmlGeneration {
+            count: 1,
+            errors: "HTML document does not contain a <head> element".to_string(),
+        })?;
+    head.append_child(dom::Element::create(
+        "script",
+        &[("src", src), ("defer", "")],
+    ));
+
+    dom.serialize()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,6 +219,28 @@ mod tests {
         assert!(html_output.contains(r#"<link rel="stylesheet" href="style.css">"#));
     }
 
+    #[test]
+    fn test_inject_head_meta() {
+        let html = r#"<!DOCTYPE html><html><head><meta name="description" content="From Typst"></head><body></body></html>"#;
+        let result = inject_head_meta(
+            html,
+            &[("description", "From metadata"), ("keywords", "a, b")],
+        )
+        .unwrap();
+        assert!(result.contains(r#"<meta name="description" content="From Typst">"#));
+        assert!(!result.contains("From metadata"));
+        assert!(result.contains(r#"<meta name="keywords" content="a, b"></head>"#));
+    }
+
+    #[test]
+    fn test_inject_head_script() {
+        let html = "<!DOCTYPE html><html><head><title>Test</title></head><body></body></html>";
+        let result = inject_head_script(html, "../rheo-search.js").unwrap();
+        assert!(result.contains(
+            r#"<title>Test</title><script src="../rheo-search.js" defer=""></script></head>"#
+        ));
+    }
+
     #[test]
     fn test_inject_head_links_empty_lists() {
         let html = "<!DOCTYPE html><html><head><title>Test</title></head><body></body></html>";
@@ -0,0 +1,314 @@
+//! Page layouts for HTML output.
+//!
+//! A layout is an HTML file with `{{placeholder}}` markers that wraps the body of
+//! every compiled page, so headers and footers live in one place instead of being
+//! repeated in each `.typ` file. Supported placeholders:
+//! - `{{title}}` - the document title (escaped)
+//! - `{{content}}` - the compiled page body
+//! - `{{nav}}` - site navigation, when an HTML spine is configured
+//! - `{{toc}}` - table of contents linking to the page's headings
+
+use crate::config::{HtmlLayout, build_glob_set};
+use crate::{GlobSet, Result, RheoError};
+use std::collections::HashSet;
+use std::path::Path;
+use tracing::warn;
+
+use super::dom;
+
+/// Headings listed in the table of contents (`<h1>` is the page title).
+const TOC_HEADINGS: &[&str] = &["h2", "h3", "h4", "h5", "h6"];
+
+/// An HTML page layout loaded from a template file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageLayout {
+    template: String,
+}
+
+impl PageLayout {
+    /// Create a layout from template source.
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+        }
+    }
+
+    /// Load a layout from a template file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let template = std::fs::read_to_string(path)
+            .map_err(|e| RheoError::io(e, format!("reading HTML layout {:?}", path)))?;
+        Ok(Self::new(template))
+    }
+
+    /// Whether the layout positions the site navigation itself via `{{nav}}`.
+    ///
+    /// If it does not, navigation is injected into `<body>` as without a layout.
+    pub fn places_nav(&self) -> bool {
+        placeholders(&self.template).any(|name| name == "nav")
+    }
+
+    /// Wrap a compiled page in this layout.
+    ///
+    /// # Arguments
+    /// * `html` - The exported HTML page
+    /// * `nav` - Rendered site navigation for `{{nav}}` (empty if none)
+    ///
+    /// # Returns
+    /// The layout with all known placeholders replaced
+    ///
+    /// # Errors
+    /// Returns error if HTML parsing or serialization fails
+    pub fn apply(&self, html: &str, nav: &str) -> Result<String> {
+        let dom = dom::HtmlDom::parse(html)?;
+
+        let title = dom
+            .find_element("title")
+            .map(|title| escape_text(title.text_content().trim()))
+            .unwrap_or_default();
+        let toc = build_toc(&dom)?;
+        let content = match dom.find_element("body") {
+            Some(body) => body.inner_html()?,
+            None => String::new(),
+        };
+
+        Ok(render(&self.template, |name| match name {
+            "title" => Some(title.as_str()),
+            "content" => Some(content.as_str()),
+            "nav" => Some(nav),
+            "toc" => Some(toc.as_str()),
+            _ => None,
+        }))
+    }
+}
+
+/// Layouts from `[[html.layouts]]`, selected per page by glob.
+#[derive(Debug, Clone, Default)]
+pub struct PageLayouts {
+    layouts: Vec<(GlobSet, PageLayout)>,
+}
+
+impl PageLayouts {
+    /// Load all configured layouts.
+    ///
+    /// # Arguments
+    /// * `root` - Project root; layout templates are resolved relative to it
+    /// * `configs` - Layout configurations in priority order
+    pub fn load(root: &Path, configs: &[HtmlLayout]) -> Result<Self> {
+        let mut layouts = Vec::with_capacity(configs.len());
+        for config in configs {
+            let globs = build_glob_set("html.layouts.files", &config.files)?;
+            layouts.push((globs, PageLayout::load(&root.join(&config.template))?));
+        }
+        Ok(Self { layouts })
+    }
+
+    /// Select the layout for a page: the first one whose `files` match, where a
+    /// layout without `files` matches every page.
+    ///
+    /// # Arguments
+    /// * `rel_path` - Source path relative to the content directory
+    pub fn select(&self, rel_path: &Path) -> Option<&PageLayout> {
+        self.layouts
+            .iter()
+            .find(|(globs, _)| globs.is_empty() || globs.is_match(rel_path))
+            .map(|(_, layout)| layout)
+    }
+}
+
+/// Build the table of contents for `{{toc}}`.
+///
+/// Headings without an `id` get one derived from their text, so the links resolve.
+fn build_toc(dom: &dom::HtmlDom) -> Result<String> {
+    let headings = dom.find_elements(TOC_HEADINGS);
+    if headings.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut used: HashSet<String> = headings.iter().filter_map(|h| h.attribute("id")).collect();
+    let toc = dom::Element::create("nav", &[("class", "rheo-toc"), ("aria-label", "Contents")]);
+    let list = dom::Element::create("ol", &[]);
+    for heading in &headings {
+        let text = heading.text_content();
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let id = match heading.attribute("id") {
+            Some(id) => id,
+            None => {
+                let id = unique_slug(&text, &used);
+                heading.set_attribute("id", &id);
+                used.insert(id.clone());
+                id
+            }
+        };
+
+        let class = format!("rheo-toc-{}", heading.tag_name());
+        let link = dom::Element::create("a", &[("href", &format!("#{}", id))]);
+        link.append_child(dom::Element::create_text(&text));
+        let item = dom::Element::create("li", &[("class", &class)]);
+        item.append_child(link);
+        list.append_child(item);
+    }
+    toc.append_child(list);
+    toc.outer_html()
+}
+
+/// Derive an id from heading text that is not yet used on the page.
+fn unique_slug(text: &str, used: &HashSet<String>) -> String {
+    let slug = text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug = if slug.is_empty() {
+        "section".to_string()
+    } else {
+        slug
+    };
+
+    if !used.contains(&slug) {
+        return slug;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", slug, n))
+        .find(|candidate| !used.contains(candidate))
+        .expect("unbounded range yields an unused id")
+}
+
+/// Names of the `{{placeholder}}` markers in a template, in order.
+fn placeholders(template: &str) -> impl Iterator<Item = &str> {
+    template.split("{{").skip(1).filter_map(|rest| {
+        let (name, _) = rest.split_once("}}")?;
+        Some(name.trim())
+    })
+}
+
+/// Replace `{{placeholder}}` markers in a single pass.
+///
+/// Unknown placeholders are kept verbatim. Substituted values are not scanned
+/// again, so page content containing `{{...}}` is left untouched.
+fn render<'a>(template: &str, value: impl Fn(&str) -> Option<&'a str>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = after[..end].trim();
+        match value(name) {
+            Some(value) => output.push_str(value),
+            None => {
+                warn!(placeholder = name, "unknown placeholder in HTML layout");
+                output.push_str(&rest[start..start + 2 + end + 2]);
+            }
+        }
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+    output
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "<!DOCTYPE html><html><head><title>Tom &amp; Jerry</title></head>\
+        <body><h1>Tom &amp; Jerry</h1><h2>Intro</h2><p>Hi {{content}}</p><h3 id=\"x\">Deep Dive</h3><h2>Intro</h2></body></html>";
+
+    #[test]
+    fn test_apply_layout() {
+        let layout = PageLayout::new(
+            "<html><head><title>{{ title }} | Site</title></head><body><header>{{nav}}</header><main>{{content}}</main><aside>{{toc}}</aside>{{unknown}}</body></html>",
+        );
+        let html = layout.apply(PAGE, "<nav>NAV</nav>").unwrap();
+
+        assert!(html.contains("<title>Tom &amp; Jerry | Site</title>"));
+        assert!(html.contains("<header><nav>NAV</nav></header>"));
+        // Content placeholders are not expanded again
+        assert!(html.contains(
+            "<main><h1>Tom &amp; Jerry</h1><h2 id=\"intro\">Intro</h2><p>Hi {{content}}</p>"
+        ));
+        assert!(html.contains("{{unknown}}"));
+    }
+
+    #[test]
+    fn test_toc_assigns_unique_ids() {
+        let layout = PageLayout::new("{{toc}}");
+        let toc = layout.apply(PAGE, "").unwrap();
+        assert_eq!(
+            toc,
+            "<nav class=\"rheo-toc\" aria-label=\"Contents\"><ol>\
+             <li class=\"rheo-toc-h2\"><a href=\"#intro\">Intro</a></li>\
+             <li class=\"rheo-toc-h3\"><a href=\"#x\">Deep Dive</a></li>\
+             <li class=\"rheo-toc-h2\"><a href=\"#intro-2\">Intro</a></li>\
+             </ol></nav>"
+        );
+    }
+
+    #[test]
+    fn test_toc_empty_without_headings() {
+        let layout = PageLayout::new("[{{toc}}]");
+        let html = layout
+            .apply("<html><body><p>Text</p></body></html>", "")
+            .unwrap();
+        assert_eq!(html, "[]");
+    }
+
+    #[test]
+    fn test_places_nav() {
+        assert!(PageLayout::new("<body>{{ nav }}{{content}}</body>").places_nav());
+        assert!(!PageLayout::new("<body>{{content}}</body>").places_nav());
+    }
+
+    #[test]
+    fn test_select_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("post.html"), "post {{content}}").unwrap();
+        std::fs::write(dir.path().join("default.html"), "default {{content}}").unwrap();
+        let layouts = PageLayouts::load(
+            dir.path(),
+            &[
+                HtmlLayout {
+                    template: "post.html".to_string(),
+                    files: vec!["posts/**".to_string()],
+                },
+                HtmlLayout {
+                    template: "default.html".to_string(),
+                    files: vec![],
+                },
+            ],
+        )
+        .unwrap();
+
+        let post = layouts.select(Path::new("posts/a.typ")).unwrap();
+        assert!(post.template.starts_with("post"));
+        let other = layouts.select(Path::new("about.typ")).unwrap();
+        assert!(other.template.starts_with("default"));
+        assert!(
+            PageLayouts::default()
+                .select(Path::new("about.typ"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_load_missing_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = PageLayouts::load(
+            dir.path(),
+            &[HtmlLayout {
+                template: "missing.html".to_string(),
+                files: vec![],
+            }],
+        );
+        assert!(result.is_err());
+    }
+}
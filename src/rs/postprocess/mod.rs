@@ -4,14 +4,20 @@
 //! - Link transformation (.typ → .html/.xhtml)
 //! - DOM manipulation utilities (html5ever)
 //! - HTML head injection (CSS/font links)
+//! - Site navigation injection (HTML spine)
+//! - Page layouts wrapping compiled HTML bodies
 
 use crate::constants::{HTML_EXT, XHTML_EXT};
 
 pub mod dom;
 pub mod html_head;
+pub mod layout;
+pub mod site_nav;
 
 // Re-export commonly used functions
-pub use html_head::inject_head_links;
+pub use html_head::{inject_head_links, inject_head_meta, inject_head_script};
+pub use layout::{PageLayout, PageLayouts};
+pub use site_nav::{SiteNav, inject_site_nav, render_site_nav};
 
 use std::path::PathBuf;
 
@@ -0,0 +1,329 @@
+//! Site navigation injection for HTML output.
+//!
+//! When an HTML spine is configured, every page gets a navigation element listing
+//! the spine's pages in order (with the current page marked) and previous/next links.
+
+use crate::Result;
+use crate::reticulate::types::PageRef;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use super::dom;
+
+/// A single page in the site navigation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NavEntry {
+    /// Source file of the page, used to identify the current page.
+    pub source: PathBuf,
+    /// Output path relative to the HTML output directory (e.g. "posts/intro.html").
+    pub href: String,
+    /// Page title shown in the navigation.
+    pub title: String,
+    /// Label other pages reference the page by (e.g. `about` for `@about`).
+    pub label: String,
+}
+
+/// Site navigation built from an HTML spine.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SiteNav {
+    /// Site title from the spine configuration.
+    pub title: Option<String>,
+    /// Pages in spine order.
+    pub entries: Vec<NavEntry>,
+}
+
+impl SiteNav {
+    /// Find the position of a source file in the navigation.
+    ///
+    /// Paths are compared after canonicalization, so relative and absolute
+    /// paths to the same file match.
+    pub fn position(&self, source: &Path) -> Option<usize> {
+        let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let source = canonical(source);
+        self.entries
+            .iter()
+            .position(|entry| canonical(&entry.source) == source)
+    }
+
+    /// The pages by label, with links relative to the page at `current`.
+    ///
+    /// # Arguments
+    /// * `current` - Index of the referencing page in `entries`
+    pub fn page_refs(&self, current: usize) -> HashMap<String, PageRef> {
+        let from = self
+            .entries
+            .get(current)
+            .map(|entry| entry.href.as_str())
+            .unwrap_or_default();
+        self.entries
+            .iter()
+            .map(|entry| {
+                let page = PageRef {
+                    href: relative_href(from, &entry.href),
+                    title: entry.title.clone(),
+                };
+                (entry.label.clone(), page)
+            })
+            .collect()
+    }
+}
+
+/// Inject site navigation into an HTML page.
+///
+/// Prepends a `<nav class="rheo-nav">` listing all pages to `<body>` and appends a
+/// `<nav class="rheo-pagination">` with previous/next links. The current page is
+/// marked with `aria-current="page"`. Links are relative to the current page, so
+/// they work for nested output layouts.
+///
+/// # Arguments
+/// * `html` - The HTML content to modify
+/// * `nav` - Site navigation
+/// * `current` - Index of the current page in `nav.entries`, if it is part of the spine
+///
+/// # Returns
+/// HTML with navigation injected into the <body> section
+///
+/// # Errors
+/// Returns error if HTML parsing or serialization fails
+pub fn inject_site_nav(html: &str, nav: &SiteNav, current: Option<usize>) -> Result<String> {
+    let dom = dom::HtmlDom::parse(html)?;
+
+    // html5ever always creates a <body> element
+    let Some(body) = dom.find_element("body") else {
+        return dom.serialize();
+    };
+
+    let (site_nav, pagination) = build_nav_elements(nav, current);
+    body.prepend_child(site_nav);
+    if let Some(pagination) = pagination {
+        body.append_child(pagination);
+    }
+
+    dom.serialize()
+}
+
+/// Render site navigation as an HTML fragment.
+///
+/// Used by page layouts that place the navigation themselves (`{{nav}}`). The
+/// fragment contains the same elements [`inject_site_nav`] would insert.
+///
+/// # Arguments
+/// * `nav` - Site navigation
+/// * `current` - Index of the current page in `nav.entries`, if it is part of the spine
+///
+/// # Returns
+/// HTML of the `rheo-nav` element, followed by `rheo-pagination` if applicable
+pub fn render_site_nav(nav: &SiteNav, current: Option<usize>) -> Result<String> {
+    let (site_nav, pagination) = build_nav_elements(nav, current);
+    let mut html = site_nav.outer_html()?;
+    if let Some(pagination) = pagination {
+        html.push_str(&pagination.outer_html()?);
+    }
+    Ok(html)
+}
+
+/// Build the site navigation list and, for spine pages, the previous/next links.
+fn build_nav_elements(
+    nav: &SiteNav,
+    current: Option<usize>,
+) -> (dom::Element, Option<dom::Element>) {
+    let current_href = current
+        .and_then(|i| nav.entries.get(i))
+        .map(|entry| entry.href.as_str())
+        .unwrap_or_default();
+    let href = |target: &str| relative_href(current_href, target);
+
+    // Site navigation listing every page
+    let site_nav = dom::Element::create("nav", &[("class", "rheo-nav"), ("aria-label", "Site")]);
+    if let (Some(title), Some(first)) = (&nav.title, nav.entries.first()) {
+        let link = dom::Element::create(
+            "a",
+            &[("class", "rheo-nav-title"), ("href", &href(&first.href))],
+        );
+        link.append_child(dom::Element::create_text(title));
+        site_nav.append_child(link);
+    }
+
+    let list = dom::Element::create("ol", &[]);
+    for (i, entry) in nav.entries.iter().enumerate() {
+        let target = href(&entry.href);
+        let mut attrs = vec![("href", target.as_str())];
+        if current == Some(i) {
+            attrs.push(("aria-current", "page"));
+        }
+        let link = dom::Element::create("a", &attrs);
+        link.append_child(dom::Element::create_text(&entry.title));
+        let item = dom::Element::create("li", &[]);
+        item.append_child(link);
+        list.append_child(item);
+    }
+    site_nav.append_child(list);
+
+    // Previous/next links
+    let current = match current {
+        Some(current) => current,
+        None => return (site_nav, None),
+    };
+    let prev = current
+        .checked_sub(1)
+        .and_then(|i| nav.entries.get(i))
+        .map(|entry| ("prev", entry));
+    let next = nav.entries.get(current + 1).map(|entry| ("next", entry));
+    if prev.is_none() && next.is_none() {
+        return (site_nav, None);
+    }
+
+    let pagination = dom::Element::create(
+        "nav",
+        &[("class", "rheo-pagination"), ("aria-label", "Pagination")],
+    );
+    for (rel, entry) in prev.into_iter().chain(next) {
+        let link = dom::Element::create("a", &[("rel", rel), ("href", &href(&entry.href))]);
+        link.append_child(dom::Element::create_text(&entry.title));
+        pagination.append_child(link);
+    }
+    (site_nav, Some(pagination))
+}
+
+/// Compute the link from one page to another, both relative to the output directory.
+///
+/// # Arguments
+/// * `from` - Output path of the linking page (e.g. "posts/intro.html")
+/// * `to` - Output path of the target page (e.g. "index.html")
+///
+/// # Returns
+/// Relative URL (e.g. "../index.html")
+pub fn relative_href(from: &str, to: &str) -> String {
+    let from_dir: Vec<Component> = Path::new(from)
+        .parent()
+        .map(|dir| dir.components().collect())
+        .unwrap_or_default();
+    let to_components: Vec<Component> = Path::new(to).components().collect();
+
+    let common = from_dir
+        .iter()
+        .zip(&to_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from_dir.len() - common];
+    parts.extend(
+        to_components[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nav() -> SiteNav {
+        let entry = |source: &str, href: &str, title: &str| NavEntry {
+            source: PathBuf::from(source),
+            href: href.to_string(),
+            title: title.to_string(),
+            label: Path::new(source)
+                .file_stem()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned(),
+        };
+        SiteNav {
+            title: Some("My Website".to_string()),
+            entries: vec![
+                entry("index.typ", "index.html", "Home"),
+                entry("posts/intro.typ", "posts/intro.html", "Intro"),
+                entry("about.typ", "about.html", "About"),
+            ],
+        }
+    }
+
+    const PAGE: &str =
+        "<!DOCTYPE html><html><head></head><body><article>Content</article></body></html>";
+
+    #[test]
+    fn test_relative_href() {
+        assert_eq!(relative_href("index.html", "about.html"), "about.html");
+        assert_eq!(
+            relative_href("posts/intro.html", "index.html"),
+            "../index.html"
+        );
+        assert_eq!(
+            relative_href("index.html", "posts/intro.html"),
+            "posts/intro.html"
+        );
+        assert_eq!(
+            relative_href("posts/a/b.html", "posts/c/d.html"),
+            "../c/d.html"
+        );
+        assert_eq!(relative_href("", "index.html"), "index.html");
+    }
+
+    #[test]
+    fn test_page_refs() {
+        let refs = nav().page_refs(1);
+        assert_eq!(refs.len(), 3);
+        assert_eq!(refs["about"].href, "../about.html");
+        assert_eq!(refs["about"].title, "About");
+        assert_eq!(refs["intro"].href, "intro.html");
+    }
+
+    #[test]
+    fn test_inject_site_nav_marks_current_page() {
+        let result = inject_site_nav(PAGE, &nav(), Some(0)).unwrap();
+
+        assert!(result.contains(r#"<body><nav class="rheo-nav" aria-label="Site">"#));
+        assert!(result.contains(r#"<a class="rheo-nav-title" href="index.html">My Website</a>"#));
+        assert!(result.contains(r#"<li><a href="index.html" aria-current="page">Home</a></li>"#));
+        assert!(result.contains(r#"<li><a href="posts/intro.html">Intro</a></li>"#));
+
+        // Navigation comes before the content, pagination after it
+        let nav_pos = result.find("rheo-nav").unwrap();
+        let content_pos = result.find("<article>").unwrap();
+        let pagination_pos = result.find("rheo-pagination").unwrap();
+        assert!(nav_pos < content_pos && content_pos < pagination_pos);
+    }
+
+    #[test]
+    fn test_inject_site_nav_prev_next() {
+        let result = inject_site_nav(PAGE, &nav(), Some(1)).unwrap();
+
+        // Links are relative to the nested page
+        assert!(result.contains(r#"<a rel="prev" href="../index.html">Home</a>"#));
+        assert!(result.contains(r#"<a rel="next" href="../about.html">About</a>"#));
+        assert!(result.contains(r#"<a href="intro.html" aria-current="page">Intro</a>"#));
+
+        // First page has no previous link, last page no next link
+        let first = inject_site_nav(PAGE, &nav(), Some(0)).unwrap();
+        assert!(!first.contains(r#"rel="prev""#));
+        let last = inject_site_nav(PAGE, &nav(), Some(2)).unwrap();
+        assert!(!last.contains(r#"rel="next""#));
+    }
+
+    #[test]
+    fn test_inject_site_nav_page_outside_spine() {
+        let result = inject_site_nav(PAGE, &nav(), None).unwrap();
+        assert!(result.contains("rheo-nav"));
+        assert!(!result.contains("aria-current"));
+        assert!(!result.contains("rheo-pagination"));
+    }
+
+    #[test]
+    fn test_render_site_nav() {
+        let html = render_site_nav(&nav(), Some(2)).unwrap();
+        assert!(html.starts_with(r#"<nav class="rheo-nav" aria-label="Site">"#));
+        assert!(html.ends_with(
+            r#"<nav class="rheo-pagination" aria-label="Pagination"><a rel="prev" href="posts/intro.html">Intro</a></nav>"#
+        ));
+    }
+
+    #[test]
+    fn test_position() {
+        let nav = nav();
+        assert_eq!(nav.position(Path::new("posts/intro.typ")), Some(1));
+        assert_eq!(nav.position(Path::new("missing.typ")), None);
+    }
+}
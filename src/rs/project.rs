@@ -1,9 +1,10 @@
 use crate::config::EpubSpine;
+use crate::discovery::ContentFilter;
 use crate::formats::pdf::DocumentTitle;
+use crate::world::WorldSettings;
 use crate::{Result, RheoConfig, RheoError};
 use std::path::{Path, PathBuf};
 use tracing::debug;
-use walkdir::WalkDir;
 
 /// Mode for project compilation
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -29,6 +30,9 @@ pub struct ProjectConfig {
     /// List of .typ files in the project
     pub typ_files: Vec<PathBuf>,
 
+    /// Filter applied to discovered files and spine globs (e.g. drafts)
+    pub filter: ContentFilter,
+
     /// Project-specific style.css (for HTML export) if it exists
     pub style_css: Option<PathBuf>,
 
@@ -46,7 +50,12 @@ impl ProjectConfig {
     /// # Arguments
     /// * `path` - Path to project directory or single .typ file
     /// * `config_path` - Optional path to custom rheo.toml config file
-    pub fn from_path(path: &Path, config_path: Option<&Path>) -> Result<Self> {
+    /// * `include_drafts` - Whether draft documents are discovered
+    pub fn from_path(
+        path: &Path,
+        config_path: Option<&Path>,
+        include_drafts: bool,
+    ) -> Result<Self> {
         // Check if path exists and determine if it's a file or directory
         let metadata = path
             .metadata()
@@ -55,14 +64,71 @@ impl ProjectConfig {
         if metadata.is_file() {
             Self::from_single_file(path, config_path)
         } else if metadata.is_dir() {
-            Self::from_directory(path, config_path)
+            Self::from_directory(path, config_path, include_drafts)
         } else {
             Err(RheoError::path(path, "path must be a file or directory"))
         }
     }
 
+    /// Rediscover the project's .typ files, e.g. after files were created or removed
+    /// in watch mode.
+    ///
+    /// In single-file mode the file list never changes.
+    ///
+    /// # Returns
+    /// The files added to and removed from `typ_files`
+    pub fn rediscover(&mut self) -> DiscoveryChanges {
+        if self.mode == ProjectMode::SingleFile {
+            return DiscoveryChanges::default();
+        }
+
+        let typ_files = self.filter.discover_typ_files();
+        let changes = DiscoveryChanges {
+            added: typ_files
+                .iter()
+                .filter(|file| !self.typ_files.contains(file))
+                .cloned()
+                .collect(),
+            removed: self
+                .typ_files
+                .iter()
+                .filter(|file| !typ_files.contains(file))
+                .cloned()
+                .collect(),
+        };
+        self.typ_files = typ_files;
+        changes
+    }
+
+    /// Settings of the worlds compiling this project.
+    ///
+    /// # Errors
+    /// Returns an error if a configured font path is not a directory
+    pub fn world_settings(&self) -> Result<WorldSettings> {
+        let font_paths = self.config.resolve_font_paths(&self.root);
+        if let Some(dir) = font_paths.iter().find(|dir| !dir.is_dir()) {
+            return Err(RheoError::path(dir, "font path is not a directory"));
+        }
+        Ok(WorldSettings {
+            inputs: self.config.inputs.clone(),
+            font_paths,
+            packages_dir: self
+                .config
+                .packages_dir
+                .as_ref()
+                .map(|dir| self.root.join(dir)),
+            offline: self.config.offline,
+            inject_template: self.config.inject_template,
+            build_date: self.config.build.resolve_date()?,
+        })
+    }
+
     /// Detect project configuration from a directory path
-    fn from_directory(path: &Path, config_path: Option<&Path>) -> Result<Self> {
+    fn from_directory(
+        path: &Path,
+        config_path: Option<&Path>,
+        include_drafts: bool,
+    ) -> Result<Self> {
         // Canonicalize the root path for consistent path handling
         let root = path.canonicalize().map_err(|e| {
             RheoError::path(
@@ -107,13 +173,11 @@ impl ProjectConfig {
             .unwrap_or_else(|| root.clone());
         debug!(search_dir = %search_dir.display(), "searching for .typ files");
 
-        // Find all .typ files in the search directory (recursive walk)
-        let typ_files: Vec<PathBuf> = WalkDir::new(&search_dir)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("typ"))
-            .map(|e| e.path().to_path_buf())
-            .collect();
+        // Find all .typ files in the search directory (recursive walk), skipping ignored,
+        // excluded and draft files
+        let filter =
+            ContentFilter::new(&search_dir, &config, include_drafts)?.with_project_root(&root);
+        let typ_files = filter.discover_typ_files();
 
         // Detect optional project-specific resources
         let style_css = root.join("style.css");
@@ -128,6 +192,7 @@ impl ProjectConfig {
             root,
             config,
             typ_files,
+            filter,
             style_css,
             mode: ProjectMode::Directory,
             config_path: loaded_config_path,
@@ -178,8 +243,10 @@ impl ProjectConfig {
             config
         };
 
-        // Single file in typ_files list
+        // Single file in typ_files list; an explicitly named file is built even if it is
+        // excluded or a draft
         let typ_files = vec![file_path.clone()];
+        let filter = ContentFilter::default();
 
         // Check for optional resources in root directory
         let style_css = root.join("style.css");
@@ -194,6 +261,7 @@ impl ProjectConfig {
             root,
             config,
             typ_files,
+            filter,
             style_css,
             mode: ProjectMode::SingleFile,
             config_path: loaded_config_path,
@@ -201,6 +269,22 @@ impl ProjectConfig {
     }
 }
 
+/// Files added to and removed from a project by rediscovery.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DiscoveryChanges {
+    /// Newly discovered .typ files
+    pub added: Vec<PathBuf>,
+    /// .typ files that no longer take part in the build
+    pub removed: Vec<PathBuf>,
+}
+
+impl DiscoveryChanges {
+    /// Whether the set of project files changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
 /// Apply smart defaults when no rheo.toml exists.
 ///
 /// This generates sensible spine configurations for EPUB based on the project
@@ -238,7 +322,7 @@ mod tests {
         let file = temp.path().join("document.typ");
         fs::write(&file, "#heading[Test]").unwrap();
 
-        let project = ProjectConfig::from_path(&file, None).unwrap();
+        let project = ProjectConfig::from_path(&file, None, false).unwrap();
 
         assert_eq!(project.name, "document");
         assert_eq!(project.mode, ProjectMode::SingleFile);
@@ -252,7 +336,7 @@ mod tests {
         let file = temp.path().join("document.txt");
         fs::write(&file, "test").unwrap();
 
-        let result = ProjectConfig::from_path(&file, None);
+        let result = ProjectConfig::from_path(&file, None, false);
         assert!(result.is_err());
         let err_msg = format!("{}", result.unwrap_err());
         assert!(err_msg.contains(".typ extension"));
@@ -261,7 +345,7 @@ mod tests {
     #[test]
     fn test_single_file_nonexistent_fails() {
         let path = PathBuf::from("/tmp/nonexistent_file_12345_rheo_test.typ");
-        let result = ProjectConfig::from_path(&path, None);
+        let result = ProjectConfig::from_path(&path, None, false);
         assert!(result.is_err());
         let err_msg = format!("{}", result.unwrap_err());
         assert!(err_msg.contains("does not exist"));
@@ -279,7 +363,7 @@ mod tests {
         let file = temp.path().join("document.typ");
         fs::write(&file, "#heading[Test]").unwrap();
 
-        let project = ProjectConfig::from_path(&file, None).unwrap();
+        let project = ProjectConfig::from_path(&file, None, false).unwrap();
 
         assert!(project.style_css.is_some());
     }
@@ -290,12 +374,95 @@ mod tests {
         fs::write(temp.path().join("doc1.typ"), "#heading[1]").unwrap();
         fs::write(temp.path().join("doc2.typ"), "#heading[2]").unwrap();
 
-        let project = ProjectConfig::from_path(temp.path(), None).unwrap();
+        let project = ProjectConfig::from_path(temp.path(), None, false).unwrap();
 
         assert_eq!(project.mode, ProjectMode::Directory);
         assert_eq!(project.typ_files.len(), 2);
     }
 
+    #[test]
+    fn test_directory_mode_skips_drafts() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("rheo.toml"),
+            format!(
+                "version = \"{}\"\ndrafts = [\"wip-*.typ\"]\n",
+                env!("CARGO_PKG_VERSION")
+            ),
+        )
+        .unwrap();
+        fs::write(temp.path().join("done.typ"), "= Done").unwrap();
+        fs::write(temp.path().join("wip-next.typ"), "= Next").unwrap();
+        fs::write(
+            temp.path().join("marked.typ"),
+            "#metadata((draft: true)) <rheo>",
+        )
+        .unwrap();
+
+        let project = ProjectConfig::from_path(temp.path(), None, false).unwrap();
+        assert_eq!(project.typ_files, vec![project.root.join("done.typ")]);
+
+        let project = ProjectConfig::from_path(temp.path(), None, true).unwrap();
+        assert_eq!(project.typ_files.len(), 3);
+
+        // An explicitly named draft or excluded file is still compiled
+        let project =
+            ProjectConfig::from_path(&temp.path().join("marked.typ"), None, false).unwrap();
+        assert_eq!(project.typ_files.len(), 1);
+    }
+
+    #[test]
+    fn test_directory_mode_skips_excluded() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("rheo.toml"),
+            format!(
+                "version = \"{}\"\nexclude = [\"lib/**\"]\n",
+                env!("CARGO_PKG_VERSION")
+            ),
+        )
+        .unwrap();
+        for dir in ["lib", "_partials", "posts"] {
+            fs::create_dir_all(temp.path().join(dir)).unwrap();
+        }
+        fs::write(temp.path().join("index.typ"), "= Home").unwrap();
+        fs::write(temp.path().join("posts/intro.typ"), "= Intro").unwrap();
+        fs::write(temp.path().join("posts/_aside.typ"), "Aside").unwrap();
+        fs::write(temp.path().join("lib/template.typ"), "#let t = 1").unwrap();
+        fs::write(temp.path().join("_partials/header.typ"), "Header").unwrap();
+
+        let project = ProjectConfig::from_path(temp.path(), None, true).unwrap();
+        let mut files = project.typ_files.clone();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                project.root.join("index.typ"),
+                project.root.join("posts/intro.typ")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rediscover() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("a.typ"), "A").unwrap();
+        fs::write(temp.path().join("b.typ"), "B").unwrap();
+        let mut project = ProjectConfig::from_path(temp.path(), None, false).unwrap();
+
+        assert!(project.rediscover().is_empty());
+
+        fs::remove_file(temp.path().join("a.typ")).unwrap();
+        fs::create_dir(temp.path().join("posts")).unwrap();
+        fs::write(temp.path().join("posts/c.typ"), "C").unwrap();
+        fs::write(temp.path().join("posts/_partial.typ"), "P").unwrap();
+
+        let changes = project.rediscover();
+        assert_eq!(changes.added, vec![project.root.join("posts/c.typ")]);
+        assert_eq!(changes.removed, vec![project.root.join("a.typ")]);
+        assert_eq!(project.typ_files.len(), 2);
+    }
+
     #[test]
     fn test_single_file_with_relative_path() {
         let temp = TempDir::new().unwrap();
@@ -307,7 +474,7 @@ mod tests {
         std::env::set_current_dir(temp.path()).unwrap();
 
         // Use relative path (no directory component)
-        let result = ProjectConfig::from_path(Path::new("document.typ"), None);
+        let result = ProjectConfig::from_path(Path::new("document.typ"), None, false);
 
         // Restore original directory
         std::env::set_current_dir(original_dir).unwrap();
@@ -326,7 +493,7 @@ mod tests {
         let file = temp.path().join("document.typ");
         fs::write(&file, "#heading[Test]").unwrap();
 
-        let project = ProjectConfig::from_path(&file, None).unwrap();
+        let project = ProjectConfig::from_path(&file, None, false).unwrap();
 
         // Single-file mode without custom config should have None
         assert!(project.config_path.is_none());
@@ -338,7 +505,7 @@ mod tests {
         let file = temp.path().join("my-document.typ");
         fs::write(&file, "#heading[Test]").unwrap();
 
-        let project = ProjectConfig::from_path(&file, None).unwrap();
+        let project = ProjectConfig::from_path(&file, None, false).unwrap();
 
         // Should have default spine config for EPUB
         assert!(project.config.epub.spine.is_some());
@@ -353,7 +520,7 @@ mod tests {
         fs::write(temp.path().join("a.typ"), "A").unwrap();
         fs::write(temp.path().join("b.typ"), "B").unwrap();
 
-        let project = ProjectConfig::from_path(temp.path(), None).unwrap();
+        let project = ProjectConfig::from_path(temp.path(), None, false).unwrap();
 
         // Should have default spine config for EPUB
         assert!(project.config.epub.spine.is_some());
@@ -377,7 +544,7 @@ mod tests {
         .unwrap();
         fs::write(temp.path().join("custom.typ"), "content").unwrap();
 
-        let project = ProjectConfig::from_path(temp.path(), None).unwrap();
+        let project = ProjectConfig::from_path(temp.path(), None, false).unwrap();
 
         // Should preserve explicit config
         let merge = project.config.epub.spine.as_ref().unwrap();
@@ -391,7 +558,7 @@ mod tests {
         fs::write(temp.path().join("a.typ"), "A").unwrap();
         fs::write(temp.path().join("b.typ"), "B").unwrap();
 
-        let project = ProjectConfig::from_path(temp.path(), None).unwrap();
+        let project = ProjectConfig::from_path(temp.path(), None, false).unwrap();
 
         // PDF should not get default spine config (backwards compatibility)
         assert!(project.config.pdf.spine.is_none());
@@ -7,6 +7,8 @@ use tracing::info;
 pub struct FormatResult {
     pub succeeded: usize,
     pub failed: usize,
+    /// Outputs that were up to date and not recompiled
+    pub fresh: usize,
 }
 
 /// Aggregated compilation results across all output formats
@@ -33,6 +35,11 @@ impl CompilationResults {
         self.results.entry(format).or_default().failed += 1;
     }
 
+    /// Record an output that was up to date and skipped for the given format
+    pub fn record_fresh(&mut self, format: OutputFormat) {
+        self.results.entry(format).or_default().fresh += 1;
+    }
+
     /// Get the result counts for a specific format
     pub fn get(&self, format: OutputFormat) -> FormatResult {
         self.results.get(&format).copied().unwrap_or_default()
@@ -47,9 +54,17 @@ impl CompilationResults {
     pub fn log_summary(&self, requested_formats: &[OutputFormat]) {
         for format in requested_formats {
             let result = self.get(*format);
-            let total = result.succeeded + result.failed;
+            let total = result.succeeded + result.failed + result.fresh;
             if total > 0 {
-                if result.failed == 0 {
+                if result.fresh > 0 {
+                    info!(
+                        format = format!("{:?}", format),
+                        "compiled {} file(s), {} failed, {} fresh",
+                        result.succeeded,
+                        result.failed,
+                        result.fresh
+                    );
+                } else if result.failed == 0 {
                     info!(
                         format = format!("{:?}", format),
                         "successfully compiled {} file(s)", result.succeeded
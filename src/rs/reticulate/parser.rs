@@ -1,9 +1,14 @@
-use crate::reticulate::types::LinkInfo;
+use crate::reticulate::types::{LinkInfo, RefInfo};
+use std::collections::HashSet;
+use std::ops::Range;
 use typst::syntax::{Source, SyntaxKind, SyntaxNode};
 
 /// The identifier in the Typst AST for links.
 const LINK_IDENT_ID: &str = "link";
 
+/// The identifier in the Typst AST for the document set rule.
+const DOCUMENT_IDENT_ID: &str = "document";
+
 /// Extract all links from Typst source by parsing and traversing AST
 pub fn extract_links(source: &Source) -> Vec<LinkInfo> {
     let root = typst::syntax::parse(source.text());
@@ -115,6 +120,95 @@ fn extract_text_from_node(node: &SyntaxNode) -> Option<String> {
     }
 }
 
+/// Find the page label attached to the document set rule (`#set document(..) <label>`).
+///
+/// Typst doesn't allow labels on set rules, so the label is returned together
+/// with the byte range that must be removed for the source to parse: from the
+/// end of the set rule to the end of the label.
+pub fn find_page_label(source: &Source) -> Option<(String, Range<usize>)> {
+    let root = typst::syntax::parse(source.text());
+    let children: Vec<&SyntaxNode> = root.children().collect();
+    let mut offset = 0;
+    for (i, child) in children.iter().enumerate() {
+        offset += child.len();
+        let is_document_rule = child.kind() == SyntaxKind::SetRule
+            && child
+                .children()
+                .any(|n| n.kind() == SyntaxKind::Ident && n.text() == DOCUMENT_IDENT_ID);
+        if !is_document_rule {
+            continue;
+        }
+
+        // The label follows on the same line, after the parser's error
+        let mut end = offset;
+        for next in &children[i + 1..] {
+            end += next.len();
+            match next.kind() {
+                SyntaxKind::Error => {}
+                SyntaxKind::Space if !next.text().contains('\n') => {}
+                SyntaxKind::Label => return Some((label_name(next), offset..end)),
+                _ => break,
+            }
+        }
+    }
+    None
+}
+
+/// Extract all references (`@label`) from Typst source
+pub fn extract_refs(source: &Source) -> Vec<RefInfo> {
+    let root = typst::syntax::parse(source.text());
+    let mut refs = Vec::new();
+    extract_refs_from_node(&root, 0, &mut refs);
+    refs
+}
+
+fn extract_refs_from_node(node: &SyntaxNode, offset: usize, refs: &mut Vec<RefInfo>) {
+    if node.kind() == SyntaxKind::Ref {
+        let marker = node.children().find(|n| n.kind() == SyntaxKind::RefMarker);
+        if let Some(marker) = marker {
+            refs.push(RefInfo {
+                label: marker.text().trim_start_matches('@').to_string(),
+                supplement: node
+                    .children()
+                    .find(|n| n.kind() == SyntaxKind::ContentBlock)
+                    .map(|block| block.clone().into_text().to_string()),
+                byte_range: offset..(offset + node.len()),
+            });
+        }
+        return;
+    }
+
+    let mut child_offset = offset;
+    for child in node.children() {
+        extract_refs_from_node(child, child_offset, refs);
+        child_offset += child.len();
+    }
+}
+
+/// Collect the names of all labels (`<label>`) in Typst source
+pub fn extract_labels(source: &Source) -> HashSet<String> {
+    fn collect(node: &SyntaxNode, labels: &mut HashSet<String>) {
+        if node.kind() == SyntaxKind::Label {
+            labels.insert(label_name(node));
+        }
+        for child in node.children() {
+            collect(child, labels);
+        }
+    }
+
+    let mut labels = HashSet::new();
+    collect(&typst::syntax::parse(source.text()), &mut labels);
+    labels
+}
+
+/// Name of a label node, without the angle brackets
+fn label_name(node: &SyntaxNode) -> String {
+    node.text()
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .to_string()
+}
+
 /// Calculate the byte offset of a target node within the root AST
 fn calculate_node_offset(root: &SyntaxNode, target: &SyntaxNode) -> Option<usize> {
     calculate_node_offset_impl(root, target, 0)
@@ -213,4 +307,30 @@ mod tests {
         assert_eq!(links[0].url, "url");
         assert_eq!(links[0].body, "bold and italic");
     }
+
+    #[test]
+    fn test_find_page_label() {
+        let text = "#set document(title: [About]) <about>\n\n= About";
+        let (label, range) = find_page_label(&Source::detached(text)).unwrap();
+        assert_eq!(label, "about");
+        assert_eq!(&text[range], " <about>");
+
+        // Labels on other lines or elements are not page labels
+        let source = Source::detached("#set document(title: [About])\n= About <about>");
+        assert!(find_page_label(&source).is_none());
+    }
+
+    #[test]
+    fn test_extract_refs() {
+        let text = "See @about and @intro[the intro].";
+        let refs = extract_refs(&Source::detached(text));
+
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].label, "about");
+        assert_eq!(refs[0].supplement, None);
+        assert_eq!(&text[refs[0].byte_range.clone()], "@about");
+        assert_eq!(refs[1].label, "intro");
+        assert_eq!(refs[1].supplement.as_deref(), Some("[the intro]"));
+        assert_eq!(&text[refs[1].byte_range.clone()], "@intro[the intro]");
+    }
 }
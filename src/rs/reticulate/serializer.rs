@@ -51,6 +51,7 @@ pub fn apply_transformations(
                 // New:      #link(<label>)[body]
                 replace_url_in_link(original, new_label, true)
             }
+            LinkTransform::Replace { text } => text.clone(),
 
             LinkTransform::KeepOriginal => {
                 // No change
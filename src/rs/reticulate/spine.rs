@@ -1,4 +1,5 @@
 use crate::config::SpineConfig;
+use crate::discovery::ContentFilter;
 use crate::formats::pdf::{DocumentTitle, sanitize_label_name};
 use crate::{OutputFormat, Result, RheoError, TYP_EXT};
 use std::collections::HashSet;
@@ -33,6 +34,7 @@ impl RheoSpine {
     /// * `root` - Project root directory
     /// * `spine_config` - Optional spine configuration (determines spine files)
     /// * `output_format` - Target output format (determines link transformation behavior)
+    /// * `filter` - Filter applied to globbed spine files (e.g. to skip drafts)
     ///
     /// # Returns
     /// A RheoSpine containing transformed Typst sources ready for compilation.
@@ -40,9 +42,10 @@ impl RheoSpine {
         root: &Path,
         spine_config: Option<&dyn SpineConfig>,
         output_format: OutputFormat,
+        filter: &ContentFilter,
     ) -> Result<RheoSpine> {
         // Generate spine: ordered list of .typ files
-        let spine_files = generate_spine(root, spine_config, false)?;
+        let spine_files = generate_spine(root, spine_config, false, filter)?;
 
         // Check for duplicate filenames
         check_duplicate_filenames(&spine_files)?;
@@ -170,7 +173,7 @@ fn check_duplicate_filenames(spine_files: &[PathBuf]) -> Result<()> {
     Ok(())
 }
 
-fn collect_one_typst_file(root: &Path) -> Result<Vec<PathBuf>> {
+fn collect_one_typst_file(root: &Path, filter: &ContentFilter) -> Result<Vec<PathBuf>> {
     let typst_files: Vec<PathBuf> = WalkDir::new(root)
         .into_iter()
         .filter_map(|entry| Some(entry.ok()?.path().to_path_buf()))
@@ -181,6 +184,7 @@ fn collect_one_typst_file(root: &Path) -> Result<Vec<PathBuf>> {
                 .map(|ext| ext == &TYP_EXT[1..])
                 .unwrap_or(false)
         })
+        .filter(|entry| filter.includes(entry))
         .collect();
 
     match typst_files.len() {
@@ -198,6 +202,7 @@ fn collect_one_typst_file(root: &Path) -> Result<Vec<PathBuf>> {
 /// * `root` - Project root directory
 /// * `spine_config` - Optional spine configuration with vertebrae patterns
 /// * `require_spine` - If true, spine_config must be provided (PDF mode)
+/// * `filter` - Filter applied to matched files (e.g. to skip drafts)
 ///
 /// # Errors
 /// Returns error if:
@@ -209,6 +214,7 @@ pub fn generate_spine(
     root: &Path,
     spine_config: Option<&dyn SpineConfig>,
     require_spine: bool,
+    filter: &ContentFilter,
 ) -> Result<Vec<PathBuf>> {
     // PDF mode: spine config is required
     if require_spine && spine_config.is_none() {
@@ -219,11 +225,11 @@ pub fn generate_spine(
 
     match spine_config {
         // Single-file mode
-        None => collect_one_typst_file(root),
+        None => collect_one_typst_file(root, filter),
 
         // Empty vertebrae pattern: auto-discover single file only
         // This is used for single-file mode with default EPUB spine config
-        Some(spine) if spine.vertebrae().is_empty() => collect_one_typst_file(root),
+        Some(spine) if spine.vertebrae().is_empty() => collect_one_typst_file(root, filter),
 
         // Vertebrae is specified
         // Process glob patterns from spine config
@@ -240,6 +246,7 @@ pub fn generate_spine(
                     .filter(|path| path.is_file())
                     .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("typ"))
                     .filter(|path| path.file_name().is_some()) // Ensure path has a filename
+                    .filter(|path| filter.includes(path))
                     .collect();
 
                 // Sort lexicographically within each pattern
@@ -284,7 +291,7 @@ mod tests {
     #[test]
     fn test_generate_spine_requires_merge_mode() {
         let temp = create_test_dir_with_files(&["test.typ"]);
-        let result = generate_spine(temp.path(), None, true);
+        let result = generate_spine(temp.path(), None, true, &ContentFilter::default());
         assert!(result.is_err());
         assert!(
             result
@@ -297,7 +304,7 @@ mod tests {
     #[test]
     fn test_generate_spine_epub_single_file_fallback() {
         let temp = create_test_dir_with_files(&["single.typ"]);
-        let result = generate_spine(temp.path(), None, false);
+        let result = generate_spine(temp.path(), None, false, &ContentFilter::default());
         assert!(result.is_ok());
         let files = result.unwrap();
         assert_eq!(files.len(), 1);
@@ -307,7 +314,7 @@ mod tests {
     #[test]
     fn test_generate_spine_epub_multiple_files_error() {
         let temp = create_test_dir_with_files(&["first.typ", "second.typ"]);
-        let result = generate_spine(temp.path(), None, false);
+        let result = generate_spine(temp.path(), None, false, &ContentFilter::default());
         assert!(result.is_err());
         assert!(
             result
@@ -320,7 +327,7 @@ mod tests {
     #[test]
     fn test_generate_spine_epub_no_files_error() {
         let temp = create_test_dir_with_files(&["readme.md"]);
-        let result = generate_spine(temp.path(), None, false);
+        let result = generate_spine(temp.path(), None, false, &ContentFilter::default());
         assert!(result.is_err());
         assert!(
             result
@@ -337,7 +344,7 @@ mod tests {
             title: Some("Test".to_string()),
             vertebrae: vec!["*.typ".to_string()],
         };
-        let result = generate_spine(temp.path(), Some(&spine), false);
+        let result = generate_spine(temp.path(), Some(&spine), false, &ContentFilter::default());
         assert!(result.is_ok());
         let files = result.unwrap();
         assert_eq!(files.len(), 3);
@@ -360,7 +367,7 @@ mod tests {
             ],
             merge: None,
         };
-        let result = generate_spine(temp.path(), Some(&spine), true);
+        let result = generate_spine(temp.path(), Some(&spine), true, &ContentFilter::default());
         assert!(result.is_ok());
         let files = result.unwrap();
         assert_eq!(files.len(), 4);
@@ -386,6 +393,28 @@ mod tests {
         assert_eq!(files[3].file_name().unwrap(), "appendix.typ");
     }
 
+    #[test]
+    fn test_generate_spine_skips_drafts() {
+        let temp = create_test_dir_with_files(&["a.typ", "b.typ", "drafts/c.typ"]);
+        fs::write(temp.path().join("b.typ"), "#metadata((draft: true)) <rheo>").unwrap();
+        let spine = HtmlSpine {
+            title: None,
+            vertebrae: vec!["**/*.typ".to_string()],
+        };
+        let config = crate::RheoConfig {
+            drafts: vec!["drafts/**".to_string()],
+            ..Default::default()
+        };
+
+        let filter = ContentFilter::new(temp.path(), &config, false).unwrap();
+        let files = generate_spine(temp.path(), Some(&spine), false, &filter).unwrap();
+        assert_eq!(files, vec![temp.path().join("a.typ")]);
+
+        let filter = ContentFilter::new(temp.path(), &config, true).unwrap();
+        let files = generate_spine(temp.path(), Some(&spine), false, &filter).unwrap();
+        assert_eq!(files.len(), 3);
+    }
+
     #[test]
     fn test_generate_spine_no_matches_error() {
         let temp = create_test_dir_with_files(&["readme.md"]);
@@ -394,7 +423,7 @@ mod tests {
             vertebrae: vec!["*.typ".to_string()],
             merge: None,
         };
-        let result = generate_spine(temp.path(), Some(&spine), false);
+        let result = generate_spine(temp.path(), Some(&spine), false, &ContentFilter::default());
         assert!(result.is_err());
         assert!(
             result
@@ -413,7 +442,7 @@ mod tests {
             merge: None,
         };
 
-        let result = generate_spine(temp.path(), Some(&spine), false);
+        let result = generate_spine(temp.path(), Some(&spine), false, &ContentFilter::default());
         assert!(result.is_ok());
         let files = result.unwrap();
         assert_eq!(files.len(), 1);
@@ -429,7 +458,7 @@ mod tests {
             merge: None,
         };
 
-        let result = generate_spine(temp.path(), Some(&spine), false);
+        let result = generate_spine(temp.path(), Some(&spine), false, &ContentFilter::default());
         assert!(result.is_err());
         assert!(
             result
@@ -444,7 +473,7 @@ mod tests {
         let temp = create_test_dir_with_files(&["single.typ"]);
 
         // Test that fallback with single file works and is ordered
-        let result = generate_spine(temp.path(), None, false);
+        let result = generate_spine(temp.path(), None, false, &ContentFilter::default());
         assert!(result.is_ok());
         let files = result.unwrap();
         assert_eq!(files.len(), 1);
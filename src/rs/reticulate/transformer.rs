@@ -1,4 +1,4 @@
-use super::types::{LinkInfo, LinkTransform};
+use super::types::{LinkInfo, LinkTransform, PageRef};
 use crate::constants::TYP_EXT;
 use crate::formats::pdf::sanitize_label_name;
 use crate::reticulate::validator::is_relative_typ_link;
@@ -14,17 +14,33 @@ use std::path::{Path, PathBuf};
 pub struct LinkTransformer {
     output_format: OutputFormat,
     spine: Option<Vec<PathBuf>>,
+    flatten: bool,
+    page_refs: HashMap<String, PageRef>,
 }
 
 impl LinkTransformer {
     /// Create a new LinkTransformer for the specified output format.
+    ///
+    /// EPUB links are always flattened, since every chapter lives next to the
+    /// package document regardless of its source directory.
     pub fn new(format: OutputFormat) -> Self {
         Self {
             output_format: format,
             spine: None,
+            flatten: format == OutputFormat::Epub,
+            page_refs: HashMap::new(),
         }
     }
 
+    /// Strip directories from rewritten links.
+    ///
+    /// Used when outputs are written to a single directory (flat layout), so a
+    /// link to `../chapters/ch1.typ` must resolve to `ch1.html`.
+    pub fn flatten(mut self, flatten: bool) -> Self {
+        self.flatten = flatten || self.output_format == OutputFormat::Epub;
+        self
+    }
+
     /// Set the spine for merged PDF compilation.
     ///
     /// The spine defines the ordered list of files in a merged PDF, which is used
@@ -34,10 +50,21 @@ impl LinkTransformer {
         self
     }
 
+    /// Set the pages that references link to, by label.
+    ///
+    /// Used for HTML sites with a spine: every page is compiled on its own, so
+    /// `@about` can't resolve to the label of another page. Such references are
+    /// turned into links to the page, unless the source defines the label itself.
+    pub fn with_page_refs(mut self, page_refs: HashMap<String, PageRef>) -> Self {
+        self.page_refs = page_refs;
+        self
+    }
+
     /// Transform source code by processing all links.
     ///
     /// This is the main entry point that combines link extraction, transformation
-    /// computation, and application.
+    /// computation, and application. A page label on the document set rule
+    /// (`#set document(..) <label>`) is removed, since Typst can't parse it.
     ///
     /// # Arguments
     /// * `source` - The source code to transform
@@ -59,7 +86,44 @@ impl LinkTransformer {
         let links = parser::extract_links(&source_obj);
 
         // Compute transformations
-        let transformations = self.compute_transformations(&links, current_file)?;
+        let mut transformations = self.compute_transformations(&links, current_file)?;
+
+        let page_label = parser::find_page_label(&source_obj);
+        if let Some((_, range)) = &page_label {
+            transformations.push((
+                range.clone(),
+                LinkTransform::Replace {
+                    text: String::new(),
+                },
+            ));
+        }
+
+        // References to other pages become links to them
+        if !self.page_refs.is_empty() {
+            let mut defined = parser::extract_labels(&source_obj);
+            if let Some((label, _)) = &page_label {
+                defined.remove(label);
+            }
+            for reference in parser::extract_refs(&source_obj) {
+                let Some(page) = self.page_refs.get(&reference.label) else {
+                    continue;
+                };
+                if defined.contains(&reference.label) {
+                    continue;
+                }
+                let text = match &reference.supplement {
+                    Some(supplement) => {
+                        format!("#link({}){}", typst_string(&page.href), supplement)
+                    }
+                    None => format!(
+                        "#link({}, {})",
+                        typst_string(&page.href),
+                        typst_string(&page.title)
+                    ),
+                };
+                transformations.push((reference.byte_range, LinkTransform::Replace { text }));
+            }
+        }
 
         // Find code block ranges to protect from transformation
         let code_ranges = serializer::find_code_block_ranges(&source_obj);
@@ -122,13 +186,13 @@ impl LinkTransformer {
                     OutputFormat::Html => {
                         // HTML: convert .typ to .html
                         LinkTransform::ReplaceUrl {
-                            new_url: url.replace(TYP_EXT, HTML_EXT),
+                            new_url: self.target_url(url).replace(TYP_EXT, HTML_EXT),
                         }
                     }
                     OutputFormat::Epub => {
                         // EPUB: convert .typ to .xhtml
                         LinkTransform::ReplaceUrl {
-                            new_url: url.replace(TYP_EXT, XHTML_EXT),
+                            new_url: self.target_url(url).replace(TYP_EXT, XHTML_EXT),
                         }
                     }
                 }
@@ -142,6 +206,20 @@ impl LinkTransformer {
 
         Ok(transformations)
     }
+
+    /// Returns the link URL, with directories stripped if links are flattened.
+    ///
+    /// Links to siblings (`ch2.typ`, `./ch2.typ`) are returned unchanged.
+    fn target_url<'a>(&self, url: &'a str) -> &'a str {
+        let is_sibling = Path::new(url)
+            .parent()
+            .is_none_or(|parent| parent.as_os_str().is_empty() || parent == Path::new("."));
+        if self.flatten && !is_sibling {
+            extract_filename(url)
+        } else {
+            url
+        }
+    }
 }
 
 /// Build a map of filename stems to sanitized labels for merged PDF compilation
@@ -160,6 +238,11 @@ fn build_label_map(spine_files: &[PathBuf]) -> HashMap<String, String> {
     map
 }
 
+/// Quote text as a Typst string literal
+fn typst_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 /// Extract the filename from a path (handles both relative and absolute paths)
 fn extract_filename(path: &str) -> &str {
     Path::new(path)
@@ -274,6 +357,99 @@ mod tests {
         assert!(matches!(transforms[1].1, LinkTransform::KeepOriginal));
     }
 
+    #[test]
+    fn test_html_preserves_directories_by_default() {
+        let links = vec![make_link("../chapters/ch1.typ", "text", 0..10)];
+        let transformer = LinkTransformer::new(OutputFormat::Html);
+        let result = transformer
+            .compute_transformations(&links, Path::new("appendix/notes.typ"))
+            .unwrap();
+
+        match &result[0].1 {
+            LinkTransform::ReplaceUrl { new_url } => {
+                assert_eq!(new_url, "../chapters/ch1.html")
+            }
+            _ => panic!("Expected ReplaceUrl transform for .typ link"),
+        }
+    }
+
+    #[test]
+    fn test_html_flatten_strips_directories() {
+        let links = vec![
+            make_link("../chapters/ch1.typ", "text", 0..10),
+            make_link("ch2.typ", "text", 20..30),
+        ];
+        let transformer = LinkTransformer::new(OutputFormat::Html).flatten(true);
+        let result = transformer
+            .compute_transformations(&links, Path::new("appendix/notes.typ"))
+            .unwrap();
+
+        match (&result[0].1, &result[1].1) {
+            (
+                LinkTransform::ReplaceUrl { new_url: first },
+                LinkTransform::ReplaceUrl { new_url: second },
+            ) => {
+                assert_eq!(first, "ch1.html");
+                assert_eq!(second, "ch2.html");
+            }
+            _ => panic!("Expected ReplaceUrl transforms for .typ links"),
+        }
+    }
+
+    #[test]
+    fn test_epub_always_flattens() {
+        let links = vec![make_link("chapters/ch1.typ", "text", 0..10)];
+        let transformer = LinkTransformer::new(OutputFormat::Epub).flatten(false);
+        let result = transformer
+            .compute_transformations(&links, Path::new("intro.typ"))
+            .unwrap();
+
+        match &result[0].1 {
+            LinkTransform::ReplaceUrl { new_url } => assert_eq!(new_url, "ch1.xhtml"),
+            _ => panic!("Expected ReplaceUrl transform for .typ link"),
+        }
+    }
+
+    #[test]
+    fn test_page_label_is_removed() {
+        let source = "#set document(title: [About]) <about>\n\n= About";
+        let result = LinkTransformer::new(OutputFormat::Pdf)
+            .transform_source(source, Path::new("about.typ"), Path::new(""))
+            .unwrap();
+        assert_eq!(result, "#set document(title: [About])\n\n= About");
+    }
+
+    #[test]
+    fn test_refs_to_pages_become_links() {
+        let page_refs = HashMap::from([(
+            "about".to_string(),
+            PageRef {
+                href: "../about.html".to_string(),
+                title: "About \"us\"".to_string(),
+            },
+        )]);
+        let transformer = LinkTransformer::new(OutputFormat::Html).with_page_refs(page_refs);
+
+        let result = transformer
+            .transform_source(
+                "See @about and @about[here].",
+                Path::new("posts/a.typ"),
+                Path::new(""),
+            )
+            .unwrap();
+        assert_eq!(
+            result,
+            r#"See #link("../about.html", "About \"us\"") and #link("../about.html")[here]."#
+        );
+
+        // Labels defined in the page itself take precedence
+        let source = "= About <about>\n\nSee @about.";
+        let result = transformer
+            .transform_source(source, Path::new("posts/a.typ"), Path::new(""))
+            .unwrap();
+        assert_eq!(result, source);
+    }
+
     #[test]
     fn test_sanitize_label_name() {
         assert_eq!(sanitize_label_name("chapter 01"), "chapter_01");
@@ -17,6 +17,32 @@ pub struct LinkInfo {
     pub byte_range: Range<usize>,
 }
 
+/// Information about a reference (`@label` or `@label[supplement]`) extracted from the AST
+#[derive(Debug, Clone)]
+pub struct RefInfo {
+    /// The referenced label (e.g. "about" for `@about`)
+    pub label: String,
+
+    /// Source text of the supplement, including its brackets
+    pub supplement: Option<String>,
+
+    /// Byte range in the source text
+    pub byte_range: Range<usize>,
+}
+
+/// A page that references can link to.
+///
+/// Pages of an HTML site are compiled on their own, so a reference to another
+/// page's label is turned into a link to the page instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRef {
+    /// URL of the page, relative to the referencing page
+    pub href: String,
+
+    /// Title of the page, used as link text for references without a supplement
+    pub title: String,
+}
+
 /// Link transformation operation
 #[derive(Debug, Clone)]
 pub enum LinkTransform {
@@ -29,6 +55,9 @@ pub enum LinkTransform {
     /// Replace URL with label
     ReplaceUrlWithLabel { new_label: String },
 
+    /// Replace the whole range with new source text
+    Replace { text: String },
+
     /// Keep original (no transformation)
     KeepOriginal,
 }
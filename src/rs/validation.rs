@@ -1,4 +1,6 @@
-use crate::config::{EpubConfig, EpubSpine, HtmlConfig, HtmlSpine, PdfConfig, PdfSpine};
+use crate::config::{
+    EpubConfig, EpubSpine, HtmlConfig, HtmlFeed, HtmlSpine, PdfConfig, PdfSpine, build_glob_set,
+};
 use crate::manifest_version::ManifestVersion;
 use crate::{Result, RheoConfig, RheoError};
 use tracing::warn;
@@ -28,6 +30,16 @@ impl ValidateConfig for RheoConfig {
             );
         }
 
+        build_glob_set("exclude", &self.exclude)?;
+        build_glob_set("drafts", &self.drafts)?;
+
+        if self.inputs.contains_key(crate::world::TARGET_INPUT) {
+            return Err(RheoError::project_config(format!(
+                "input '{}' is set by rheo and can't be configured",
+                crate::world::TARGET_INPUT
+            )));
+        }
+
         // Delegate to existing validation
         self.pdf.validate()?;
         self.html.validate()?;
@@ -51,6 +63,29 @@ impl ValidateConfig for HtmlConfig {
         if let Some(spine) = &self.spine {
             spine.validate()?;
         }
+        build_glob_set("html.assets", &self.assets)?;
+        for layout in &self.layouts {
+            build_glob_set("html.layouts.files", &layout.files)?;
+        }
+
+        // Sitemap URLs must be absolute
+        if let Some(base_url) = &self.base_url
+            && !(base_url.starts_with("https://") || base_url.starts_with("http://"))
+        {
+            return Err(RheoError::project_config(format!(
+                "html.base_url must be an absolute http(s) URL, got '{}'",
+                base_url
+            )));
+        }
+
+        if let Some(feed) = &self.feed {
+            feed.validate()?;
+            if self.base_url.is_none() {
+                return Err(RheoError::project_config(
+                    "html.base_url is required when html.feed is configured",
+                ));
+            }
+        }
         // Stylesheet and font paths are validated at usage time
         Ok(())
     }
@@ -66,6 +101,9 @@ impl ValidateConfig for EpubConfig {
 }
 
 /// Validate glob patterns in a vertebrae list.
+///
+/// Vertebrae are expanded against the file system with the `glob` crate, unlike
+/// the other patterns in rheo.toml (see [`build_glob_set`]).
 fn validate_vertebrae(vertebrae: &[String]) -> Result<()> {
     for pattern in vertebrae {
         glob::Pattern::new(pattern).map_err(|e| {
@@ -106,6 +144,18 @@ impl ValidateConfig for HtmlSpine {
     }
 }
 
+impl ValidateConfig for HtmlFeed {
+    fn validate(&self) -> Result<()> {
+        build_glob_set("html.feed.entries", &self.entries)?;
+        if self.entries.is_empty() {
+            return Err(RheoError::project_config(
+                "html.feed.entries must list at least one glob pattern",
+            ));
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,6 +283,7 @@ mod tests {
         let config = EpubConfig {
             identifier: None,
             date: None,
+            copy_fonts: false,
             spine: Some(spine),
         };
         assert!(config.validate().is_ok());
@@ -252,11 +303,97 @@ mod tests {
         let config = HtmlConfig {
             stylesheets: vec!["style.css".to_string()],
             fonts: vec![],
-            spine: None,
+            assets: vec!["img/**".to_string()],
+            ..HtmlConfig::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_html_config_validate_base_url() {
+        let config = HtmlConfig {
+            base_url: Some("https://example.com".to_string()),
+            ..HtmlConfig::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let config = HtmlConfig {
+            base_url: Some("example.com".to_string()),
+            ..HtmlConfig::default()
+        };
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("html.base_url must be an absolute")
+        );
+    }
+
+    #[test]
+    fn test_html_feed_requires_base_url() {
+        let feed = HtmlFeed {
+            title: None,
+            author: None,
+            entries: vec!["posts/*.typ".to_string()],
+            limit: 20,
+        };
+        let config = HtmlConfig {
+            feed: Some(feed.clone()),
+            ..HtmlConfig::default()
+        };
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("html.base_url is required")
+        );
+
+        let config = HtmlConfig {
+            base_url: Some("https://example.com".to_string()),
+            feed: Some(feed),
+            ..HtmlConfig::default()
         };
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_html_feed_validate_entries() {
+        let feed = HtmlFeed {
+            title: None,
+            author: None,
+            entries: vec![],
+            limit: 20,
+        };
+        assert!(feed.validate().is_err());
+    }
+
+    #[test]
+    fn test_html_config_validate_invalid_assets() {
+        let config = HtmlConfig {
+            assets: vec!["[invalid".to_string()],
+            ..HtmlConfig::default()
+        };
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("invalid glob pattern")
+        );
+
+        // Validated with the same syntax the patterns are matched with
+        let config = HtmlConfig {
+            assets: vec!["{img,css".to_string()],
+            ..HtmlConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_rheo_config_validates_with_matching_version() {
         let toml = format!("version = \"{}\"", env!("CARGO_PKG_VERSION"));
@@ -265,6 +402,16 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_rheo_config_rejects_target_input() {
+        let toml = format!(
+            "version = \"{}\"\n[inputs]\nrheo-target = \"pdf\"",
+            env!("CARGO_PKG_VERSION")
+        );
+        let config: RheoConfig = toml::from_str(&toml).unwrap();
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_rheo_config_warns_on_newer_version() {
         // Create config with version 99.0.0 (newer than current)
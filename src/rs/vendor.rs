@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tracing::{debug, info, warn};
+use typst::layout::PagedDocument;
+use typst::syntax::package::PackageSpec;
+use typst_html::HtmlDocument;
+use walkdir::WalkDir;
+
+use crate::project::ProjectConfig;
+use crate::world::{FormatWorlds, RHEO_NAMESPACE};
+use crate::{OutputFormat, Result, RheoError};
+
+/// Copy every Typst package a project imports into its packages directory.
+///
+/// The project's files are compiled for each configured format to find the
+/// imported packages, including packages imported by other packages. Packages
+/// that are already vendored are kept as they are, and packages built into rheo
+/// (`@rheo/...`) are skipped.
+///
+/// # Arguments
+/// * `project` - Project with a configured `packages_dir`
+///
+/// # Returns
+/// The number of newly vendored packages
+///
+/// # Errors
+/// Returns an error if no packages directory is configured, or if a package
+/// can't be prepared or copied
+pub fn vendor_packages(project: &ProjectConfig) -> Result<usize> {
+    let settings = project.world_settings()?;
+    let packages_dir = settings.packages_dir.clone().ok_or_else(|| {
+        RheoError::project_config("no packages directory configured; set packages_dir in rheo.toml")
+    })?;
+    let main = project
+        .typ_files
+        .first()
+        .ok_or_else(|| RheoError::project_config("no .typ files found in project"))?;
+    let root = project
+        .config
+        .resolve_content_dir(&project.root)
+        .unwrap_or_else(|| project.root.clone());
+    let mut worlds = FormatWorlds::new(&root, main, &settings)?;
+
+    // Imports may depend on the output format, so every format is compiled
+    let mut packages: BTreeMap<String, PackageSpec> = BTreeMap::new();
+    for &format in &project.config.formats {
+        let world = worlds.get(Some(format));
+        for file in &project.typ_files {
+            world.set_main(file)?;
+            world.reset();
+            let errors = match format {
+                OutputFormat::Pdf => typst::compile::<PagedDocument>(world).output.err(),
+                OutputFormat::Html | OutputFormat::Epub => {
+                    typst::compile::<HtmlDocument>(world).output.err()
+                }
+            };
+            if let Some(error) = errors.as_ref().and_then(|errors| errors.first()) {
+                warn!(file = %file.display(), error = %error.message, "compilation failed, imported packages may be incomplete");
+            }
+            for spec in world.packages() {
+                packages.insert(spec.to_string(), spec);
+            }
+        }
+    }
+    debug!(packages = packages.len(), "found imported packages");
+
+    let world = worlds.get(None);
+    let mut vendored = 0;
+    for (name, spec) in &packages {
+        if spec.namespace == RHEO_NAMESPACE {
+            debug!(package = %name, "skipping package built into rheo");
+            continue;
+        }
+        let dest = packages_dir
+            .join(spec.namespace.as_str())
+            .join(spec.name.as_str())
+            .join(spec.version.to_string());
+        if dest.exists() {
+            debug!(package = %name, "package already vendored");
+            continue;
+        }
+        let source = world.prepare_package(spec).map_err(|e| {
+            RheoError::project_config(format!("failed to prepare package {}: {}", name, e))
+        })?;
+        copy_package(&source, &dest)?;
+        info!(package = %name, dest = %dest.display(), "vendored package");
+        vendored += 1;
+    }
+    Ok(vendored)
+}
+
+/// Copy a package directory, so that an interrupted copy never leaves a
+/// partial package behind.
+fn copy_package(source: &Path, dest: &Path) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| RheoError::io(e, format!("creating {}", parent.display())))?;
+    }
+    let file_name = dest.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = dest.with_file_name(format!(".{}.tmp", file_name));
+    if tmp.exists() {
+        fs::remove_dir_all(&tmp)
+            .map_err(|e| RheoError::io(e, format!("removing {}", tmp.display())))?;
+    }
+
+    for entry in WalkDir::new(source) {
+        let entry = entry.map_err(|e| {
+            RheoError::path(source, format!("failed to read package directory: {}", e))
+        })?;
+        let rel = entry.path().strip_prefix(source).unwrap_or(entry.path());
+        let target = tmp.join(rel);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)
+                .map_err(|e| RheoError::io(e, format!("creating {}", target.display())))?;
+        } else {
+            fs::copy(entry.path(), &target).map_err(|e| {
+                RheoError::io(
+                    e,
+                    format!("copying {} to {}", entry.path().display(), target.display()),
+                )
+            })?;
+        }
+    }
+
+    fs::rename(&tmp, dest)
+        .map_err(|e| RheoError::io(e, format!("moving package to {}", dest.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{RheoWorld, WorldSettings};
+    use tempfile::TempDir;
+
+    /// Write a minimal local package that imports nothing.
+    fn write_package(dir: &Path) {
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        fs::write(
+            dir.join("typst.toml"),
+            "[package]\nname = \"brand\"\nversion = \"0.1.0\"\nentrypoint = \"lib/brand.typ\"\n",
+        )
+        .unwrap();
+        fs::write(dir.join("lib").join("brand.typ"), "#let accent = red\n").unwrap();
+    }
+
+    #[test]
+    fn test_vendor_keeps_vendored_packages() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        fs::write(
+            root.join("rheo.toml"),
+            "version = \"0.1.2\"\nformats = [\"pdf\"]\npackages_dir = \"vendor\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("a.typ"),
+            "#import \"@local/brand:0.1.0\": accent\n#import \"@rheo/rheo:0.1.0\": lemma\n#text(fill: accent)[A]\n",
+        )
+        .unwrap();
+        let mut project = ProjectConfig::from_path(&root, None, false).unwrap();
+
+        // Packages in the packages directory are kept as they are
+        let dest = root.join("vendor/local/brand/0.1.0");
+        write_package(&dest);
+        assert_eq!(vendor_packages(&project).unwrap(), 0);
+        assert!(dest.join("lib/brand.typ").is_file());
+        // Built-in packages are never vendored
+        assert!(!root.join("vendor/rheo").exists());
+
+        project.config.packages_dir = None;
+        assert!(vendor_packages(&project).is_err());
+    }
+
+    #[test]
+    fn test_offline_packages() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        fs::write(root.join("a.typ"), "= A\n").unwrap();
+        write_package(&root.join("vendor/preview/brand/0.1.0"));
+        let settings = WorldSettings {
+            packages_dir: Some(root.join("vendor")),
+            offline: true,
+            ..WorldSettings::default()
+        };
+        let world = RheoWorld::new(&root, &root.join("a.typ"), None)
+            .unwrap()
+            .with_settings(&settings);
+
+        let vendored: PackageSpec = "@preview/brand:0.1.0".parse().unwrap();
+        assert_eq!(
+            world.prepare_package(&vendored).unwrap(),
+            root.join("vendor/preview/brand/0.1.0")
+        );
+
+        // Other packages are never downloaded
+        let missing: PackageSpec = "@preview/rheo-missing-package:0.0.1".parse().unwrap();
+        let error = world.prepare_package(&missing).unwrap_err().to_string();
+        assert!(error.contains("not available offline"), "{}", error);
+    }
+
+    #[test]
+    fn test_copy_package() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("source");
+        write_package(&source);
+        let dest = temp.path().join("vendor/local/brand/0.1.0");
+
+        copy_package(&source, &dest).unwrap();
+        assert!(dest.join("typst.toml").is_file());
+        assert!(dest.join("lib/brand.typ").is_file());
+        assert!(!dest.with_file_name(".0.1.0.tmp").exists());
+    }
+}
@@ -2,7 +2,10 @@ use crate::{
     Result,
     project::{ProjectConfig, ProjectMode},
 };
-use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use notify::event::ModifyKind;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::channel;
 use std::time::Duration;
@@ -11,8 +14,13 @@ use tracing::{debug, info, warn};
 /// Event indicating files have changed and compilation should be triggered
 #[derive(Debug)]
 pub enum WatchEvent {
-    /// Source files or assets changed, trigger recompilation
-    FilesChanged,
+    /// Source files or assets changed, trigger recompilation of the outputs that
+    /// depend on the given paths
+    FilesChanged(Vec<PathBuf>),
+    /// Source files were created, removed or renamed, rediscover project files.
+    /// Carries the changed paths for an incremental rebuild in case rediscovery
+    /// finds no added or removed files.
+    FilesAddedOrRemoved(Vec<PathBuf>),
     /// Config file changed, need to reload ProjectConfig
     ConfigChanged,
 }
@@ -20,21 +28,34 @@ pub enum WatchEvent {
 /// Watch project files for changes and trigger recompilation
 ///
 /// This function sets up file system watching for:
-/// - All .typ files in the project
-/// - Asset files (style.css)
+/// - All files in the project directory (sources, images, data, assets)
 /// - Project configuration (rheo.toml)
+/// - Layout templates (`[[html.layouts]]`), also outside the project directory
+///
+/// Changed paths are passed on as they are; which outputs depend on them is
+/// decided by the compilation (see `IncrementalState::needs_rebuild`).
+///
+/// In directory mode, created, removed and renamed .typ files (or directories
+/// containing them) are reported as `WatchEvent::FilesAddedOrRemoved`. Saving
+/// by renaming a temporary file over an existing .typ file (vim, JetBrains IDEs)
+/// is a regular change.
 ///
 /// Changes are debounced with a 1-second delay to avoid rapid rebuilds during editing.
 ///
 /// # Arguments
-/// * `project` - Project configuration with source files
+/// * `project` - Project configuration with source files. It is only borrowed while
+///   filtering events, so the callback may update it (e.g. after rediscovery).
 /// * `build_dir` - Canonicalized build directory path to exclude from watching
 /// * `callback` - Function called when files change, receives WatchEvent
 ///
 /// # Returns
 /// * `Ok(())` when watching stops gracefully (e.g., Ctrl+C)
 /// * `Err` if watcher setup fails
-pub fn watch_project<F>(project: &ProjectConfig, build_dir: &Path, mut callback: F) -> Result<()>
+pub fn watch_project<F>(
+    project: &RefCell<ProjectConfig>,
+    build_dir: &Path,
+    mut callback: F,
+) -> Result<()>
 where
     F: FnMut(WatchEvent) -> Result<()>,
 {
@@ -48,10 +69,11 @@ where
     .map_err(|e| crate::RheoError::file_watcher(e, "creating file watcher"))?;
 
     // Watch based on project mode
-    match project.mode {
+    let borrowed = project.borrow();
+    match borrowed.mode {
         ProjectMode::SingleFile => {
             // Watch only the single file's parent directory (non-recursive)
-            let file_to_watch = &project.typ_files[0];
+            let file_to_watch = &borrowed.typ_files[0];
             let watch_dir = file_to_watch
                 .parent()
                 .ok_or_else(|| crate::RheoError::project_config("file has no parent directory"))?;
@@ -63,19 +85,36 @@ where
         }
         ProjectMode::Directory => {
             // Existing behavior: recursive watch of project root
-            info!(path = %project.root.display(), "watching project directory");
+            info!(path = %borrowed.root.display(), "watching project directory");
             watcher
-                .watch(&project.root, RecursiveMode::Recursive)
+                .watch(&borrowed.root, RecursiveMode::Recursive)
                 .map_err(|e| crate::RheoError::file_watcher(e, "watching project directory"))?;
         }
     }
+    // Layout templates are not dependencies of the compiled documents, so the
+    // ones outside the watched directory are watched separately
+    for template in borrowed.config.resolve_layout_templates(&borrowed.root) {
+        let inside = match borrowed.mode {
+            ProjectMode::SingleFile => template.parent() == borrowed.typ_files[0].parent(),
+            ProjectMode::Directory => template.starts_with(&borrowed.root),
+        };
+        if !inside && template.is_file() {
+            debug!(template = %template.display(), "watching layout template");
+            watcher
+                .watch(&template, RecursiveMode::NonRecursive)
+                .map_err(|e| crate::RheoError::file_watcher(e, "watching layout template"))?;
+        }
+    }
+    drop(borrowed);
 
     // Debounce logic: collect events for 1 second before triggering recompilation
     // This prevents excessive recompilation when editors save multiple files rapidly
     // or when a single edit triggers multiple filesystem events
     let debounce_duration = Duration::from_secs(1);
     let mut last_event_time = std::time::Instant::now();
-    let mut pending_changes = false; // True if any .typ files changed
+    let mut pending_changes = false; // True if any project files changed
+    let mut changed_paths = BTreeSet::new(); // Paths changed since the last rebuild
+    let mut files_added_or_removed = false; // True if .typ files were created/removed/renamed
     let mut config_changed = false; // True if rheo.toml changed (requires full reload)
 
     info!("watching for changes (press Ctrl+C to stop)");
@@ -90,16 +129,27 @@ where
                         // Ignore Access events (file opens/reads) - only care about modifications
                         // The Typst compiler opens source files during compilation, which would
                         // trigger infinite recompilation loops if we treated Access as a change
-                        if matches!(event.kind, notify::EventKind::Access(_)) {
+                        if matches!(event.kind, EventKind::Access(_)) {
                             continue;
                         }
 
-                        // Filter events to only relevant files (.typ files, rheo.toml, assets)
+                        // Filter out events of the build directory and ignored files
+                        let borrowed = project.borrow();
+                        // Created, removed or renamed .typ files (or directories) require
+                        // rediscovering the project files
+                        let added_or_removed = adds_or_removes_files(&event, &borrowed, build_dir);
                         let paths: Vec<PathBuf> = event
                             .paths
                             .into_iter()
-                            .filter(|p| is_relevant_path(p, project, build_dir))
+                            .filter(|p| is_relevant_path(p, &borrowed, build_dir))
                             .collect();
+                        drop(borrowed);
+
+                        if added_or_removed {
+                            debug!("detected added or removed files");
+                            last_event_time = std::time::Instant::now();
+                            files_added_or_removed = true;
+                        }
 
                         if !paths.is_empty() {
                             debug!(?paths, "detected file changes");
@@ -118,6 +168,9 @@ where
                             } else {
                                 pending_changes = true;
                             }
+                            // Canonical paths match the dependencies recorded by the World
+                            changed_paths
+                                .extend(paths.into_iter().map(|p| p.canonicalize().unwrap_or(p)));
                         }
                     }
                     Err(e) => {
@@ -128,14 +181,20 @@ where
             Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
                 // No new events received in last 100ms
                 // Check if we have pending changes and debounce period has elapsed
-                if pending_changes || config_changed {
+                if pending_changes || files_added_or_removed || config_changed {
                     let elapsed = last_event_time.elapsed();
                     if elapsed >= debounce_duration {
                         // Debounce period elapsed - trigger recompilation
                         let event = if config_changed {
                             WatchEvent::ConfigChanged
+                        } else if files_added_or_removed {
+                            WatchEvent::FilesAddedOrRemoved(
+                                std::mem::take(&mut changed_paths).into_iter().collect(),
+                            )
                         } else {
-                            WatchEvent::FilesChanged
+                            WatchEvent::FilesChanged(
+                                std::mem::take(&mut changed_paths).into_iter().collect(),
+                            )
                         };
 
                         if let Err(e) = callback(event) {
@@ -144,7 +203,9 @@ where
 
                         // Reset flags for next batch of changes
                         pending_changes = false;
+                        files_added_or_removed = false;
                         config_changed = false;
+                        changed_paths.clear();
                     }
                 }
             }
@@ -159,64 +220,214 @@ where
     Ok(())
 }
 
+/// Check if a path is under the (canonicalized) build directory
+fn is_in_build_dir(path: &Path, build_dir: &Path) -> bool {
+    // Try canonicalized comparison first (handles symlinks and relative paths)
+    match path.canonicalize() {
+        Ok(canonical_path) => canonical_path.starts_with(build_dir),
+        // Fallback: If canonicalize fails (file doesn't exist yet or was removed), check
+        // prefix match. This handles cases where notify fires events for paths being created
+        Err(_) => path.starts_with(build_dir),
+    }
+}
+
+/// Check if an event creates, removes or renames files
+fn is_structural_event(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+    )
+}
+
+/// Check if an event adds or removes project files, which requires rediscovering them
+fn adds_or_removes_files(event: &notify::Event, project: &ProjectConfig, build_dir: &Path) -> bool {
+    project.mode == ProjectMode::Directory
+        && is_structural_event(&event.kind)
+        && event.paths.iter().any(|p| {
+            !is_in_build_dir(p, build_dir)
+                && !project.filter.is_ignored_in_project(p)
+                && affects_typ_files(p, project)
+        })
+}
+
+/// Check if a created, removed or renamed path can change the project's .typ files
+///
+/// This is the case for .typ files that appeared or disappeared, i.e. that are
+/// project files before the event but not after it or vice versa, and for
+/// directories, which may contain (or have contained) .typ files. A .typ file
+/// replaced by renaming another file over it is still a project file.
+fn affects_typ_files(path: &Path, project: &ProjectConfig) -> bool {
+    if path.extension().and_then(|e| e.to_str()) == Some("typ") {
+        let known = project.typ_files.iter().any(|file| file == path);
+        return known != path.is_file();
+    }
+    path.is_dir() || (!path.exists() && project.typ_files.iter().any(|file| file.starts_with(path)))
+}
+
 /// Check if a path is relevant for triggering recompilation
+///
+/// Any file can be a dependency (images, bibliographies, data files, excluded
+/// modules), so every change outside the build directory is relevant, except
+/// for ignored and hidden paths in directory mode (e.g. node_modules/, .git/).
 fn is_relevant_path(path: &Path, project: &ProjectConfig, build_dir: &Path) -> bool {
     // CRITICAL: Exclude all paths under the build directory to prevent infinite loops
-    // Try canonicalized comparison first (handles symlinks and relative paths)
-    if let Ok(canonical_path) = path.canonicalize() {
-        if canonical_path.starts_with(build_dir) {
-            return false;
-        }
-    }
-    // Fallback: If canonicalize fails (file doesn't exist yet), check prefix match
-    // This handles cases where notify fires events for paths being created
-    else if path.starts_with(build_dir) {
+    if is_in_build_dir(path, build_dir) {
         return false;
     }
 
+    // Layout templates are relevant wherever they are
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if project
+        .config
+        .resolve_layout_templates(&project.root)
+        .contains(&canonical)
+    {
+        return true;
+    }
+
     match project.mode {
-        ProjectMode::SingleFile => {
-            // Only the exact file is relevant (and assets in parent directory)
-            let target_file = &project.typ_files[0];
+        // Only the file's directory is watched (non-recursively)
+        ProjectMode::SingleFile => true,
+        ProjectMode::Directory => !project.filter.is_ignored_in_project(path),
+    }
+}
 
-            // Check if it's the specific .typ file
-            if path == target_file {
-                return true;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
 
-            // Check if it's style.css in the same directory
-            if path.file_name().and_then(|n| n.to_str()) == Some("style.css")
-                && let Some(parent) = path.parent()
-                && parent == project.root
-            {
-                return true;
-            }
+    #[test]
+    fn test_every_non_ignored_path_is_relevant() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        fs::write(root.join("rheo.toml"), "version = \"0.1.2\"\n").unwrap();
+        fs::write(root.join(".gitignore"), "node_modules/\n").unwrap();
+        fs::write(root.join("index.typ"), "= Index\n").unwrap();
+        let project = ProjectConfig::from_path(&root, None, false).unwrap();
+        let build_dir = root.join("build");
 
-            false
+        for file in [
+            "index.typ",
+            "img/logo.png",
+            "refs.bib",
+            "data.csv",
+            "layout.html",
+        ] {
+            assert!(
+                is_relevant_path(&root.join(file), &project, &build_dir),
+                "{}",
+                file
+            );
         }
-        ProjectMode::Directory => {
-            // Existing logic for directory mode
-            // Check if it's a .typ file
-            if path.extension().and_then(|e| e.to_str()) == Some("typ") {
-                return true;
-            }
+        assert!(!is_relevant_path(
+            &root.join("build/index.html"),
+            &project,
+            &build_dir
+        ));
+        assert!(!is_relevant_path(
+            &root.join("node_modules/a.js"),
+            &project,
+            &build_dir
+        ));
+        assert!(!is_relevant_path(
+            &root.join(".hidden/a.png"),
+            &project,
+            &build_dir
+        ));
+    }
 
-            // Check if it's rheo.toml
-            if path.file_name().and_then(|n| n.to_str()) == Some("rheo.toml") {
-                return true;
-            }
+    #[test]
+    fn test_ignored_paths_outside_content_dir_are_not_relevant() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        fs::write(
+            root.join("rheo.toml"),
+            "version = \"0.1.2\"\ncontent_dir = \"src\"\n",
+        )
+        .unwrap();
+        fs::write(root.join(".gitignore"), "node_modules/\n").unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/index.typ"), "= Index\n").unwrap();
+        let project = ProjectConfig::from_path(&root, None, false).unwrap();
+        let build_dir = root.join("build");
 
-            // Check if it's style.css
-            if path.file_name().and_then(|n| n.to_str()) == Some("style.css") {
-                // Only if it's in the project root
-                if let Some(parent) = path.parent()
-                    && parent == project.root
-                {
-                    return true;
-                }
-            }
+        for file in [".git/index", "node_modules/x.js"] {
+            assert!(
+                !is_relevant_path(&root.join(file), &project, &build_dir),
+                "{}",
+                file
+            );
+        }
+        for file in ["src/index.typ", "img/logo.png"] {
+            assert!(
+                is_relevant_path(&root.join(file), &project, &build_dir),
+                "{}",
+                file
+            );
+        }
+    }
 
-            false
+    #[test]
+    fn test_rename_over_typ_file_is_a_change() {
+        use notify::event::{CreateKind, RenameMode};
+
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        fs::write(root.join("rheo.toml"), "version = \"0.1.2\"\n").unwrap();
+        fs::write(root.join("index.typ"), "= Index\n").unwrap();
+        let project = ProjectConfig::from_path(&root, None, false).unwrap();
+        let build_dir = root.join("build");
+
+        // Save by writing a temporary file and renaming it over the original
+        let index = root.join("index.typ");
+        let tmp = root.join("index.typ___jb_tmp___");
+        fs::write(&tmp, "= Changed\n").unwrap();
+        fs::rename(&tmp, &index).unwrap();
+        let rename = notify::Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+            .add_path(tmp)
+            .add_path(index.clone());
+        let create =
+            notify::Event::new(EventKind::Create(CreateKind::File)).add_path(index.clone());
+        for event in [rename, create] {
+            assert!(!adds_or_removes_files(&event, &project, &build_dir));
         }
+        // ...so the rebuild is incremental for the saved file
+        assert!(is_relevant_path(&index, &project, &build_dir));
+
+        // New and removed .typ files still require rediscovery
+        let new = root.join("new.typ");
+        fs::write(&new, "= New\n").unwrap();
+        let create = notify::Event::new(EventKind::Create(CreateKind::File)).add_path(new);
+        assert!(adds_or_removes_files(&create, &project, &build_dir));
+        fs::remove_file(&index).unwrap();
+        let remove =
+            notify::Event::new(EventKind::Remove(notify::event::RemoveKind::File)).add_path(index);
+        assert!(adds_or_removes_files(&remove, &project, &build_dir));
+    }
+
+    #[test]
+    fn test_layout_templates_are_relevant() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        fs::write(
+            root.join("rheo.toml"),
+            "version = \"0.1.2\"\n[[html.layouts]]\ntemplate = \".layouts/page.html\"\n",
+        )
+        .unwrap();
+        fs::create_dir(root.join(".layouts")).unwrap();
+        fs::write(root.join(".layouts/page.html"), "{{content}}").unwrap();
+        fs::write(root.join("index.typ"), "= Index\n").unwrap();
+        let project = ProjectConfig::from_path(&root, None, false).unwrap();
+        let build_dir = root.join("build");
+
+        // Hidden, but configured as a layout
+        let layout = root.join(".layouts/page.html");
+        assert!(is_relevant_path(&layout, &project, &build_dir));
+        let other = root.join(".layouts/other.html");
+        assert!(!is_relevant_path(&other, &project, &build_dir));
     }
 }
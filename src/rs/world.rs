@@ -1,41 +1,137 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use crate::config::OutputLayout;
+use crate::fonts::FontCache;
+use crate::postprocess::site_nav::SiteNav;
 use crate::{OutputFormat, Result, RheoError};
-use chrono::{Datelike, Local};
+use chrono::{DateTime, Datelike, Local, Utc};
 use codespan_reporting::files::{Error as CodespanError, Files};
+use lazy_static::lazy_static;
 use parking_lot::Mutex;
-use typst::diag::{FileError, FileResult};
+use typst::diag::{FileError, FileResult, PackageError, PackageResult, eco_format};
 use typst::foundations::{Bytes, Datetime, Dict, IntoValue};
+use typst::syntax::package::PackageSpec;
 use typst::syntax::{FileId, Lines, Source, VirtualPath};
 use typst::text::{Font, FontBook};
 use typst::utils::LazyHash;
 use typst::{Library, LibraryExt, World};
 use typst_kit::download::Downloader;
-use typst_kit::fonts::{FontSlot, Fonts};
 use typst_kit::package::PackageStorage;
 use typst_library::{Feature, Features};
 
+lazy_static! {
+    /// Package storage shared by all worlds of a build, so packages are
+    /// prepared (and downloaded) at most once.
+    static ref PACKAGE_STORAGE: Arc<PackageStorage> = Arc::new(PackageStorage::new(
+        None, // Use default cache directory
+        None, // Use default data directory
+        Downloader::new(concat!("rheo/", env!("CARGO_PKG_VERSION"))),
+    ));
+}
+
+/// Key of the `sys.inputs` entry holding the output format.
+pub const TARGET_INPUT: &str = "rheo-target";
+
+/// Namespace of the packages built into rheo, which are served from memory.
+pub const RHEO_NAMESPACE: &str = "rheo";
+
+/// Version of the built-in `@rheo/rheo` package (rheo's Typst helpers).
+pub const RHEO_PACKAGE_VERSION: &str = "0.1.0";
+
+/// Files of the built-in `@rheo/rheo` package, by path within the package.
+const RHEO_PACKAGE_FILES: &[(&str, &str)] = &[
+    ("typst.toml", include_str!("../typ/typst.toml")),
+    ("rheo.typ", include_str!("../typ/rheo.typ")),
+];
+
+/// Look up a file of a package built into rheo.
+///
+/// # Returns
+/// `None` if the file is not in the `rheo` namespace, otherwise the file's
+/// text or an error if the package or file doesn't exist
+fn builtin_package_file(id: FileId) -> Option<FileResult<&'static str>> {
+    let spec = id
+        .package()
+        .filter(|spec| spec.namespace == RHEO_NAMESPACE)?;
+    if spec.name != "rheo" || spec.version.to_string() != RHEO_PACKAGE_VERSION {
+        return Some(Err(FileError::Package(PackageError::NotFound(
+            spec.clone(),
+        ))));
+    }
+    let path = id.vpath().as_rootless_path();
+    let text = RHEO_PACKAGE_FILES
+        .iter()
+        .find(|(name, _)| Path::new(name) == path)
+        .map(|(_, text)| *text)
+        .ok_or_else(|| FileError::NotFound(id.vpath().as_rooted_path().to_path_buf()));
+    Some(text)
+}
+
 /// Build sys.inputs Dict for Typst compilation.
 ///
 /// This creates the dictionary that's accessible via `sys.inputs` in Typst code.
-/// For EPUB/HTML/PDF compilation, we pass `{"rheo-target": "epub"|"html"|"pdf"}`
-/// so user code can detect the output format using:
+/// It holds the custom inputs of the build, and for EPUB/HTML/PDF compilation
+/// `{"rheo-target": "epub"|"html"|"pdf"}` so user code can detect the output
+/// format using:
 /// `if "rheo-target" in sys.inputs { sys.inputs.rheo-target }`
-fn build_inputs(output_format: Option<OutputFormat>) -> Dict {
-    let mut dict = Dict::new();
+fn build_inputs(output_format: Option<OutputFormat>, inputs: &BTreeMap<String, String>) -> Dict {
+    let mut dict: Dict = inputs
+        .iter()
+        .map(|(key, value)| (key.as_str().into(), value.as_str().into_value()))
+        .collect();
     if let Some(format) = output_format {
         let format_str = match format {
             OutputFormat::Pdf => "pdf",
             OutputFormat::Html => "html",
             OutputFormat::Epub => "epub",
         };
-        dict.insert("rheo-target".into(), format_str.into_value());
+        dict.insert(TARGET_INPUT.into(), format_str.into_value());
     }
     dict
 }
 
+/// Build the standard library with the HTML feature enabled and sys.inputs for
+/// format detection and custom inputs.
+fn build_library(
+    output_format: Option<OutputFormat>,
+    inputs: &BTreeMap<String, String>,
+) -> Arc<LazyHash<Library>> {
+    let features: Features = [Feature::Html].into_iter().collect();
+    let library = Library::builder()
+        .with_features(features)
+        .with_inputs(build_inputs(output_format, inputs))
+        .build();
+    Arc::new(LazyHash::new(library))
+}
+
+/// Settings of the worlds of a build, from rheo.toml and command-line flags.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorldSettings {
+    /// Custom `sys.inputs` (besides the output format).
+    pub inputs: BTreeMap<String, String>,
+    /// Directories with project fonts, which take precedence over system fonts.
+    pub font_paths: Vec<PathBuf>,
+    /// Directory with project-local packages (`{namespace}/{name}/{version}`),
+    /// which take precedence over Typst's package directories.
+    pub packages_dir: Option<PathBuf>,
+    /// Whether to only use packages that are already on disk (never download).
+    pub offline: bool,
+    /// Whether to import the `@rheo/rheo` package and apply `rheo_template`
+    /// in every main file.
+    pub inject_template: bool,
+    /// Fixed date of the build (`SOURCE_DATE_EPOCH` or `[build] date`), used
+    /// instead of the current time so that rebuilds are byte-identical.
+    pub build_date: Option<DateTime<Utc>>,
+}
+
+/// File id of a virtual main file with the given name in the root directory.
+fn virtual_main(name: &str) -> FileId {
+    FileId::new(None, VirtualPath::new(name))
+}
+
 /// A simple World implementation for rheo compilation.
 pub struct RheoWorld {
     /// The root directory for resolving imports (document directory).
@@ -44,23 +140,32 @@ pub struct RheoWorld {
     /// The main file to compile.
     main: FileId,
 
+    /// In-memory text of the main file, if it is virtual (see [`RheoWorld::set_main_source`]).
+    main_source: Option<String>,
+
     /// Typst's standard library.
-    library: LazyHash<Library>,
+    library: Arc<LazyHash<Library>>,
 
-    /// Metadata about discovered fonts.
-    book: LazyHash<FontBook>,
+    /// Custom `sys.inputs` and font directories.
+    settings: Arc<WorldSettings>,
 
-    /// Locations of and storage for lazily loaded fonts.
-    fonts: Vec<FontSlot>,
+    /// Discovered fonts (shared between all worlds with the same font directories).
+    fonts: FontCache,
 
     /// Maps file ids to source files.
     slots: Mutex<HashMap<FileId, FileSlot>>,
 
     /// Package storage for downloading and caching packages.
-    package_storage: PackageStorage,
+    package_storage: Arc<PackageStorage>,
 
     /// Output format for link transformations (None = no transformation).
     output_format: Option<OutputFormat>,
+
+    /// Output layout, which determines whether transformed links keep directories.
+    layout: OutputLayout,
+
+    /// Site navigation of HTML builds with a spine, which references to pages link to.
+    site_nav: Option<Arc<SiteNav>>,
 }
 
 /// Holds the processed data for a file ID.
@@ -99,38 +204,116 @@ impl RheoWorld {
         })?;
         let main = FileId::new(None, main_vpath);
 
-        // Build library with HTML feature enabled and sys.inputs for format detection
-        let features: Features = [Feature::Html].into_iter().collect();
-        let inputs = build_inputs(output_format);
-        let library = Library::builder()
-            .with_features(features)
-            .with_inputs(inputs)
-            .build();
-
-        // Search for fonts using typst-kit
-        // Respect TYPST_IGNORE_SYSTEM_FONTS for test consistency
-        let include_system_fonts = std::env::var("TYPST_IGNORE_SYSTEM_FONTS").is_err();
-        let font_search = Fonts::searcher()
-            .include_system_fonts(include_system_fonts)
-            .search();
-
-        // Create package storage with default paths and downloader
-        let package_storage = PackageStorage::new(
-            None, // Use default cache directory
-            None, // Use default data directory
-            Downloader::new(concat!("rheo/", env!("CARGO_PKG_VERSION"))),
-        );
+        Ok(Self::with_main(root, main, output_format))
+    }
 
-        Ok(Self {
+    /// Create a new world whose main file is an in-memory source.
+    ///
+    /// # Arguments
+    /// * `root` - The root directory for resolving imports (document directory)
+    /// * `name` - File name of the virtual main file in the root (shown in diagnostics)
+    /// * `text` - Source text of the main file
+    /// * `output_format` - Output format for link transformations (None = no transformation)
+    pub fn from_source(
+        root: &Path,
+        name: &str,
+        text: impl Into<String>,
+        output_format: Option<OutputFormat>,
+    ) -> Result<Self> {
+        let root = root.canonicalize().map_err(|e| {
+            RheoError::path(
+                root,
+                format!("failed to canonicalize root directory: {}", e),
+            )
+        })?;
+        let mut world = Self::with_main(root, virtual_main(name), output_format);
+        world.main_source = Some(text.into());
+        Ok(world)
+    }
+
+    /// Create a world with a resolved root and main file, using the fonts and
+    /// package storage shared by all worlds of the build.
+    fn with_main(root: PathBuf, main: FileId, output_format: Option<OutputFormat>) -> Self {
+        Self {
             root,
             main,
-            library: LazyHash::new(library),
-            book: font_search.book.into(),
-            fonts: font_search.fonts,
+            main_source: None,
+            library: build_library(output_format, &BTreeMap::new()),
+            settings: Arc::default(),
+            fonts: FontCache::shared(),
             slots: Mutex::new(HashMap::new()),
-            package_storage,
+            package_storage: Arc::clone(&PACKAGE_STORAGE),
             output_format,
-        })
+            layout: OutputLayout::default(),
+            site_nav: None,
+        }
+    }
+
+    /// Create a world that shares everything with this world except the file cache.
+    ///
+    /// Used to compile several files of the same format in parallel: each job
+    /// gets its own fork, with its own main file and dependencies.
+    pub fn fork(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            main: self.main,
+            main_source: self.main_source.clone(),
+            library: Arc::clone(&self.library),
+            settings: Arc::clone(&self.settings),
+            fonts: self.fonts.clone(),
+            slots: Mutex::new(HashMap::new()),
+            package_storage: Arc::clone(&self.package_storage),
+            output_format: self.output_format,
+            layout: self.layout,
+            site_nav: self.site_nav.clone(),
+        }
+    }
+
+    /// Create a world for another output format that shares this world's fonts
+    /// and package storage.
+    ///
+    /// The new world has the same root, main file and layout, but its own
+    /// `sys.inputs` and link transformations for the format, and an empty file cache.
+    ///
+    /// # Arguments
+    /// * `output_format` - Output format for link transformations (None = no transformation)
+    pub fn for_format(&self, output_format: Option<OutputFormat>) -> Self {
+        Self {
+            root: self.root.clone(),
+            main: self.main,
+            main_source: self.main_source.clone(),
+            library: build_library(output_format, &self.settings.inputs),
+            settings: Arc::clone(&self.settings),
+            fonts: self.fonts.clone(),
+            slots: Mutex::new(HashMap::new()),
+            package_storage: Arc::clone(&self.package_storage),
+            output_format,
+            layout: self.layout,
+            site_nav: self.site_nav.clone(),
+        }
+    }
+
+    /// Apply build settings to compiled documents.
+    ///
+    /// Custom inputs are added to `sys.inputs` (the output format is still
+    /// available as `sys.inputs.rheo-target`), and fonts in the font directories
+    /// take precedence over system fonts.
+    ///
+    /// # Arguments
+    /// * `settings` - Custom inputs and font directories
+    pub fn with_settings(mut self, settings: &WorldSettings) -> Self {
+        if *self.settings == *settings {
+            return self;
+        }
+        if self.settings.inputs != settings.inputs {
+            self.library = build_library(self.output_format, &settings.inputs);
+        }
+        if self.settings.font_paths != settings.font_paths {
+            self.fonts = FontCache::with_font_paths(&settings.font_paths);
+        }
+        self.settings = Arc::new(settings.clone());
+        self.reset();
+        self
     }
 
     /// Reset the file cache for incremental compilation.
@@ -165,9 +348,54 @@ impl RheoWorld {
         })?;
 
         self.main = FileId::new(None, main_vpath);
+        self.main_source = None;
         Ok(())
     }
 
+    /// Change the main file to an in-memory source.
+    ///
+    /// The source is served under a virtual path in the root directory, so its
+    /// imports resolve like those of a file in the root, but nothing is written
+    /// to disk. Used for spine sources of merged PDFs and EPUB chapters.
+    ///
+    /// # Arguments
+    /// * `name` - File name of the virtual main file in the root (shown in diagnostics)
+    /// * `text` - Source text of the main file
+    pub fn set_main_source(&mut self, name: &str, text: impl Into<String>) {
+        self.main = virtual_main(name);
+        self.main_source = Some(text.into());
+        // Drop the cached text of a previous source with the same name
+        self.slots.lock().remove(&self.main);
+    }
+
+    /// Change the output layout used for link transformations.
+    ///
+    /// Sources are cached with their links already transformed, so the cache is
+    /// reset when the layout changes.
+    ///
+    /// # Arguments
+    /// * `layout` - Layout of the per-file outputs
+    pub fn set_layout(&mut self, layout: OutputLayout) {
+        if self.layout != layout {
+            self.layout = layout;
+            self.reset();
+        }
+    }
+
+    /// Change the site navigation that references to pages link to.
+    ///
+    /// Like [`RheoWorld::set_layout`], the source cache is reset when the
+    /// navigation changes.
+    ///
+    /// # Arguments
+    /// * `site_nav` - Site navigation of an HTML build with a spine
+    pub fn set_site_nav(&mut self, site_nav: Option<&SiteNav>) {
+        if self.site_nav.as_deref() != site_nav {
+            self.site_nav = site_nav.cloned().map(Arc::new);
+            self.reset();
+        }
+    }
+
     /// Transform links in source text based on output format.
     ///
     /// Applies AST-based link transformations:
@@ -175,6 +403,8 @@ impl RheoWorld {
     /// - EPUB: .typ → .xhtml
     /// - PDF: Removes .typ links (or converts to labels if spine is provided)
     ///
+    /// In HTML spine pages, references to other pages (e.g. `@about`) become links.
+    ///
     /// # Arguments
     /// * `text` - Source text to transform
     /// * `id` - File ID (for error reporting and path context)
@@ -185,7 +415,15 @@ impl RheoWorld {
     fn transform_links(&self, text: &str, id: FileId, format: &OutputFormat) -> FileResult<String> {
         use crate::reticulate::transformer::LinkTransformer;
 
-        let transformer = LinkTransformer::new(*format);
+        let mut transformer =
+            LinkTransformer::new(*format).flatten(self.layout == OutputLayout::Flat);
+        if let Some(nav) = &self.site_nav
+            && *format == OutputFormat::Html
+            && id.package().is_none()
+            && let Some(current) = nav.position(&self.root.join(id.vpath().as_rootless_path()))
+        {
+            transformer = transformer.with_page_refs(nav.page_refs(current));
+        }
         transformer
             .transform_source(text, id.vpath().as_rootless_path(), &self.root)
             .map_err(|e| FileError::Other(Some(e.to_string().into())))
@@ -206,9 +444,7 @@ impl RheoWorld {
         let buf;
         if let Some(spec) = id.package() {
             // Download and prepare the package if needed
-            buf = self
-                .package_storage
-                .prepare_package(spec, &mut PrintDownload::new(spec))?;
+            buf = self.prepare_package(spec)?;
             root = &buf;
         }
 
@@ -241,6 +477,51 @@ impl RheoWorld {
         Ok(path)
     }
 
+    /// Locate a package on disk, downloading it if needed.
+    ///
+    /// Packages in the project's packages directory take precedence. In offline
+    /// mode, only packages in the packages directory or in Typst's package
+    /// directories can be used. Packages built into rheo are never on disk.
+    ///
+    /// # Arguments
+    /// * `spec` - Package to locate
+    ///
+    /// # Returns
+    /// The root directory of the package
+    pub fn prepare_package(&self, spec: &PackageSpec) -> PackageResult<PathBuf> {
+        if spec.namespace == RHEO_NAMESPACE {
+            return Err(PackageError::Other(Some(eco_format!(
+                "{spec} is built into rheo and not stored on disk"
+            ))));
+        }
+        let subdir = format!("{}/{}/{}", spec.namespace, spec.name, spec.version);
+        if let Some(packages_dir) = &self.settings.packages_dir {
+            let dir = packages_dir.join(&subdir);
+            if dir.exists() {
+                return Ok(dir);
+            }
+        }
+
+        if !self.settings.offline {
+            return self
+                .package_storage
+                .prepare_package(spec, &mut PrintDownload::new(spec));
+        }
+        [
+            self.package_storage.package_path(),
+            self.package_storage.package_cache_path(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join(&subdir))
+        .find(|dir| dir.exists())
+        .ok_or_else(|| {
+            PackageError::Other(Some(eco_format!(
+                "{spec} is not available offline; vendor it with `rheo vendor` or build without --offline"
+            )))
+        })
+    }
+
     /// Look up the lines of a source file.
     ///
     /// This is used by the codespan-reporting integration to provide source
@@ -283,6 +564,95 @@ impl RheoWorld {
     pub fn root(&self) -> &Path {
         &self.root
     }
+
+    /// Fixed date of the build, if any (see [`WorldSettings::build_date`]).
+    pub fn build_date(&self) -> Option<DateTime<Utc>> {
+        self.settings.build_date
+    }
+
+    /// Local files (sources and binary files) read since the last reset.
+    ///
+    /// These are the dependencies of the last compilation, used for incremental
+    /// rebuilds in watch mode. Package files are left out, as packages don't change.
+    pub fn dependencies(&self) -> Vec<PathBuf> {
+        let ids: Vec<FileId> = self.slots.lock().keys().copied().collect();
+        ids.into_iter()
+            .filter(|id| id.package().is_none())
+            .filter(|id| *id != self.main || self.main_source.is_none())
+            .filter_map(|id| self.path_for_id(id).ok())
+            .collect()
+    }
+
+    /// Packages that files were loaded from since the last reset, sorted.
+    pub fn packages(&self) -> Vec<PackageSpec> {
+        let mut packages: Vec<PackageSpec> = self
+            .slots
+            .lock()
+            .keys()
+            .filter_map(|id| id.package().cloned())
+            .collect();
+        packages.sort_by_cached_key(|spec| spec.to_string());
+        packages.dedup();
+        packages
+    }
+
+    /// Binary files loaded through [`World::file`] since the last reset.
+    ///
+    /// This covers images, SVGs, fonts and data files that the compiled document
+    /// read. Results are sorted by virtual path so callers get a stable order.
+    pub fn loaded_files(&self) -> Vec<(FileId, Bytes)> {
+        let mut files: Vec<(FileId, Bytes)> = self
+            .slots
+            .lock()
+            .iter()
+            .filter_map(|(id, slot)| Some((*id, slot.file.clone()?)))
+            .collect();
+        files.sort_by_key(|(id, _)| {
+            (
+                id.package().map(|spec| spec.to_string()),
+                id.vpath().clone(),
+            )
+        });
+        files
+    }
+}
+
+/// Worlds for each output format, reused across compilations in watch mode.
+///
+/// `sys.inputs` and link transformations depend on the output format, so each
+/// format is compiled in its own world, exactly like a fresh build. Fonts and
+/// package storage are loaded once and shared between the worlds.
+pub struct FormatWorlds {
+    worlds: HashMap<Option<OutputFormat>, RheoWorld>,
+}
+
+impl FormatWorlds {
+    /// Create the worlds for compiling files under the given root.
+    ///
+    /// # Arguments
+    /// * `root` - The root directory for resolving imports (document directory)
+    /// * `main_file` - The initial main .typ file (updated per compilation)
+    /// * `settings` - Custom `sys.inputs` and font directories of every format
+    pub fn new(root: &Path, main_file: &Path, settings: &WorldSettings) -> Result<Self> {
+        let world = RheoWorld::new(root, main_file, None)?.with_settings(settings);
+        Ok(Self {
+            worlds: HashMap::from([(None, world)]),
+        })
+    }
+
+    /// Get the world for an output format, creating it on first use.
+    ///
+    /// # Arguments
+    /// * `output_format` - Output format for link transformations (None = no transformation)
+    pub fn get(&mut self, output_format: Option<OutputFormat>) -> &mut RheoWorld {
+        if !self.worlds.contains_key(&output_format) {
+            let world = self.worlds[&None].for_format(output_format);
+            self.worlds.insert(output_format, world);
+        }
+        self.worlds
+            .get_mut(&output_format)
+            .expect("world was just created")
+    }
 }
 
 impl World for RheoWorld {
@@ -291,7 +661,7 @@ impl World for RheoWorld {
     }
 
     fn book(&self) -> &LazyHash<FontBook> {
-        &self.book
+        self.fonts.book()
     }
 
     fn main(&self) -> FileId {
@@ -306,9 +676,17 @@ impl World for RheoWorld {
             return Ok(source.clone());
         }
 
-        // Load from file system
-        let path = self.path_for_id(id)?;
-        let mut text = fs::read_to_string(&path).map_err(|e| FileError::from_io(e, &path))?;
+        // Load from memory (virtual main file or built-in package) or the file system
+        let mut text = match &self.main_source {
+            Some(text) if id == self.main => text.clone(),
+            _ => match builtin_package_file(id) {
+                Some(text) => text?.to_string(),
+                None => {
+                    let path = self.path_for_id(id)?;
+                    fs::read_to_string(&path).map_err(|e| FileError::from_io(e, &path))?
+                }
+            },
+        };
 
         // Inject target() polyfill into ALL .typ files for EPUB compilation
         // This shadows the built-in target() to check sys.inputs.rheo-target first,
@@ -321,12 +699,11 @@ impl World for RheoWorld {
             ""
         };
 
-        // For the main file, also inject the rheo.typ template
-        if id == self.main {
-            let rheo_content = include_str!("../typ/rheo.typ");
+        // For the main file, also apply the rheo template if enabled
+        if id == self.main && self.settings.inject_template {
             let template_inject = format!(
-                "{}{}\n#show: rheo_template\n\n",
-                target_polyfill, rheo_content
+                "{}#import \"@{}/rheo:{}\": *\n#show: rheo_template\n\n",
+                target_polyfill, RHEO_NAMESPACE, RHEO_PACKAGE_VERSION
             );
             text = format!("{}{}", template_inject, text);
         } else if !target_polyfill.is_empty() {
@@ -358,11 +735,14 @@ impl World for RheoWorld {
             return Ok(file.clone());
         }
 
-        // Load from file system
-        let path = self.path_for_id(id)?;
-        let data = fs::read(&path).map_err(|e| FileError::from_io(e, &path))?;
-
-        let bytes = Bytes::new(data);
+        // Load from memory (built-in package) or the file system
+        let bytes = match builtin_package_file(id) {
+            Some(text) => Bytes::from_string(text?),
+            None => {
+                let path = self.path_for_id(id)?;
+                Bytes::new(fs::read(&path).map_err(|e| FileError::from_io(e, &path))?)
+            }
+        };
 
         // Cache the file
         self.slots.lock().entry(id).or_insert_with(|| FileSlot {
@@ -374,11 +754,15 @@ impl World for RheoWorld {
     }
 
     fn font(&self, index: usize) -> Option<Font> {
-        self.fonts.get(index)?.get()
+        self.fonts.font(index)
     }
 
     fn today(&self, offset: Option<i64>) -> Option<Datetime> {
-        let now = Local::now();
+        // The fixed build date (in UTC) or the current local time
+        let now = match self.settings.build_date {
+            Some(date) => date.naive_utc(),
+            None => Local::now().naive_local(),
+        };
 
         // The time with the specified UTC offset, or within the local time zone.
         let with_offset = match offset {
@@ -458,7 +842,7 @@ struct PrintDownload {
 }
 
 impl PrintDownload {
-    fn new(spec: &typst::syntax::package::PackageSpec) -> Self {
+    fn new(spec: &PackageSpec) -> Self {
         Self {
             package_name: format!("{}@{}", spec.name, spec.version),
         }
@@ -497,3 +881,46 @@ impl typst_kit::download::Progress for PrintDownload {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use typst::layout::PagedDocument;
+
+    /// Compile a main file with the given source to PDF in an empty project.
+    fn compile(source: &str, settings: &WorldSettings) -> bool {
+        let temp = TempDir::new().unwrap();
+        let main = temp.path().join("main.typ");
+        fs::write(&main, source).unwrap();
+        let world = RheoWorld::new(temp.path(), &main, Some(OutputFormat::Pdf))
+            .unwrap()
+            .with_settings(settings);
+        typst::compile::<PagedDocument>(&world).output.is_ok()
+    }
+
+    #[test]
+    fn test_rheo_package_is_built_in() {
+        let settings = WorldSettings::default();
+        let source = "#import \"@rheo/rheo:0.1.0\": *\n#assert(is-rheo-pdf())\n#lemma[A]\n";
+        assert!(compile(source, &settings));
+        assert!(!compile("#import \"@rheo/rheo:9.9.9\": *\n", &settings));
+
+        let manifest: toml::Table = toml::from_str(RHEO_PACKAGE_FILES[0].1).unwrap();
+        assert_eq!(
+            manifest["package"]["version"].as_str(),
+            Some(RHEO_PACKAGE_VERSION)
+        );
+    }
+
+    #[test]
+    fn test_inject_template_is_opt_in() {
+        let source = "#lemma[A]\n";
+        assert!(!compile(source, &WorldSettings::default()));
+        let settings = WorldSettings {
+            inject_template: true,
+            ..WorldSettings::default()
+        };
+        assert!(compile(source, &settings));
+    }
+}
@@ -0,0 +1,137 @@
+// Client-side search for rheo HTML sites.
+//
+// Loads search-index.json (next to this script) on first use and renders a
+// search box into the element with id "rheo-search", or at the top of <body>
+// if the page has no such element. No dependencies.
+(function () {
+  "use strict";
+
+  var script = document.currentScript;
+  var base = new URL(".", script ? script.src : document.baseURI);
+  var index = null;
+  var MAX_RESULTS = 10;
+  var SNIPPET_RADIUS = 60;
+
+  function loadIndex() {
+    if (!index) {
+      index = fetch(new URL("search-index.json", base))
+        .then(function (response) {
+          return response.ok ? response.json() : [];
+        })
+        .catch(function () {
+          return [];
+        });
+    }
+    return index;
+  }
+
+  function score(page, terms) {
+    var title = page.title.toLowerCase();
+    var headings = page.headings.join("\n").toLowerCase();
+    var text = page.text.toLowerCase();
+    var total = 0;
+    for (var i = 0; i < terms.length; i++) {
+      var term = terms[i];
+      var termScore =
+        (title.indexOf(term) >= 0 ? 10 : 0) +
+        (headings.indexOf(term) >= 0 ? 5 : 0) +
+        (text.indexOf(term) >= 0 ? 1 : 0);
+      // Every term has to match somewhere
+      if (termScore === 0) {
+        return 0;
+      }
+      total += termScore;
+    }
+    return total;
+  }
+
+  function snippet(text, term) {
+    var at = text.toLowerCase().indexOf(term);
+    if (at < 0) {
+      return text.slice(0, 2 * SNIPPET_RADIUS);
+    }
+    var start = Math.max(0, at - SNIPPET_RADIUS);
+    var end = Math.min(text.length, at + term.length + SNIPPET_RADIUS);
+    return (start > 0 ? "…" : "") + text.slice(start, end) + (end < text.length ? "…" : "");
+  }
+
+  function render(results, list, terms) {
+    list.textContent = "";
+    results.forEach(function (page) {
+      var item = document.createElement("li");
+      var link = document.createElement("a");
+      link.href = new URL(page.url, base).href;
+      link.textContent = page.title;
+      var excerpt = document.createElement("p");
+      excerpt.textContent = snippet(page.text, terms[0]);
+      item.appendChild(link);
+      item.appendChild(excerpt);
+      list.appendChild(item);
+    });
+  }
+
+  function search(query, list) {
+    var terms = query.toLowerCase().split(/\s+/).filter(Boolean);
+    if (terms.length === 0) {
+      list.textContent = "";
+      return;
+    }
+    loadIndex().then(function (pages) {
+      var results = pages
+        .map(function (page) {
+          return { page: page, score: score(page, terms) };
+        })
+        .filter(function (result) {
+          return result.score > 0;
+        })
+        .sort(function (a, b) {
+          return b.score - a.score;
+        })
+        .slice(0, MAX_RESULTS)
+        .map(function (result) {
+          return result.page;
+        });
+      render(results, list, terms);
+    });
+  }
+
+  function init() {
+    var container = document.getElementById("rheo-search");
+    if (!container) {
+      container = document.createElement("div");
+      container.id = "rheo-search";
+      document.body.insertBefore(container, document.body.firstChild);
+    }
+    container.classList.add("rheo-search");
+    container.setAttribute("role", "search");
+
+    var input = document.createElement("input");
+    input.type = "search";
+    input.placeholder = "Search";
+    input.setAttribute("aria-label", "Search");
+    var list = document.createElement("ol");
+    list.className = "rheo-search-results";
+    container.appendChild(input);
+    container.appendChild(list);
+
+    input.addEventListener("focus", loadIndex);
+    input.addEventListener("input", function () {
+      search(input.value, list);
+    });
+    document.addEventListener("keydown", function (event) {
+      if (event.key === "/" && document.activeElement !== input) {
+        event.preventDefault();
+        input.focus();
+      } else if (event.key === "Escape" && document.activeElement === input) {
+        input.value = "";
+        list.textContent = "";
+      }
+    });
+  }
+
+  if (document.readyState === "loading") {
+    document.addEventListener("DOMContentLoaded", init);
+  } else {
+    init();
+  }
+})();
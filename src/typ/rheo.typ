@@ -1,3 +1,6 @@
+// rheo's helpers, served by rheo as the built-in `@rheo/rheo:0.1.0` package:
+// #import "@rheo/rheo:0.1.0": *
+
 // Get the rheo output format, with fallback to Typst's target()
 // Returns: "epub", "html", "pdf" when compiled with rheo
 //          "html" or "paged" when compiled with vanilla Typst
@@ -24,6 +27,3 @@
 #let rheo_template(doc) = context {
   doc
 }
-
-// Libertinus Serif is embedded in Typst, so we can rely on it always being available. Any subsequent font declarations will override this. 
-#set text(font: "Libertinus Serif")
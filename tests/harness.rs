@@ -20,6 +20,7 @@ use std::path::PathBuf;
 #[test_case("tests/cases/code_blocks_with_links")]
 #[test_case("tests/cases/cross_directory_links")]
 #[test_case("tests/cases/epub_inferred_spine")]
+#[test_case("tests/cases/html_spine")]
 #[test_case("tests/cases/link_path_edge_cases")]
 #[test_case("tests/cases/link_transformation")]
 #[test_case("tests/cases/links_with_fragments")]
@@ -84,7 +85,8 @@ fn run_test_case(name: &str) {
     };
 
     // Load project from isolated copy
-    let project = ProjectConfig::from_path(&project_path, None).expect("Failed to load project");
+    let project =
+        ProjectConfig::from_path(&project_path, None, false).expect("Failed to load project");
     let config = RheoConfig::load(&project.root);
 
     // Get declared formats from test case (respects markers for single-file tests)
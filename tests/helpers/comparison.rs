@@ -30,10 +30,7 @@ pub fn verify_pdf_output(test_name: &str, actual_dir: &Path) {
 
     for_each_file_with_ext(actual_dir, "pdf", |entry| {
         let rel_path = entry.path().strip_prefix(actual_dir).unwrap();
-        let metadata_file = ref_dir.join(format!(
-            "{}.metadata.json",
-            rel_path.file_stem().unwrap().to_string_lossy()
-        ));
+        let metadata_file = ref_dir.join(rel_path.with_extension("metadata.json"));
 
         if !metadata_file.exists() {
             panic!(
@@ -771,9 +768,8 @@ fn validate_pdf_assets(reference_dir: &Path, actual_dir: &Path) -> Result<(), St
     })
     .into_iter()
     .filter_map(|p| {
-        p.file_stem()
-            .and_then(|s| s.to_str())
-            .and_then(|s| s.strip_suffix(".metadata"))
+        p.to_str()
+            .and_then(|s| s.strip_suffix(".metadata.json"))
             .map(|s| format!("{}.pdf", s))
     })
     .collect();
@@ -154,11 +154,12 @@ pub fn update_pdf_references(test_name: &str, actual_dir: &Path) -> Result<(), S
                 .strip_prefix(actual_dir)
                 .map_err(|e| format!("Failed to get relative path: {}", e))?;
 
-            // Save metadata JSON
-            let metadata_file = ref_dir.join(format!(
-                "{}.metadata.json",
-                rel_path.file_stem().unwrap().to_string_lossy()
-            ));
+            // Save metadata JSON (mirroring nested output directories)
+            let metadata_file = ref_dir.join(rel_path.with_extension("metadata.json"));
+            if let Some(parent) = metadata_file.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create PDF reference directory: {}", e))?;
+            }
 
             let json = serde_json::to_string_pretty(&metadata)
                 .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
@@ -249,11 +250,12 @@ pub fn update_epub_references(test_name: &str, actual_dir: &Path) -> Result<(),
                 .strip_prefix(actual_dir)
                 .map_err(|e| format!("Failed to get relative path: {}", e))?;
 
-            // Save metadata JSON
-            let metadata_file = ref_dir.join(format!(
-                "{}.metadata.json",
-                rel_path.file_stem().unwrap().to_string_lossy()
-            ));
+            // Save metadata JSON (mirroring nested output directories)
+            let metadata_file = ref_dir.join(rel_path.with_extension("metadata.json"));
+            if let Some(parent) = metadata_file.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create PDF reference directory: {}", e))?;
+            }
 
             let json = serde_json::to_string_pretty(&metadata)
                 .map_err(|e| format!("Failed to serialize metadata: {}", e))?;